@@ -0,0 +1,88 @@
+use std::fmt;
+
+/// # ConfigErrorKind
+///
+/// Enumeration of the different Problems that can be found
+/// while reading and checking the Configuration Files.
+#[derive(Clone, Copy, PartialEq)]
+pub enum ConfigErrorKind {
+    Io, // File could not be opened or read
+    Malformed, // Line does not follow the Name=Value Rule or a Mapping is broken
+    UnknownKey, // Name is not known to SLMS
+    InvalidValue, // Value can not be converted into the required Type
+    OutOfRange, // Value was converted but is outside of the allowed Range
+    Missing, // Required Setting was not configured
+    PathNotFound, // Configured File or Directory does not exist
+    PathNotAccessible, // Configured File or Directory can not be read / written
+    InterfaceNotFound, // Configured Network Interface is not available
+}
+
+impl ConfigErrorKind {
+    /// Returns a short printable Name of the Error Kind
+    pub fn get(&self) -> &'static str {
+        match *self {
+            ConfigErrorKind::Io => "io",
+            ConfigErrorKind::Malformed => "malformed",
+            ConfigErrorKind::UnknownKey => "unknown key",
+            ConfigErrorKind::InvalidValue => "invalid value",
+            ConfigErrorKind::OutOfRange => "out of range",
+            ConfigErrorKind::Missing => "missing",
+            ConfigErrorKind::PathNotFound => "not found",
+            ConfigErrorKind::PathNotAccessible => "not accessible",
+            ConfigErrorKind::InterfaceNotFound => "unknown interface",
+        }
+    }
+}
+
+/// # ConfigError
+///
+/// Describes a single Problem found inside a Configuration File.
+/// Holds the File and Line the Problem was found in, the Name of
+/// the Setting and a human readable Reason.
+/// A Line of 0 means the Problem is not bound to a specific Line,
+/// e.g. a Setting that is missing completely.
+#[derive(Clone)]
+pub struct ConfigError {
+    pub kind: ConfigErrorKind, // What kind of Problem this is
+    pub file: String, // Path to the Configuration File
+    pub line: usize, // Line Number inside the File (starting at 1)
+    pub key: String, // Name of the Setting
+    pub reason: String, // Human readable Description
+}
+
+impl ConfigError {
+    /// Creates a new ConfigError with the given Values
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - Kind of the Problem
+    /// * `file` - Path to the Configuration File
+    /// * `line` - Line Number or 0 if not bound to a Line
+    /// * `key` - Name of the Setting
+    /// * `reason` - Description of the Problem
+    pub fn new(kind: ConfigErrorKind, file: &str, line: usize, key: &str, reason: &str) -> ConfigError {
+        ConfigError {
+            kind,
+            file: file.to_string(),
+            line,
+            key: key.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.line > 0 {
+            write!(f, "{}:{}: ", self.file, self.line)?;
+        } else {
+            write!(f, "{}: ", self.file)?;
+        }
+
+        if self.key.is_empty() {
+            write!(f, "{}: {}", self.kind.get(), self.reason)
+        } else {
+            write!(f, "{} ({}): {}", self.key, self.kind.get(), self.reason)
+        }
+    }
+}
@@ -2,13 +2,101 @@ use std::io::BufReader;
 use std::io::BufRead;
 use std::fs::File;
 use std::fs;
+use std::path::Path;
+use std::ffi::CString;
+use std::net::IpAddr;
 use uuid::Uuid;
 use pnet::datalink;
 use sys_info::{os_release, os_type};
+use libc;
+use regex::Regex;
+
+use media::MediaType;
+use tools::{AccessLogFormat, LogModule, LogOutput};
 
 use super::serverconfiguration::ServerConfiguration;
 use super::rendererconfiguration::RendererConfiguration;
 use super::rendererconfiguration::SourceTargetMap;
+use super::configerror::{ConfigError, ConfigErrorKind};
+use super::shareconfiguration::ShareConfiguration;
+use super::network::Network;
+use super::schedule::{Schedule, SchedulePolicy};
+
+/// Names of all Settings known inside the Server Configuration
+const SERVER_KEYS: &[&str] = &[
+    "servername",
+    "rendererdir",
+    "defaultrenderer",
+    "thumbnaildir",
+    "serverport",
+    "serverinterface",
+    "folders",
+    "share",
+    "sharename",
+    "sharetype",
+    "shareexclude",
+    "shareexcluderegex",
+    "sharemaxdepth",
+    "sharefollowsymlinks",
+    "shareallowednetworks",
+    "sharedeniednetworks",
+    "generatethumbnails",
+    "logfile",
+    "loglevel",
+    "loglevelssdp",
+    "logleveldatabase",
+    "loglevelhttp",
+    "loglevelupnp",
+    "logoutput",
+    "logrotatesize",
+    "logrotatedaily",
+    "logretention",
+    "accesslog",
+    "accesslogformat",
+    "databasepath",
+    "scanthreads",
+    "keepalivetimeout",
+    "maxkeepaliverequests",
+    "httpthreads",
+    "maxstreams",
+    "maxstreamsperclient",
+    "sockettimeout",
+    "apitoken",
+    "playbackthreshold",
+    "bookmarksperrenderer",
+    "allowednetworks",
+    "deniednetworks",
+];
+
+/// Names of all Settings known inside a Renderer Configuration
+const RENDERER_KEYS: &[&str] = &[
+    "displayname",
+    "useragentsearchstring",
+    "remoteipaddress",
+    "fileextensions",
+    "filextensions",
+    "conmap",
+    "transcodecontainer",
+    "audiochannels",
+    "transcodeenabled",
+    "transcodeaudioenabled",
+    "transcodevideoenabled",
+    "transcodecodec",
+    "audiolanguage",
+    "subtitleconnection",
+    "encodesubtitles",
+    "titleinsteadofname",
+    "hidefileextension",
+    "muxtomatch",
+    "allowedpath",
+    "deniedpath",
+    "allowedmediatypes",
+    "maxrating",
+    "hideunrated",
+    "streamschedule",
+    "hideoutsideschedule",
+    "scheduleendpolicy",
+];
 
 /// # ConfigurationHandler
 ///
@@ -16,17 +104,19 @@ use super::rendererconfiguration::SourceTargetMap;
 /// Files and provides the corresponding Structures to
 /// parts of the Software where these are required.
 ///
+/// Every Problem found while parsing is collected as ConfigError
+/// inside the errors List, so the caller can decide how to report
+/// them. Problems that prevent the Server from running will also
+/// cause parse() to return false.
+///
 /// # TO-DO
 /// - Determine what Path to use when no share Folder is given -> Security Risk to share whole File System
-/// - Add Checks to determine if Configuration is set AND USEABLE / AVAILABLE -> Check Path / File Access, Interfaces, etc.
-/// - Handle File System Errors -> Not Found, Permission Denied...
-/// - Add check for Renderers Configuration
-/// - Replace all Unwraps with error checks
 pub struct ConfigurationHandler {
     pub server_configuration: ServerConfiguration, // Server Configurations
     pub renderer_configurations: Vec<RendererConfiguration>, // List of Renderer Configurations
     pub default_index: usize, // Position of the Default Renderer inside the renderer_configurations List
     pub cfg_file_path: String, // Path to the Configuration File
+    pub errors: Vec<ConfigError>, // Problems found while parsing the Configuration Files
 }
 
 impl ConfigurationHandler {
@@ -38,15 +128,45 @@ impl ConfigurationHandler {
             renderer_configurations: Vec::new(),
             default_index: 0,
             cfg_file_path: String::from("/etc/slms/server.cfg"),
+            errors: Vec::new(),
         }
     }
 
+    /// Returns the Configuration of the Renderer sending a Request.
+    /// Renderers are identified by their IP Address first and by
+    /// their User Agent second. Everything else gets the Default
+    /// Renderer. Returns None if there is no Renderer at all.
+    ///
+    /// # Arguments
+    ///
+    /// * `user_agent` - Value of the "User-Agent" Header
+    /// * `address` - Address of the Renderer
+    pub fn get_renderer(&self, user_agent: &str, address: &IpAddr) -> Option<&RendererConfiguration> {
+        let by_address = self.renderer_configurations
+            .iter()
+            .find(|renderer| renderer.remote_ip.parse::<IpAddr>().ok() == Some(*address));
+
+        if by_address.is_some() {
+            return by_address;
+        }
+
+        let by_user_agent = self.renderer_configurations.iter().find(|renderer| {
+            renderer
+                .user_agent_search
+                .iter()
+                .any(|search| !search.is_empty() && user_agent.contains(search.as_str()))
+        });
+
+        by_user_agent.or_else(|| self.renderer_configurations.get(self.default_index))
+    }
+
     pub fn clone(&self) -> ConfigurationHandler {
         ConfigurationHandler {
             server_configuration: self.server_configuration.clone(),
             renderer_configurations: self.renderer_configurations.clone(),
             default_index: self.default_index,
             cfg_file_path: self.cfg_file_path.clone(),
+            errors: self.errors.clone(),
         }
     }
 
@@ -54,72 +174,40 @@ impl ConfigurationHandler {
     /// the given Path. The server_configuration Structure
     /// will than hold the readed Configuration.
     /// If there was an Error this function returns false,
-    /// and true if everything went well.
+    /// and true if everything went well. Any Problem found
+    /// is stored in the errors List, including those that
+    /// do not prevent the Server from running.
     pub fn parse(&mut self, cfg_path: &str) -> bool {
         println!("Reading Configuration: {}", cfg_path);
 
         self.cfg_file_path = cfg_path.to_string();
+        self.errors.clear();
+        self.renderer_configurations.clear();
         let mut success = true;
+        let mut key_lines: Vec<(String, usize)> = Vec::new();
+        let mut share_lines: Vec<(&str, usize)> = Vec::new();
+        let mut current_share: Option<usize> = None;
 
-        // Open Cfg File
-        let cfg_file = match File::open(&self.cfg_file_path) {
+        // Read all Lines
+        let lines = match self.read_lines(cfg_path, SERVER_KEYS) {
             Ok(value) => value,
-            Err(e) => {
-                println!("Unable to open Server Configuration! Reason: {}", e);
-                return false;
-            }
+            Err(_) => return false,
         };
-        let file = BufReader::new(&cfg_file);
-
-        // Read all Lines
-        for line in file.lines() {
-            let line_res = match line {
-                Ok(value) => value,
-                Err(e) => {
-                    println!("Unable to read Configuration Line! Reason: {}", e);
-                    continue;
-                }
-            };
-            let mut name = line_res.clone();
 
-            // Skip Comments
-            if name.len() <= 1 || &name[0..1] == "#" || &name[0..1] == "\n" {
-                continue;
-            }
-
-            name = name[..match name.find("=") {
-                            Some(value) => value,
-                            None => {
-                                println!("Unable to find \"=\" in Name with {}", name);
-                                continue;
-                            }
-                        }].trim()
-                .to_string();
-            let mut value = line_res;
-            value = value[(match value.find("=") {
-                               Some(value) => value,
-                               None => {
-                                   println!("Unable to find \"=\" in Value with {}", value);
-                                   continue;
-                               }
-                           }) + 1..]
-                .trim()
-                .to_string();
-
-            if value.len() == 0 {
-                continue;
-            }
+        for (line_nr, name, value) in lines {
+            key_lines.push((name.clone(), line_nr));
 
             // Get Settings according their names
-            match name.to_lowercase().as_ref() {
-                "servername" => self.server_configuration.server_name = value.to_string(),
-                "rendererdir" => self.server_configuration.renderer_dir = value.to_string(),
-                "defaultrenderer" => {
-                    self.server_configuration.default_renderer_path = value.to_string()
-                }
-                "thumbnaildir" => self.server_configuration.thumbnail_dir = value.to_string(),
+            match name.as_ref() {
+                "servername" => self.server_configuration.server_name = value,
+                "rendererdir" => self.server_configuration.renderer_dir = value,
+                "defaultrenderer" => self.server_configuration.default_renderer_path = value,
+                "thumbnaildir" => self.server_configuration.thumbnail_dir = value,
                 "serverport" => {
-                    self.server_configuration.server_port = value.parse::<u16>().unwrap()
+                    match self.parse_number(cfg_path, line_nr, "ServerPort", &value, 1, 65535) {
+                        Some(port) => self.server_configuration.server_port = port as u16,
+                        None => success = false,
+                    }
                 }
                 "serverinterface" => {
                     self.server_configuration.server_interface = value.to_string();
@@ -127,153 +215,346 @@ impl ConfigurationHandler {
                     for iface in datalink::interfaces() {
                         if iface.name == value {
                             found = true;
-                            self.server_configuration.server_ip = match iface.ips.get(0) {
-                                Some(value) => {
-                                    let ip = value.to_string();
-                                    ip[..match ip.find("/") {
-                                           Some(position) => position,
-                                           None => ip.len(),
-                                       }].to_string()
-                                }
+                            self.server_configuration.server_ip = match iface.ips.first() {
+                                Some(ip) => ConfigurationHandler::strip_prefix(&ip.to_string()),
                                 None => String::new(),
                             };
                         }
                     }
 
                     if !found {
-                        println!("The given Network Interface {} is not  available!", value);
+                        self.errors.push(ConfigError::new(
+                            ConfigErrorKind::InterfaceNotFound,
+                            cfg_path,
+                            line_nr,
+                            "ServerInterface",
+                            &format!(
+                                "Network Interface {} is not available. Falling back to default!",
+                                value
+                            ),
+                        ));
                         self.server_configuration.server_interface = String::new();
                         self.server_configuration.server_ip = String::new();
                     }
                 }
                 "folders" => {
-                    self.server_configuration.share_dirs =
-                        value.split(";").map(|s| s.to_string()).collect()
+                    for folder in value.split(';').map(|s| s.trim()).filter(|s| !s.is_empty()) {
+                        self.server_configuration.shares.push(ShareConfiguration::new(folder));
+                        share_lines.push(("Folders", line_nr));
+                    }
+                }
+                "share" => {
+                    self.server_configuration.shares.push(ShareConfiguration::new(&value));
+                    share_lines.push(("Share", line_nr));
+                    current_share = Some(self.server_configuration.shares.len() - 1);
+                }
+                "sharename" | "sharetype" | "shareexclude" | "shareexcluderegex" |
+                "sharemaxdepth" | "sharefollowsymlinks" | "shareallowednetworks" | "sharedeniednetworks" => {
+                    match current_share {
+                        Some(index) => {
+                            if !self.parse_share_setting(cfg_path, line_nr, &name, &value, index) {
+                                success = false;
+                            }
+                        }
+                        None => {
+                            self.errors.push(ConfigError::new(
+                                ConfigErrorKind::Malformed,
+                                cfg_path,
+                                line_nr,
+                                &name,
+                                "Share Settings need to follow a \"Share\" Line",
+                            ));
+                            success = false;
+                        }
+                    }
                 }
                 "generatethumbnails" => {
-                    if value == "true" || value == "1" {
-                        self.server_configuration.generate_thumbnails = true;
-                    } else {
-                        self.server_configuration.generate_thumbnails = false;
-                    }
-                } 
-                "logfile" => self.server_configuration.log_path = value.to_string(),
-                "loglevel" => self.server_configuration.log_level = value.parse::<u8>().unwrap(),
-                "databasepath" => self.server_configuration.media_db_path = value.to_string(),
-                _ => println!("Notice: Unknown Name - Value Pair: {} - {}", name, value),
+                    match self.parse_bool(cfg_path, line_nr, "GenerateThumbnails", &value) {
+                        Some(flag) => self.server_configuration.generate_thumbnails = flag,
+                        None => success = false,
+                    }
+                }
+                "logfile" => self.server_configuration.log_path = value,
+                "loglevel" => {
+                    match self.parse_number(cfg_path, line_nr, "LogLevel", &value, 0, 4) {
+                        Some(level) => self.server_configuration.log_level = level as u8,
+                        None => success = false,
+                    }
+                }
+                "loglevelssdp" | "logleveldatabase" | "loglevelhttp" | "loglevelupnp" => {
+                    let (key, module) = match name.as_ref() {
+                        "loglevelssdp" => ("LogLevelSSDP", LogModule::Ssdp),
+                        "logleveldatabase" => ("LogLevelDatabase", LogModule::Database),
+                        "loglevelhttp" => ("LogLevelHTTP", LogModule::Http),
+                        _ => ("LogLevelUPnP", LogModule::Upnp),
+                    };
+
+                    match self.parse_number(cfg_path, line_nr, key, &value, 0, 4) {
+                        Some(level) => self.server_configuration.log_levels.push((module, level as u8)),
+                        None => success = false,
+                    }
+                }
+                "logoutput" => {
+                    match value.to_lowercase().as_ref() {
+                        "file" => self.server_configuration.log_output = LogOutput::File,
+                        "syslog" => self.server_configuration.log_output = LogOutput::Syslog,
+                        "journald" => self.server_configuration.log_output = LogOutput::Journald,
+                        _ => {
+                            self.errors.push(ConfigError::new(
+                                ConfigErrorKind::InvalidValue,
+                                cfg_path,
+                                line_nr,
+                                "LogOutput",
+                                &format!("{} is not one of file, syslog or journald", value),
+                            ));
+                            success = false;
+                        }
+                    }
+                }
+                "logrotatesize" => {
+                    match self.parse_number(cfg_path, line_nr, "LogRotateSize", &value, 0, 102400) {
+                        Some(size) => self.server_configuration.log_rotate_size = size,
+                        None => success = false,
+                    }
+                }
+                "logrotatedaily" => {
+                    match self.parse_bool(cfg_path, line_nr, "LogRotateDaily", &value) {
+                        Some(flag) => self.server_configuration.log_rotate_daily = flag,
+                        None => success = false,
+                    }
+                }
+                "logretention" => {
+                    match self.parse_number(cfg_path, line_nr, "LogRetention", &value, 0, 1000) {
+                        Some(count) => self.server_configuration.log_retention = count as usize,
+                        None => success = false,
+                    }
+                }
+                "accesslog" => self.server_configuration.access_log_path = value,
+                "accesslogformat" => {
+                    match value.to_lowercase().as_ref() {
+                        "combined" => self.server_configuration.access_log_format = AccessLogFormat::Combined,
+                        "json" => self.server_configuration.access_log_format = AccessLogFormat::Json,
+                        _ => {
+                            self.errors.push(ConfigError::new(
+                                ConfigErrorKind::InvalidValue,
+                                cfg_path,
+                                line_nr,
+                                "AccessLogFormat",
+                                &format!("{} is not one of combined or json", value),
+                            ));
+                            success = false;
+                        }
+                    }
+                }
+                "databasepath" => self.server_configuration.media_db_path = value,
+                "scanthreads" => {
+                    match self.parse_number(cfg_path, line_nr, "ScanThreads", &value, 0, 64) {
+                        Some(threads) => self.server_configuration.scan_threads = threads as usize,
+                        None => success = false,
+                    }
+                }
+                "keepalivetimeout" => {
+                    match self.parse_number(cfg_path, line_nr, "KeepAliveTimeout", &value, 0, 3600) {
+                        Some(seconds) => self.server_configuration.keep_alive_timeout = seconds,
+                        None => success = false,
+                    }
+                }
+                "maxkeepaliverequests" => {
+                    match self.parse_number(cfg_path, line_nr, "MaxKeepAliveRequests", &value, 1, 100000) {
+                        Some(count) => self.server_configuration.max_keep_alive_requests = count as usize,
+                        None => success = false,
+                    }
+                }
+                "httpthreads" => {
+                    match self.parse_number(cfg_path, line_nr, "HttpThreads", &value, 1, 1024) {
+                        Some(count) => self.server_configuration.http_threads = count as usize,
+                        None => success = false,
+                    }
+                }
+                "maxstreams" => {
+                    match self.parse_number(cfg_path, line_nr, "MaxStreams", &value, 1, 1024) {
+                        Some(count) => self.server_configuration.max_streams = count as usize,
+                        None => success = false,
+                    }
+                }
+                "maxstreamsperclient" => {
+                    match self.parse_number(cfg_path, line_nr, "MaxStreamsPerClient", &value, 1, 1024) {
+                        Some(count) => self.server_configuration.max_streams_per_client = count as usize,
+                        None => success = false,
+                    }
+                }
+                "sockettimeout" => {
+                    match self.parse_number(cfg_path, line_nr, "SocketTimeout", &value, 1, 3600) {
+                        Some(seconds) => self.server_configuration.socket_timeout = seconds,
+                        None => success = false,
+                    }
+                }
+                "apitoken" => self.server_configuration.api_token = value,
+                "playbackthreshold" => {
+                    match self.parse_number(cfg_path, line_nr, "PlaybackThreshold", &value, 1, 100) {
+                        Some(percent) => self.server_configuration.playback_threshold = percent,
+                        None => success = false,
+                    }
+                }
+                "bookmarksperrenderer" => {
+                    match self.parse_bool(cfg_path, line_nr, "BookmarksPerRenderer", &value) {
+                        Some(flag) => self.server_configuration.bookmarks_per_renderer = flag,
+                        None => success = false,
+                    }
+                }
+                "allowednetworks" => {
+                    match self.parse_networks(cfg_path, line_nr, "AllowedNetworks", &value) {
+                        Some(networks) => self.server_configuration.access_rules.allowed.extend(networks),
+                        None => success = false,
+                    }
+                }
+                "deniednetworks" => {
+                    match self.parse_networks(cfg_path, line_nr, "DeniedNetworks", &value) {
+                        Some(networks) => self.server_configuration.access_rules.denied.extend(networks),
+                        None => success = false,
+                    }
+                }
+                _ => (),
             }
         }
 
-        // Check the Configuration if everything was set and is usable / available -> Back to defaults if not
-        if self.server_configuration.default_renderer_path.len() == 0 {
+        // Check the Configuration if everything was set -> Back to defaults if not
+        if self.server_configuration.default_renderer_path.is_empty() {
             self.server_configuration.default_renderer_path =
                 String::from("/etc/slms/renderer/default.cfg");
             success = false;
-            println!("Attention: No Default Renderer was configurated. Falling Back to default!");
+            self.push_missing("DefaultRenderer", "No Default Renderer was configurated");
         }
 
-        if self.server_configuration.log_path.len() == 0 {
+        if self.server_configuration.log_path.is_empty() {
             self.server_configuration.log_path = String::from("/var/log/slms.log");
             success = false;
-            println!("Attention: No Log File Path was configurated. Falling Back to default!");
+            self.push_missing("LogFile", "No Log File Path was configurated");
         }
 
-        if self.server_configuration.renderer_dir.len() == 0 {
-            self.server_configuration.default_renderer_path = String::from("/etc/slms/renderer/");
+        if self.server_configuration.renderer_dir.is_empty() {
+            self.server_configuration.renderer_dir = String::from("/etc/slms/renderer/");
             success = false;
-            println!(
-                "Attention: No Renderer Configuration Directory was configurated. Falling Back to default!"
+            self.push_missing(
+                "RendererDir",
+                "No Renderer Configuration Directory was configurated",
             );
         }
 
-        if self.server_configuration.server_name.len() == 0 {
+        if self.server_configuration.server_name.is_empty() {
             self.server_configuration.server_name = String::from("SLMS");
             success = false;
-            println!("Attention: No Server Name was configurated. Falling Back to default!");
+            self.push_missing("ServerName", "No Server Name was configurated");
         }
 
-        if self.server_configuration.share_dirs.len() == 0 {
-            self.server_configuration.share_dirs.push(String::from("/"));
+        if self.server_configuration.shares.is_empty() {
+            self.server_configuration.shares.push(ShareConfiguration::new("/"));
             success = false;
-            println!(
-                "Attention: No Shared Folder was configurated. Falling Back to default! -- High Security Risk --"
+            self.push_missing(
+                "Folders",
+                "No Shared Folder was configurated. -- High Security Risk --",
             );
         }
 
-        if self.server_configuration.thumbnail_dir.len() == 0 {
+        if self.server_configuration.thumbnail_dir.is_empty() {
             self.server_configuration.thumbnail_dir = String::from("/var/lib/slms/thumbnails/");
             success = false;
-            println!(
-                "Attention: No Thumbnail Directory was configurated. Falling Back to default!"
-            );
+            self.push_missing("ThumbnailDir", "No Thumbnail Directory was configurated");
         }
 
-        if self.server_configuration.server_interface.len() == 0 ||
-            self.server_configuration.server_ip.len() == 0
+        if self.server_configuration.server_interface.is_empty() ||
+            self.server_configuration.server_ip.is_empty()
         {
             let faces = datalink::interfaces();
-            let iface = match faces.get(0) {
+            let iface = match faces.first() {
                 Some(value) => value,
                 None => {
-                    println!("Attention: No Network Interface is available. Stopping!");
+                    self.push_missing(
+                        "ServerInterface",
+                        "No Network Interface is available. Stopping!",
+                    );
                     return false;
                 }
-
             };
 
-            self.server_configuration.server_ip = match iface.ips.get(0) {
-                Some(value) => {
-                    let ip = value.to_string();
-                    ip[..match ip.find("/") {
-                           Some(position) => position,
-                           None => ip.len(),
-                       }].to_string()
-                }
+            self.server_configuration.server_ip = match iface.ips.first() {
+                Some(value) => ConfigurationHandler::strip_prefix(&value.to_string()),
                 None => {
-                    println!("Attention: No IP Address could be obtained! Stopping!");
+                    self.push_missing(
+                        "ServerInterface",
+                        "No IP Address could be obtained! Stopping!",
+                    );
                     return false;
                 }
             };
         }
 
+        // Make sure all Files and Folders can be used
+        self.check_paths(&key_lines, &share_lines);
+
         // Parse all Renderers
-        let paths = match fs::read_dir(self.server_configuration.renderer_dir.clone()) {
+        let paths = match fs::read_dir(&self.server_configuration.renderer_dir) {
             Ok(value) => value,
-            Err(e) => {
-                println!(
-                    "Unable to read the Renderers Configuration Directory! Stopping! Reason: {}",
-                    e
-                );
-                return false;
-            }
+            Err(_) => return false,
         };
 
+        let mut default_found = false;
+
         for path in paths {
             match path {
                 Ok(element) => {
                     if element.path().is_file() {
-                        if self.parse_renderer(match element.path().to_str() {
-                            Some(value) => value,
-                            None => {
-                                println!("Unable to convert to str");
-                                continue;
-                            }
-                        }) == false
-                        {
+                        let renderer_path = element.path().to_string_lossy().to_string();
+                        let is_default = element.path() == Path::new(&self.server_configuration.default_renderer_path);
+
+                        if !self.parse_renderer(&renderer_path) {
                             success = false;
-                            println!("Attention: Unable to parse Renderer!");
+
+                            if is_default {
+                                let line_nr = key_lines
+                                    .iter()
+                                    .find(|entry| entry.0 == "defaultrenderer")
+                                    .map(|entry| entry.1)
+                                    .unwrap_or(0);
+                                self.errors.push(ConfigError::new(
+                                    ConfigErrorKind::InvalidValue,
+                                    cfg_path,
+                                    line_nr,
+                                    "DefaultRenderer",
+                                    &format!("Default Renderer {} contains invalid Settings", renderer_path),
+                                ));
+                                default_found = true;
+                            }
+                        } else if is_default {
+                            self.default_index = self.renderer_configurations.len() - 1;
+                            default_found = true;
                         }
                     }
                 }
-                Err(_) => {
-                    println!(
-                        "Attention: Unable to parse Renderer in Renderer Configuration Directory!"
-                    );
+                Err(e) => {
+                    let renderer_dir = self.server_configuration.renderer_dir.clone();
+                    self.errors.push(ConfigError::new(
+                        ConfigErrorKind::Io,
+                        &renderer_dir,
+                        0,
+                        "",
+                        &format!("Unable to read Renderer Configuration Entry: {}", e),
+                    ));
                     continue;
                 }
             }
         }
 
+        // Renderers that are not recognized need the Default Renderer to fall back to
+        if !default_found {
+            let default_path = self.server_configuration.default_renderer_path.clone();
+            self.push_missing(
+                "DefaultRenderer",
+                &format!("{} is not a Renderer Configuration inside the RendererDir", default_path),
+            );
+            success = false;
+        }
+
         //  Generate the Servers Tag
         self.server_configuration.server_tag =
             format!(
@@ -295,199 +576,684 @@ impl ConfigurationHandler {
     fn parse_renderer(&mut self, path: &str) -> bool {
         println!("Reading Renderer Configuration: {}", path);
 
-        let success = true;
+        let mut success = true;
         let mut rnd_cfg: RendererConfiguration = RendererConfiguration::new();
 
-        // Open Cfg File
-        let cfg_file = match File::open(path) {
+        // Read all Lines
+        let lines = match self.read_lines(path, RENDERER_KEYS) {
             Ok(value) => value,
-            Err(e) => {
-                println!(
-                    "Attention: Unable to open Renderer Configuration: {} - Reason: {}",
-                    path,
-                    e
-                );
-                return false;
-            }
+            Err(_) => return false,
         };
-        let file = BufReader::new(&cfg_file);
-
-        // Read all Lines
-        for line in file.lines() {
-            let line_res = match line {
-                Ok(value) => value,
-                Err(e) => {
-                    println!(
-                        "Attention: Unable to read Renderer Configuration Line! Reason: {}",
-                        e
-                    );
-                    return false;
-                }
-            };
-            let mut name = line_res.clone();
-
-            // Skip Comments
-            if name.len() <= 1 || &name[0..1] == "#" || &name[0..1] == "\n" {
-                continue;
-            }
-
-            name = name[..match name.find("=") {
-                            Some(value) => value,
-                            None => {
-                                println!("Unable to find \"=\" in Name for {}", name);
-                                return false;
-                            }
-                        }].trim()
-                .to_string();
-            let mut value = line_res;
-            value = value[(match value.find("=") {
-                               Some(value) => value,
-                               None => {
-                                   println!("Unable to find \"=\" in Value for {}", value);
-                                   return false;
-                               }
-                           }) + 1..]
-                .trim()
-                .to_string();
-
-            // Skip empty Values
-            if value.len() == 0 {
-                continue;
-            }
 
-            match name.to_lowercase().as_ref() {
+        for (line_nr, name, value) in lines {
+            match name.as_ref() {
                 "displayname" => rnd_cfg.display_name = value,
                 "useragentsearchstring" => rnd_cfg.user_agent_search.push(value),
-                "remoteipaddress" => rnd_cfg.remote_ip = value,
-                "filextensions" => {
-                    rnd_cfg.file_extensions = value.split(",").map(|s| s.to_string()).collect()
+                "remoteipaddress" => {
+                    if value.parse::<IpAddr>().is_err() {
+                        self.errors.push(ConfigError::new(
+                            ConfigErrorKind::InvalidValue,
+                            path,
+                            line_nr,
+                            "RemoteIpAddress",
+                            &format!("{} is not a valid IP Address", value),
+                        ));
+                        success = false;
+                    }
+                    rnd_cfg.remote_ip = value
+                }
+                "fileextensions" | "filextensions" => {
+                    rnd_cfg.file_extensions = value.split(',').map(|s| s.trim().to_string()).collect()
                 }
                 "conmap" => {
-                    let mut tmp_con: SourceTargetMap = SourceTargetMap::new();
-                    tmp_con.source = value[..match value.find(":") {
-                                               Some(value) => value,
-                                               None => {
-                                                   println!("Invalid ConMap Option!");
-                                                   return false;
-                                               }
-                                           }].to_string();
-                    tmp_con.target = value[(match value.find(":") {
-                                                Some(value) => value,
-                                                None => {
-                                                    println!("Invalid ConMap Option!");
-                                                    return false;
-                                                }
-                                            }) + 1..]
-                        .to_string();
-                    rnd_cfg.container_maps.push(tmp_con);
+                    match self.parse_map(path, line_nr, "ConMap", &value) {
+                        Some(map) => rnd_cfg.container_maps.push(map),
+                        None => success = false,
+                    }
                 }
                 "transcodecontainer" => rnd_cfg.transcode_container = value,
                 "audiochannels" => {
-                    rnd_cfg.audio_channels = match value.parse::<u8>() {
-                        Ok(value) => value,
-                        Err(_) => {
-                            println!("Invalid Audio Channel Number!");
-                            return false;
-                        }
+                    match self.parse_number(path, line_nr, "AudioChannels", &value, 1, 8) {
+                        Some(channels) => rnd_cfg.audio_channels = channels as u8,
+                        None => success = false,
                     }
                 }
                 "transcodeenabled" => {
-                    if value == "true" || value == "1" {
-                        rnd_cfg.transcode_enabled = true;
-                    } else {
-                        rnd_cfg.transcode_enabled = false;
+                    match self.parse_bool(path, line_nr, "TranscodeEnabled", &value) {
+                        Some(flag) => rnd_cfg.transcode_enabled = flag,
+                        None => success = false,
                     }
                 }
                 "transcodeaudioenabled" => {
-                    if value == "true" || value == "1" {
-                        rnd_cfg.transcode_audio_enabled = true;
-                    } else {
-                        rnd_cfg.transcode_audio_enabled = false;
+                    match self.parse_bool(path, line_nr, "TranscodeAudioEnabled", &value) {
+                        Some(flag) => rnd_cfg.transcode_audio_enabled = flag,
+                        None => success = false,
                     }
                 }
                 "transcodevideoenabled" => {
-                    if value == "true" || value == "1" {
-                        rnd_cfg.transcode_video_enabled = true;
-                    } else {
-                        rnd_cfg.transcode_video_enabled = false;
+                    match self.parse_bool(path, line_nr, "TranscodeVideoEnabled", &value) {
+                        Some(flag) => rnd_cfg.transcode_video_enabled = flag,
+                        None => success = false,
                     }
                 }
                 "transcodecodec" => {
-                    let mut tmp_codec: SourceTargetMap = SourceTargetMap::new();
-                    tmp_codec.source = value[..match value.find(":") {
-                                                 Some(value) => value,
-                                                 None => {
-                                                     println!("Invalid TranscodeCodec Option!");
-                                                     return false;
-                                                 }
-                                             }].to_string();
-                    tmp_codec.target = value[(match value.find(":") {
-                                                  Some(value) => value,
-                                                  None => {
-                                                      println!("Invalid TranscodeCodec Option!");
-                                                      return false;
-                                                  }
-                                              }) +
-                                                 1..]
-                        .to_string();
-                    rnd_cfg.transcode_codecs.push(tmp_codec);
+                    match self.parse_map(path, line_nr, "TranscodeCodec", &value) {
+                        Some(map) => rnd_cfg.transcode_codecs.push(map),
+                        None => success = false,
+                    }
                 }
                 "audiolanguage" => {
-                    rnd_cfg.audio_languages = value.split(",").map(|s| s.to_string()).collect()
+                    rnd_cfg.audio_languages = value.split(',').map(|s| s.trim().to_string()).collect()
                 }
                 "subtitleconnection" => {
-                    let mut tmp_sub: SourceTargetMap = SourceTargetMap::new();
-                    tmp_sub.source = value[..match value.find(":") {
-                                               Some(value) => value,
-                                               None => {
-                                                   println!("Invalid SubtitleConnection Option!");
-                                                   return false;
-                                               }
-                                           }].to_string();
-                    tmp_sub.target = value[(match value.find(":") {
-                                                Some(value) => value,
-                                                None => {
-                                                    println!("Invalid SubtitleConnection Option!");
-                                                    return false;
-                                                }
-                                            }) + 1..]
-                        .to_string();
-                    rnd_cfg.subtitle_connection.push(tmp_sub);
+                    match self.parse_map(path, line_nr, "SubtitleConnection", &value) {
+                        Some(map) => rnd_cfg.subtitle_connection.push(map),
+                        None => success = false,
+                    }
                 }
                 "encodesubtitles" => {
-                    if value == "true" || value == "1" {
-                        rnd_cfg.encode_subtitles = true;
-                    } else {
-                        rnd_cfg.encode_subtitles = false;
+                    match self.parse_bool(path, line_nr, "EncodeSubtitles", &value) {
+                        Some(flag) => rnd_cfg.encode_subtitles = flag,
+                        None => success = false,
                     }
                 }
                 "titleinsteadofname" => {
-                    if value == "true" || value == "1" {
-                        rnd_cfg.title_instead_of_name = true;
-                    } else {
-                        rnd_cfg.title_instead_of_name = false;
+                    match self.parse_bool(path, line_nr, "TitleInsteadOfName", &value) {
+                        Some(flag) => rnd_cfg.title_instead_of_name = flag,
+                        None => success = false,
                     }
                 }
                 "hidefileextension" => {
-                    if value == "true" || value == "1" {
-                        rnd_cfg.hide_file_extension = true;
-                    } else {
-                        rnd_cfg.hide_file_extension = false;
+                    match self.parse_bool(path, line_nr, "HideFileExtension", &value) {
+                        Some(flag) => rnd_cfg.hide_file_extension = flag,
+                        None => success = false,
                     }
                 }
                 "muxtomatch" => {
-                    if value == "true" || value == "1" {
-                        rnd_cfg.mux_to_match = true;
-                    } else {
-                        rnd_cfg.mux_to_match = false;
+                    match self.parse_bool(path, line_nr, "MuxToMatch", &value) {
+                        Some(flag) => rnd_cfg.mux_to_match = flag,
+                        None => success = false,
+                    }
+                }
+                "allowedpath" => {
+                    match self.parse_share_path(path, line_nr, "AllowedPath", &value) {
+                        Some(folder) => rnd_cfg.allowed_paths.push(folder),
+                        None => success = false,
+                    }
+                }
+                "deniedpath" => {
+                    match self.parse_share_path(path, line_nr, "DeniedPath", &value) {
+                        Some(folder) => rnd_cfg.denied_paths.push(folder),
+                        None => success = false,
+                    }
+                }
+                "allowedmediatypes" => {
+                    for name in value.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()) {
+                        match get_media_type(name) {
+                            Some(media_type) => rnd_cfg.media_types.push(media_type),
+                            None => {
+                                self.errors.push(ConfigError::new(
+                                    ConfigErrorKind::InvalidValue,
+                                    path,
+                                    line_nr,
+                                    "AllowedMediaTypes",
+                                    &format!("{} is not one of video, audio or pictures", name),
+                                ));
+                                success = false;
+                            }
+                        }
+                    }
+                }
+                "maxrating" => {
+                    match self.parse_number(path, line_nr, "MaxRating", &value, 0, 21) {
+                        Some(age) => rnd_cfg.max_rating = Some(age as u8),
+                        None => success = false,
+                    }
+                }
+                "hideunrated" => {
+                    match self.parse_bool(path, line_nr, "HideUnrated", &value) {
+                        Some(flag) => rnd_cfg.hide_unrated = flag,
+                        None => success = false,
+                    }
+                }
+                "streamschedule" => {
+                    match Schedule::parse(&value) {
+                        Some(schedule) => rnd_cfg.schedules.push(schedule),
+                        None => {
+                            self.errors.push(ConfigError::new(
+                                ConfigErrorKind::InvalidValue,
+                                path,
+                                line_nr,
+                                "StreamSchedule",
+                                &format!("{} is not a valid Schedule (e.g. mon-fri 07:00-19:30)", value),
+                            ));
+                            success = false;
+                        }
+                    }
+                }
+                "hideoutsideschedule" => {
+                    match self.parse_bool(path, line_nr, "HideOutsideSchedule", &value) {
+                        Some(flag) => rnd_cfg.hide_outside_schedule = flag,
+                        None => success = false,
+                    }
+                }
+                "scheduleendpolicy" => {
+                    match value.to_lowercase().as_ref() {
+                        "finish" => rnd_cfg.schedule_policy = SchedulePolicy::Finish,
+                        "cutoff" => rnd_cfg.schedule_policy = SchedulePolicy::CutOff,
+                        _ => {
+                            self.errors.push(ConfigError::new(
+                                ConfigErrorKind::InvalidValue,
+                                path,
+                                line_nr,
+                                "ScheduleEndPolicy",
+                                &format!("{} is not one of finish or cutoff", value),
+                            ));
+                            success = false;
+                        }
                     }
                 }
                 _ => (),
             }
         }
 
-        self.renderer_configurations.push(rnd_cfg);
+        if success {
+            self.renderer_configurations.push(rnd_cfg);
+        }
 
         success
     }
+
+    /// Reads all Settings of a Configuration File and returns them as
+    /// List of (Line Number, lowercase Name, Value). Comments and
+    /// empty Values are skipped. Lines without "=" and Names that are
+    /// not part of the given known Keys are reported as ConfigError.
+    /// Returns Err if the File can not be opened.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the Configuration File
+    /// * `known_keys` - List of lowercase Names that are allowed inside the File
+    fn read_lines(
+        &mut self,
+        path: &str,
+        known_keys: &[&str],
+    ) -> Result<Vec<(usize, String, String)>, ()> {
+        let cfg_file = match File::open(path) {
+            Ok(value) => value,
+            Err(e) => {
+                self.errors.push(ConfigError::new(
+                    ConfigErrorKind::Io,
+                    path,
+                    0,
+                    "",
+                    &format!("Unable to open Configuration File: {}", e),
+                ));
+                return Err(());
+            }
+        };
+        let file = BufReader::new(&cfg_file);
+        let mut result: Vec<(usize, String, String)> = Vec::new();
+
+        for (index, line) in file.lines().enumerate() {
+            let line_nr = index + 1;
+            let line = match line {
+                Ok(value) => value,
+                Err(e) => {
+                    self.errors.push(ConfigError::new(
+                        ConfigErrorKind::Io,
+                        path,
+                        line_nr,
+                        "",
+                        &format!("Unable to read Configuration Line: {}", e),
+                    ));
+                    return Err(());
+                }
+            };
+            let trimmed = line.trim();
+
+            // Skip Comments
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            let position = match trimmed.find('=') {
+                Some(value) => value,
+                None => {
+                    self.errors.push(ConfigError::new(
+                        ConfigErrorKind::Malformed,
+                        path,
+                        line_nr,
+                        trimmed,
+                        "Expected a Name=Value Pair",
+                    ));
+                    continue;
+                }
+            };
+            let original_name = trimmed[..position].trim();
+            let name = original_name.to_lowercase();
+            let value = trimmed[position + 1..].trim().to_string();
+
+            if !known_keys.contains(&name.as_str()) {
+                self.errors.push(ConfigError::new(
+                    ConfigErrorKind::UnknownKey,
+                    path,
+                    line_nr,
+                    original_name,
+                    "Unknown Setting will be ignored",
+                ));
+                continue;
+            }
+
+            // Skip empty Values
+            if value.is_empty() {
+                continue;
+            }
+
+            result.push((line_nr, name, value));
+        }
+
+        Ok(result)
+    }
+
+    /// Applies one of the "Share..." Settings to the Share at the given
+    /// Position. Returns false and records a ConfigError if the Value
+    /// can not be used.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the Configuration File
+    /// * `line_nr` - Line the Setting was found on
+    /// * `name` - lowercase Name of the Setting
+    /// * `value` - Value of the Setting
+    /// * `index` - Position of the Share inside the Server Configuration
+    fn parse_share_setting(
+        &mut self,
+        path: &str,
+        line_nr: usize,
+        name: &str,
+        value: &str,
+        index: usize,
+    ) -> bool {
+        match name {
+            "sharename" => self.server_configuration.shares[index].name = value.to_string(),
+            "sharetype" => {
+                let media_type = match get_media_type(value) {
+                    Some(media_type) => Some(media_type),
+                    None if value.eq_ignore_ascii_case("all") => None,
+                    None => {
+                        self.errors.push(ConfigError::new(
+                            ConfigErrorKind::InvalidValue,
+                            path,
+                            line_nr,
+                            "ShareType",
+                            &format!("{} is not one of video, audio, pictures or all", value),
+                        ));
+                        return false;
+                    }
+                };
+                self.server_configuration.shares[index].media_type = media_type;
+            }
+            "shareexclude" => {
+                self.server_configuration.shares[index].exclude_globs.push(value.to_string())
+            }
+            "shareexcluderegex" => {
+                match Regex::new(value) {
+                    Ok(regex) => self.server_configuration.shares[index].exclude_regex.push(regex),
+                    Err(e) => {
+                        self.errors.push(ConfigError::new(
+                            ConfigErrorKind::InvalidValue,
+                            path,
+                            line_nr,
+                            "ShareExcludeRegex",
+                            &format!("{} is not a valid Regular Expression: {}", value, e),
+                        ));
+                        return false;
+                    }
+                }
+            }
+            "sharemaxdepth" => {
+                match self.parse_number(path, line_nr, "ShareMaxDepth", value, 0, 1024) {
+                    Some(depth) => {
+                        self.server_configuration.shares[index].max_depth = Some(depth as usize)
+                    }
+                    None => return false,
+                }
+            }
+            "sharefollowsymlinks" => {
+                match self.parse_bool(path, line_nr, "ShareFollowSymlinks", value) {
+                    Some(flag) => self.server_configuration.shares[index].follow_symlinks = flag,
+                    None => return false,
+                }
+            }
+            "shareallowednetworks" => {
+                match self.parse_networks(path, line_nr, "ShareAllowedNetworks", value) {
+                    Some(networks) => {
+                        self.server_configuration.shares[index].access_rules.allowed.extend(networks)
+                    }
+                    None => return false,
+                }
+            }
+            "sharedeniednetworks" => {
+                match self.parse_networks(path, line_nr, "ShareDeniedNetworks", value) {
+                    Some(networks) => {
+                        self.server_configuration.shares[index].access_rules.denied.extend(networks)
+                    }
+                    None => return false,
+                }
+            }
+            _ => (),
+        }
+
+        true
+    }
+
+    /// Resolves the Name of a Share or an absolute Path into the Path
+    /// of a Folder. Returns None and records a ConfigError if the
+    /// Share is unknown.
+    fn parse_share_path(&mut self, path: &str, line_nr: usize, key: &str, value: &str) -> Option<String> {
+        if value.starts_with('/') {
+            return Some(value.to_string());
+        }
+
+        let share = self.server_configuration
+            .shares
+            .iter()
+            .find(|share| share.get_title() == value);
+
+        match share {
+            Some(share) => Some(share.path.clone()),
+            None => {
+                self.errors.push(ConfigError::new(
+                    ConfigErrorKind::InvalidValue,
+                    path,
+                    line_nr,
+                    key,
+                    &format!("{} is neither an absolute Path nor the Name of a Share", value),
+                ));
+                None
+            }
+        }
+    }
+
+    /// Parses a List of Networks in CIDR Notation separated by ";" or ",".
+    /// Returns None and records a ConfigError if one is invalid.
+    fn parse_networks(&mut self, path: &str, line_nr: usize, key: &str, value: &str) -> Option<Vec<Network>> {
+        let mut networks: Vec<Network> = Vec::new();
+
+        for entry in value.split(&[';', ','][..]).map(|s| s.trim()).filter(|s| !s.is_empty()) {
+            match Network::parse(entry) {
+                Some(network) => networks.push(network),
+                None => {
+                    self.errors.push(ConfigError::new(
+                        ConfigErrorKind::InvalidValue,
+                        path,
+                        line_nr,
+                        key,
+                        &format!("{} is not a valid Network (e.g. 192.168.1.0/24)", entry),
+                    ));
+                    return None;
+                }
+            }
+        }
+
+        Some(networks)
+    }
+
+    /// Converts a Value into a Number and makes sure it is inside the
+    /// given Range. Returns None and records a ConfigError if not.
+    fn parse_number(
+        &mut self,
+        path: &str,
+        line_nr: usize,
+        key: &str,
+        value: &str,
+        min: u64,
+        max: u64,
+    ) -> Option<u64> {
+        match value.parse::<u64>() {
+            Ok(number) => {
+                if number < min || number > max {
+                    self.errors.push(ConfigError::new(
+                        ConfigErrorKind::OutOfRange,
+                        path,
+                        line_nr,
+                        key,
+                        &format!("{} is not between {} and {}", number, min, max),
+                    ));
+                    None
+                } else {
+                    Some(number)
+                }
+            }
+            Err(_) => {
+                self.errors.push(ConfigError::new(
+                    ConfigErrorKind::InvalidValue,
+                    path,
+                    line_nr,
+                    key,
+                    &format!("{} is not a Number", value),
+                ));
+                None
+            }
+        }
+    }
+
+    /// Converts a Value into a Boolean. Allowed are "true", "false",
+    /// "1" and "0". Returns None and records a ConfigError if not.
+    fn parse_bool(&mut self, path: &str, line_nr: usize, key: &str, value: &str) -> Option<bool> {
+        match value.to_lowercase().as_ref() {
+            "true" | "1" => Some(true),
+            "false" | "0" => Some(false),
+            _ => {
+                self.errors.push(ConfigError::new(
+                    ConfigErrorKind::InvalidValue,
+                    path,
+                    line_nr,
+                    key,
+                    &format!("{} is not one of true, false, 1 or 0", value),
+                ));
+                None
+            }
+        }
+    }
+
+    /// Converts a "[source]:[target]" Value into a SourceTargetMap.
+    /// Both Parts need to be present. Returns None and records a
+    /// ConfigError if not.
+    fn parse_map(
+        &mut self,
+        path: &str,
+        line_nr: usize,
+        key: &str,
+        value: &str,
+    ) -> Option<SourceTargetMap> {
+        let parts: Vec<&str> = value.split(':').map(|s| s.trim()).collect();
+
+        if parts.len() != 2 || parts[0].is_empty() || parts[1].is_empty() {
+            self.errors.push(ConfigError::new(
+                ConfigErrorKind::Malformed,
+                path,
+                line_nr,
+                key,
+                &format!("{} does not follow the [source]:[target] Rule", value),
+            ));
+            return None;
+        }
+
+        let mut map: SourceTargetMap = SourceTargetMap::new();
+        map.source = parts[0].to_string();
+        map.target = parts[1].to_string();
+
+        Some(map)
+    }
+
+    /// Checks that all configured Files and Directories exist and
+    /// can be accessed the way SLMS needs them. Any Problem is
+    /// recorded as ConfigError.
+    ///
+    /// # Arguments
+    ///
+    /// * `key_lines` - Lines the Settings were found on
+    /// * `share_lines` - Setting and Line each Share was defined by
+    fn check_paths(&mut self, key_lines: &[(String, usize)], share_lines: &[(&str, usize)]) {
+        let cfg = self.server_configuration.clone();
+        let get_line = |key: &str| {
+            key_lines
+                .iter()
+                .find(|entry| entry.0 == key.to_lowercase())
+                .map(|entry| entry.1)
+                .unwrap_or(0)
+        };
+
+        for (share, &(key, line_nr)) in cfg.shares.iter().zip(share_lines) {
+            self.check_path(line_nr, key, &share.path, true, false);
+        }
+
+        self.check_path(get_line("RendererDir"), "RendererDir", &cfg.renderer_dir, true, false);
+        self.check_path(
+            get_line("DefaultRenderer"),
+            "DefaultRenderer",
+            &cfg.default_renderer_path,
+            false,
+            false,
+        );
+        self.check_path(get_line("ThumbnailDir"), "ThumbnailDir", &cfg.thumbnail_dir, true, true);
+
+        // The Database and Log File are created on demand. Check the Folder if they are missing
+        for &(key, file) in &[("DatabasePath", &cfg.media_db_path), ("LogFile", &cfg.log_path)] {
+            if Path::new(file).exists() {
+                self.check_path(get_line(key), key, file, false, true);
+            } else {
+                let parent = match Path::new(file).parent() {
+                    Some(value) => value.to_string_lossy().to_string(),
+                    None => String::from("/"),
+                };
+                self.check_path(get_line(key), key, &parent, true, true);
+            }
+        }
+    }
+
+    /// Checks a single File or Directory and records a ConfigError
+    /// if it does not exist, has the wrong Type or can not be accessed.
+    ///
+    /// # Arguments
+    ///
+    /// * `line_nr` - Line the Setting was found on
+    /// * `key` - Name of the Setting the Path belongs to
+    /// * `path` - Path to check
+    /// * `is_dir` - Path needs to be a Directory instead of a File
+    /// * `writable` - Path needs to be writable as well
+    fn check_path(
+        &mut self,
+        line_nr: usize,
+        key: &str,
+        path: &str,
+        is_dir: bool,
+        writable: bool,
+    ) {
+        let cfg_path = self.cfg_file_path.clone();
+
+        let metadata = match fs::metadata(path) {
+            Ok(value) => value,
+            Err(e) => {
+                self.errors.push(ConfigError::new(
+                    ConfigErrorKind::PathNotFound,
+                    &cfg_path,
+                    line_nr,
+                    key,
+                    &format!("{}: {}", path, e),
+                ));
+                return;
+            }
+        };
+
+        if is_dir && !metadata.is_dir() {
+            self.errors.push(ConfigError::new(
+                ConfigErrorKind::InvalidValue,
+                &cfg_path,
+                line_nr,
+                key,
+                &format!("{} is not a Directory", path),
+            ));
+            return;
+        } else if !is_dir && metadata.is_dir() {
+            self.errors.push(ConfigError::new(
+                ConfigErrorKind::InvalidValue,
+                &cfg_path,
+                line_nr,
+                key,
+                &format!("{} is a Directory", path),
+            ));
+            return;
+        }
+
+        let mut mode = libc::R_OK;
+        if is_dir {
+            mode |= libc::X_OK;
+        }
+        if writable {
+            mode |= libc::W_OK;
+        }
+
+        if !ConfigurationHandler::has_access(path, mode) {
+            self.errors.push(ConfigError::new(
+                ConfigErrorKind::PathNotAccessible,
+                &cfg_path,
+                line_nr,
+                key,
+                &format!(
+                    "{} is not {}",
+                    path,
+                    if writable { "readable and writable" } else { "readable" }
+                ),
+            ));
+        }
+    }
+
+    /// Uses access(2) to check if the current User has the given
+    /// Permissions on a Path.
+    fn has_access(path: &str, mode: libc::c_int) -> bool {
+        let c_path = match CString::new(path) {
+            Ok(value) => value,
+            Err(_) => return false,
+        };
+
+        unsafe { libc::access(c_path.as_ptr(), mode) == 0 }
+    }
+
+    /// Records a missing Setting of the Server Configuration
+    fn push_missing(&mut self, key: &str, reason: &str) {
+        let cfg_path = self.cfg_file_path.clone();
+        self.errors.push(ConfigError::new(
+            ConfigErrorKind::Missing,
+            &cfg_path,
+            0,
+            key,
+            reason,
+        ));
+    }
+
+    /// Removes the Network Prefix from an IP Network String (192.168.1.2/24)
+    fn strip_prefix(ip: &str) -> String {
+        ip[..match ip.find('/') {
+               Some(position) => position,
+               None => ip.len(),
+           }].to_string()
+    }
+}
+
+/// Converts the Name of a Media Type as used inside the Configuration
+/// Files into a MediaType. Returns None for unknown Names.
+///
+/// # Arguments
+///
+/// * `name` - Name of the Media Type, e.g. video or music
+fn get_media_type(name: &str) -> Option<MediaType> {
+    match name.to_lowercase().as_ref() {
+        "video" | "videos" => Some(MediaType::VIDEO),
+        "audio" | "music" => Some(MediaType::AUDIO),
+        "picture" | "pictures" | "photos" => Some(MediaType::PICTURE),
+        _ => None,
+    }
 }
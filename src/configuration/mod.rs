@@ -7,3 +7,12 @@ pub use self::rendererconfiguration::SourceTargetMap;
 
 mod configurationhandler;
 pub use self::configurationhandler::ConfigurationHandler;
+
+mod configerror;
+
+mod shareconfiguration;
+pub use self::shareconfiguration::ShareConfiguration;
+
+mod network;
+
+mod schedule;
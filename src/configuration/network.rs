@@ -0,0 +1,113 @@
+use std::net::IpAddr;
+
+/// # Network
+///
+/// An IPv4 or IPv6 Network in CIDR Notation, e.g. 192.168.1.0/24.
+/// A single Address without Prefix Length is a Network holding
+/// just that Host.
+#[derive(Clone)]
+pub struct Network {
+    address: IpAddr,
+    prefix_length: u8,
+}
+
+impl Network {
+    /// Parses a Network in CIDR Notation. Returns None if the Value
+    /// is no valid Network.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The Network, e.g. 10.0.0.0/8 or fd00::/8
+    pub fn parse(value: &str) -> Option<Network> {
+        let (address, prefix_length) = match value.find('/') {
+            Some(position) => (&value[..position], Some(&value[position + 1..])),
+            None => (value, None),
+        };
+
+        let address = normalize(address.trim().parse::<IpAddr>().ok()?);
+        let max_length = if address.is_ipv4() { 32 } else { 128 };
+
+        let prefix_length = match prefix_length {
+            Some(length) => length.trim().parse::<u8>().ok()?,
+            None => max_length,
+        };
+
+        if prefix_length > max_length {
+            return None;
+        }
+
+        Some(Network {
+            address,
+            prefix_length,
+        })
+    }
+
+    /// Checks if the Address belongs to this Network
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - The Address to check
+    pub fn contains(&self, address: &IpAddr) -> bool {
+        match (self.address, normalize(*address)) {
+            (IpAddr::V4(network), IpAddr::V4(address)) => {
+                let mask = u32::MAX.checked_shl(32 - u32::from(self.prefix_length)).unwrap_or(0);
+                u32::from(network) & mask == u32::from(address) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(address)) => {
+                let mask = u128::MAX.checked_shl(128 - u32::from(self.prefix_length)).unwrap_or(0);
+                u128::from(network) & mask == u128::from(address) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+/// # AccessRules
+///
+/// Networks that are allowed or denied to access the Server or
+/// a Share. Denied Networks win over allowed ones. If no Network
+/// is allowed explicitly, everyone not denied is allowed.
+#[derive(Clone)]
+pub struct AccessRules {
+    pub allowed: Vec<Network>, // Networks allowed to access. Empty to allow everyone
+    pub denied: Vec<Network>, // Networks never allowed to access
+}
+
+impl AccessRules {
+    /// Creates new Rules allowing everyone
+    pub fn new() -> AccessRules {
+        AccessRules {
+            allowed: Vec::new(),
+            denied: Vec::new(),
+        }
+    }
+
+    /// Checks if the Rules allow the Address to access
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - Address of the Client
+    pub fn is_allowed(&self, address: &IpAddr) -> bool {
+        if self.denied.iter().any(|network| network.contains(address)) {
+            return false;
+        }
+
+        self.allowed.is_empty() || self.allowed.iter().any(|network| network.contains(address))
+    }
+}
+
+/// Turns IPv4 Addresses mapped into IPv6 (::ffff:192.168.1.10) back
+/// into IPv4 Addresses, so they match IPv4 Networks.
+///
+/// # Arguments
+///
+/// * `address` - The Address
+fn normalize(address: IpAddr) -> IpAddr {
+    match address {
+        IpAddr::V6(value) => match value.to_ipv4_mapped() {
+            Some(mapped) => IpAddr::V4(mapped),
+            None => address,
+        },
+        _ => address,
+    }
+}
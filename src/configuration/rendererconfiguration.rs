@@ -1,3 +1,10 @@
+use std::path::Path;
+use std::time::Duration;
+
+use chrono::{DateTime, Local, Timelike};
+use media::{Item, MediaType};
+use super::schedule::{self, Schedule, SchedulePolicy};
+
 /// # SourceTargetMap
 ///
 /// This is an universal Structure that holds two Strings.
@@ -45,6 +52,14 @@ pub struct RendererConfiguration {
     pub title_instead_of_name: bool, // Use Meta-Data Title instead of File Name?
     pub hide_file_extension: bool, // Hide the File Extension from the user?
     pub mux_to_match: bool, // Remove any unneeded Tracks (and provide a single audio and video track) instead of all?
+    pub allowed_paths: Vec<String>, // Only show Content inside these Folders. Empty to show all Shares
+    pub denied_paths: Vec<String>, // Never show Content inside these Folders
+    pub media_types: Vec<MediaType>, // Media Types to show. Empty to show all
+    pub max_rating: Option<u8>, // Highest Age Rating to show. None to show everything
+    pub hide_unrated: bool, // Hide Items without a known Rating if a Maximum Rating is set?
+    pub schedules: Vec<Schedule>, // Time Windows the Renderer may stream in. Empty to stream any Time
+    pub hide_outside_schedule: bool, // Hide all Content while no Time Window is open?
+    pub schedule_policy: SchedulePolicy, // What happens to running Streams when a Time Window closes
 }
 
 impl RendererConfiguration {
@@ -69,6 +84,132 @@ impl RendererConfiguration {
             title_instead_of_name: false,
             hide_file_extension: false,
             mux_to_match: false,
+            allowed_paths: Vec::new(),
+            denied_paths: Vec::new(),
+            media_types: Vec::new(),
+            max_rating: None,
+            hide_unrated: true,
+            schedules: Vec::new(),
+            hide_outside_schedule: false,
+            schedule_policy: SchedulePolicy::Finish,
+        }
+    }
+
+    /// Checks if the Renderer may start a Stream at the given Time
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current Time
+    pub fn is_within_schedule(&self, now: &DateTime<Local>) -> bool {
+        let minute = schedule::get_minute_of_week(now);
+        self.schedules.is_empty() || self.schedules.iter().any(|schedule| schedule.contains(minute))
+    }
+
+    /// Checks if Streams started inside a Time Window may continue
+    /// after it closed
+    pub fn may_finish_streams(&self) -> bool {
+        self.schedule_policy == SchedulePolicy::Finish
+    }
+
+    /// Checks if all Content is hidden from the Renderer at the
+    /// given Time, because it is outside of its Schedule.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current Time
+    pub fn is_hidden_by_schedule(&self, now: &DateTime<Local>) -> bool {
+        self.hide_outside_schedule && !self.is_within_schedule(now)
+    }
+
+    /// Returns the Time left until a Stream started at the given Time
+    /// has to be cut off. Returns None if the Stream may finish.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current Time
+    pub fn get_stream_deadline(&self, now: &DateTime<Local>) -> Option<Duration> {
+        if self.schedule_policy != SchedulePolicy::CutOff || self.schedules.is_empty() {
+            return None;
         }
+
+        let minutes = schedule::get_open_minutes(&self.schedules, schedule::get_minute_of_week(now))?;
+        Some(Duration::from_secs((u64::from(minutes) * 60).saturating_sub(u64::from(now.second()))))
     }
+
+    /// Checks if the Folder at the given Path is shown to the Renderer.
+    /// Parents of allowed Folders are shown too, so the Renderer is
+    /// able to navigate down to them.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the Folder. Empty for virtual Folders
+    pub fn is_folder_visible(&self, path: &str) -> bool {
+        let path = Path::new(path);
+
+        if self.denied_paths.iter().any(|denied| path.starts_with(denied)) {
+            return false;
+        }
+
+        self.allowed_paths.is_empty() ||
+            self.allowed_paths
+                .iter()
+                .any(|allowed| path.starts_with(allowed) || Path::new(allowed).starts_with(path))
+    }
+
+    /// Checks if the Item is shown to the Renderer regarding its
+    /// Path, Media Type and Rating.
+    ///
+    /// # Arguments
+    ///
+    /// * `item` - The Item to check
+    pub fn is_item_visible(&self, item: &Item) -> bool {
+        let path = Path::new(&item.file_path);
+
+        if self.denied_paths.iter().any(|denied| path.starts_with(denied)) {
+            return false;
+        }
+
+        if !self.allowed_paths.is_empty() && !self.allowed_paths.iter().any(|allowed| path.starts_with(allowed)) {
+            return false;
+        }
+
+        if !self.media_types.is_empty() && !self.media_types.contains(&item.media_type) {
+            return false;
+        }
+
+        match self.max_rating {
+            Some(max_rating) => match get_rating_age(&item.meta_data.rating) {
+                Some(age) => age <= max_rating,
+                None => !self.hide_unrated,
+            },
+            None => true,
+        }
+    }
+}
+
+/// Returns the minimum Age of a Rating like "PG-13", "TV-MA" or
+/// "FSK 12". Ratings carrying a Number use that Number as Age.
+/// Returns None if the Rating is empty or unknown.
+///
+/// # Arguments
+///
+/// * `rating` - The Rating taken from the Meta Data
+fn get_rating_age(rating: &str) -> Option<u8> {
+    let rating = rating.trim().to_uppercase();
+
+    match rating.as_ref() {
+        "G" | "U" | "E" | "TV-Y" | "TV-G" => return Some(0),
+        "PG" | "TV-PG" => return Some(10),
+        "R" | "TV-MA" => return Some(17),
+        "X" | "XXX" => return Some(18),
+        _ => (),
+    }
+
+    let digits: String = rating
+        .chars()
+        .skip_while(|c| !c.is_ascii_digit())
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+
+    digits.parse::<u8>().ok()
 }
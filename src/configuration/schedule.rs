@@ -0,0 +1,161 @@
+use chrono::{DateTime, Datelike, Local, Timelike};
+
+/// Minutes of a single Day
+const MINUTES_PER_DAY: u32 = 24 * 60;
+
+/// Minutes of a whole Week
+const MINUTES_PER_WEEK: u32 = 7 * MINUTES_PER_DAY;
+
+/// Short Names of the Week Days, beginning with Monday
+const DAY_NAMES: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+
+/// # SchedulePolicy
+///
+/// What happens to running Streams when a Renderers Schedule
+/// Window closes.
+#[derive(Clone, PartialEq)]
+pub enum SchedulePolicy {
+    Finish, // Running Streams may finish, only new ones are refused
+    CutOff, // Running Streams are closed when the Window closes
+}
+
+/// # Schedule
+///
+/// A Time Window on some Days of the Week, e.g. "mon-fri 07:00-19:30".
+/// Windows ending before they start span Midnight, e.g.
+/// "fri,sat 20:00-01:00" ends on the following Day.
+#[derive(Clone)]
+pub struct Schedule {
+    days: [bool; 7], // Days the Window starts on, beginning with Monday
+    start: u32, // Minute of the Day the Window opens
+    end: u32, // Minute of the Day the Window closes. 1440 for Midnight
+}
+
+impl Schedule {
+    /// Parses a Schedule like "mon-fri 07:00-19:30", "sat,sun 09:00-21:00"
+    /// or "daily 00:00-24:00". Returns None if the Value is invalid.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The Schedule
+    pub fn parse(value: &str) -> Option<Schedule> {
+        let mut parts = value.split_whitespace();
+        let days = parse_days(parts.next()?)?;
+        let times = parts.next()?;
+
+        if parts.next().is_some() {
+            return None;
+        }
+
+        let position = times.find('-')?;
+        let start = parse_time(&times[..position])?;
+        let end = parse_time(&times[position + 1..])?;
+
+        if start == end || start == MINUTES_PER_DAY {
+            return None;
+        }
+
+        Some(Schedule { days, start, end })
+    }
+
+    /// Checks if the Window is open at the given Minute of the Week
+    ///
+    /// # Arguments
+    ///
+    /// * `minute` - Minutes since Monday 00:00
+    pub fn contains(&self, minute: u32) -> bool {
+        let day = (minute / MINUTES_PER_DAY % 7) as usize;
+        let previous = (day + 6) % 7;
+        let minute = minute % MINUTES_PER_DAY;
+
+        if self.start < self.end {
+            self.days[day] && minute >= self.start && minute < self.end
+        } else {
+            (self.days[day] && minute >= self.start) || (self.days[previous] && minute < self.end)
+        }
+    }
+}
+
+/// Returns the Minutes since Monday 00:00 of the given Time
+///
+/// # Arguments
+///
+/// * `time` - The Time
+pub fn get_minute_of_week(time: &DateTime<Local>) -> u32 {
+    time.weekday().num_days_from_monday() * MINUTES_PER_DAY + time.hour() * 60 + time.minute()
+}
+
+/// Returns the Number of Minutes, beginning with the given one, one
+/// of the Schedules is open without Interruption. Returns None if
+/// they never close.
+///
+/// # Arguments
+///
+/// * `schedules` - The Schedules
+/// * `minute` - Minutes since Monday 00:00
+pub fn get_open_minutes(schedules: &[Schedule], minute: u32) -> Option<u32> {
+    (0..MINUTES_PER_WEEK).find(|offset| {
+        let minute = (minute + offset) % MINUTES_PER_WEEK;
+        !schedules.iter().any(|schedule| schedule.contains(minute))
+    })
+}
+
+/// Parses the Days of a Schedule like "mon-fri", "sat,sun" or "daily"
+///
+/// # Arguments
+///
+/// * `value` - The Days
+fn parse_days(value: &str) -> Option<[bool; 7]> {
+    let mut days = [false; 7];
+    let value = value.to_lowercase();
+
+    if value == "daily" || value == "*" {
+        return Some([true; 7]);
+    }
+
+    for entry in value.split(',') {
+        let (first, last) = match entry.find('-') {
+            Some(position) => (get_day(&entry[..position])?, get_day(&entry[position + 1..])?),
+            None => (get_day(entry)?, get_day(entry)?),
+        };
+
+        // Ranges like "sat-mon" wrap around the End of the Week
+        let mut day = first;
+        loop {
+            days[day] = true;
+            if day == last {
+                break;
+            }
+            day = (day + 1) % 7;
+        }
+    }
+
+    Some(days)
+}
+
+/// Returns the Index of a Week Day, beginning with Monday
+///
+/// # Arguments
+///
+/// * `name` - Short Name of the Day, e.g. mon
+fn get_day(name: &str) -> Option<usize> {
+    DAY_NAMES.iter().position(|day| *day == name.trim())
+}
+
+/// Parses a Time like "07:30" into the Minute of the Day. "24:00"
+/// is allowed to mark the End of a Day.
+///
+/// # Arguments
+///
+/// * `value` - The Time
+fn parse_time(value: &str) -> Option<u32> {
+    let position = value.find(':')?;
+    let hours = value[..position].parse::<u32>().ok()?;
+    let minutes = value[position + 1..].parse::<u32>().ok()?;
+
+    if minutes > 59 || hours * 60 + minutes > MINUTES_PER_DAY {
+        return None;
+    }
+
+    Some(hours * 60 + minutes)
+}
@@ -1,3 +1,11 @@
+use std::net::IpAddr;
+use sys_info::cpu_num;
+
+use super::shareconfiguration::ShareConfiguration;
+use super::network::AccessRules;
+use super::rendererconfiguration::RendererConfiguration;
+use tools::{AccessLogFormat, LogModule, LogOutput};
+
 /// # ServerConfiguration
 ///
 /// This structures holdes the Servers Main Configuration
@@ -11,14 +19,32 @@ pub struct ServerConfiguration {
     pub thumbnail_dir: String, // Path to the Directory where Thumbnails should be stored
     pub server_port: u16, // Port to run the Server on
     pub server_interface: String, // Network Interface to run the Server on
-    pub share_dirs: Vec<String>, // Pathes to the Folders that should be shared
+    pub shares: Vec<ShareConfiguration>, // Folders that should be shared
     pub generate_thumbnails: bool, // Generate Thumbnails?
     pub log_path: String, // Path to the Log File
     pub log_level: u8, // Log Level to use
+    pub log_levels: Vec<(LogModule, u8)>, // Log Levels overriding log_level for single Subsystems
+    pub log_output: LogOutput, // Where to write the Log Entries to
+    pub log_rotate_size: u64, // Size in MB the Log File is rotated at. 0 to not rotate by Size
+    pub log_rotate_daily: bool, // Rotate the Log File every Day?
+    pub log_retention: usize, // Number of rotated Log Files to keep
+    pub access_log_path: String, // Path to the Access Log File. Empty to disable the Access Log
+    pub access_log_format: AccessLogFormat, // Format of the Access Log Lines
     pub server_tag: String, // Server Tag as Idenification
     pub server_ip: String, // Server IP
     pub server_uuid: String, // Server UUID
     pub media_db_path: String, // Path where to store the Media Database
+    pub scan_threads: usize, // Number of parallel Workers parsing Media Files. 0 for one per CPU
+    pub keep_alive_timeout: u64, // Seconds an idle Connection is kept open. 0 to close after every Request
+    pub max_keep_alive_requests: usize, // Number of Requests served on a single Connection
+    pub http_threads: usize, // Number of Workers serving Connections
+    pub max_streams: usize, // Number of Media Streams sent at the same Time
+    pub max_streams_per_client: usize, // Number of Media Streams sent to a single Client at the same Time
+    pub socket_timeout: u64, // Seconds a Socket may block while reading or writing
+    pub api_token: String, // Token the Admin API has to be called with. Empty to disable the API
+    pub playback_threshold: u64, // Percent of a File a Client has to receive for it to count as played
+    pub bookmarks_per_renderer: bool, // Keep a Resume Position per Renderer instead of a shared one?
+    pub access_rules: AccessRules, // Networks allowed / denied to access the Server
 }
 
 impl ServerConfiguration {
@@ -32,14 +58,32 @@ impl ServerConfiguration {
             thumbnail_dir: String::from("/var/lib/slms/thumbnails/"),
             server_port: 5001,
             server_interface: String::from("eth0"),
-            share_dirs: Vec::new(),
+            shares: Vec::new(),
             generate_thumbnails: false,
             log_path: String::from("/var/log/slms.log"),
             log_level: 0,
+            log_levels: Vec::new(),
+            log_output: LogOutput::File,
+            log_rotate_size: 0,
+            log_rotate_daily: false,
+            log_retention: 5,
+            access_log_path: String::new(),
+            access_log_format: AccessLogFormat::Combined,
             server_tag: String::from("SLMS"),
             server_ip: String::from("127.0.0.1"),
             server_uuid: String::from("zzxxxzz"),
             media_db_path: String::from("/var/lib/slms/db.xml"),
+            scan_threads: 0,
+            keep_alive_timeout: 15,
+            max_keep_alive_requests: 100,
+            http_threads: 64,
+            max_streams: 16,
+            max_streams_per_client: 4,
+            socket_timeout: 30,
+            api_token: String::new(),
+            playback_threshold: 50,
+            bookmarks_per_renderer: false,
+            access_rules: AccessRules::new(),
         }
     }
 
@@ -51,14 +95,82 @@ impl ServerConfiguration {
             thumbnail_dir: self.thumbnail_dir.clone(),
             server_port: self.server_port,
             server_interface: self.server_interface.clone(),
-            share_dirs: self.share_dirs.clone(),
+            shares: self.shares.clone(),
             generate_thumbnails: self.generate_thumbnails,
             log_path: self.log_path.clone(),
             log_level: self.log_level,
+            log_levels: self.log_levels.clone(),
+            log_output: self.log_output,
+            log_rotate_size: self.log_rotate_size,
+            log_rotate_daily: self.log_rotate_daily,
+            log_retention: self.log_retention,
+            access_log_path: self.access_log_path.clone(),
+            access_log_format: self.access_log_format,
             server_tag: self.server_tag.clone(),
             server_ip: self.server_ip.clone(),
             server_uuid: self.server_uuid.clone(),
             media_db_path: self.media_db_path.clone(),
+            scan_threads: self.scan_threads,
+            keep_alive_timeout: self.keep_alive_timeout,
+            max_keep_alive_requests: self.max_keep_alive_requests,
+            http_threads: self.http_threads,
+            max_streams: self.max_streams,
+            max_streams_per_client: self.max_streams_per_client,
+            socket_timeout: self.socket_timeout,
+            api_token: self.api_token.clone(),
+            playback_threshold: self.playback_threshold,
+            bookmarks_per_renderer: self.bookmarks_per_renderer,
+            access_rules: self.access_rules.clone(),
+        }
+    }
+
+    /// Returns the Number of parallel Workers used to parse Media
+    /// Files. This is one per CPU if no Number was configured.
+    pub fn get_scan_threads(&self) -> usize {
+        match self.scan_threads {
+            0 => cpu_num().unwrap_or(1) as usize,
+            value => value,
+        }
+    }
+
+    /// Returns the Share the File or Folder at the given Path lays
+    /// in. If Shares are nested, the innermost one is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the File or Folder
+    pub fn get_share(&self, path: &str) -> Option<&ShareConfiguration> {
+        self.shares
+            .iter()
+            .filter(|share| share.contains(path))
+            .max_by_key(|share| share.path.len())
+    }
+
+    /// Returns the Key Bookmarks of the Renderer are stored under.
+    /// Empty if all Renderers share the same Bookmarks.
+    ///
+    /// # Arguments
+    ///
+    /// * `renderer_cfg` - Configuration of the Renderer
+    pub fn get_bookmark_key<'a>(&self, renderer_cfg: &'a RendererConfiguration) -> &'a str {
+        if self.bookmarks_per_renderer {
+            &renderer_cfg.display_name
+        } else {
+            ""
+        }
+    }
+
+    /// Checks if a Client may access the File or Folder at the given
+    /// Path, according to the Access Rules of its Share.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the File or Folder
+    /// * `address` - Address of the Client
+    pub fn is_path_allowed(&self, path: &str, address: &IpAddr) -> bool {
+        match self.get_share(path) {
+            Some(share) => share.access_rules.is_allowed(address),
+            None => true,
         }
     }
 }
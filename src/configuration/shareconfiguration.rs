@@ -0,0 +1,174 @@
+use std::path::Path;
+use regex::Regex;
+
+use media::MediaType;
+use super::network::AccessRules;
+
+/// # ShareConfiguration
+///
+/// Holds the Settings of a single shared Folder. Besides the
+/// Path itself a Share can have its own Display Name, be
+/// restricted to a single kind of Media and exclude Files or
+/// Folders by Pattern. These Settings are applied by the
+/// DatabaseManager while scanning the Share.
+#[derive(Clone)]
+pub struct ShareConfiguration {
+    pub path: String, // Path to the shared Folder
+    pub name: String, // Name displayed to the Renderer. Empty to use the Folders Name
+    pub media_type: Option<MediaType>, // Only provide Media of this Type. None for all Types
+    pub exclude_globs: Vec<String>, // Glob Patterns of Files / Folders to skip
+    pub exclude_regex: Vec<Regex>, // Regular Expressions of Paths to skip
+    pub max_depth: Option<usize>, // Maximum Number of Sub Folder Levels to scan. None for unlimited
+    pub follow_symlinks: bool, // Follow symbolic Links while scanning?
+    pub access_rules: AccessRules, // Networks allowed / denied to access this Share
+}
+
+impl ShareConfiguration {
+    /// Creates a new ShareConfiguration for the given Path
+    /// without any Restrictions.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the shared Folder
+    pub fn new(path: &str) -> ShareConfiguration {
+        ShareConfiguration {
+            path: path.to_string(),
+            name: String::new(),
+            media_type: None,
+            exclude_globs: Vec::new(),
+            exclude_regex: Vec::new(),
+            max_depth: None,
+            follow_symlinks: true,
+            access_rules: AccessRules::new(),
+        }
+    }
+
+    /// Returns the Title to display for this Share. This is the
+    /// configured Name or the last Component of the Path.
+    pub fn get_title(&self) -> String {
+        if !self.name.is_empty() {
+            return self.name.clone();
+        }
+
+        match Path::new(&self.path).file_name() {
+            Some(value) => value.to_string_lossy().to_string(),
+            None => self.path.clone(),
+        }
+    }
+
+    /// Checks if the given Path is located inside this Share
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of a File or Folder
+    pub fn contains(&self, path: &str) -> bool {
+        Path::new(path).starts_with(&self.path)
+    }
+
+    /// Returns the Number of Folder Levels the given Path is
+    /// located below the Shares Root. The Root itself and Files
+    /// directly inside of it are at Level 0.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of a Folder inside this Share
+    pub fn get_depth(&self, path: &str) -> usize {
+        match Path::new(path).strip_prefix(&self.path) {
+            Ok(value) => value.components().count(),
+            Err(_) => 0,
+        }
+    }
+
+    /// Checks if a Folder at the given Level may still be scanned
+    ///
+    /// # Arguments
+    ///
+    /// * `depth` - Level of the Folder below the Shares Root
+    pub fn allows_depth(&self, depth: usize) -> bool {
+        match self.max_depth {
+            Some(value) => depth <= value,
+            None => true,
+        }
+    }
+
+    /// Checks if Media of the given Type may be provided by this Share
+    ///
+    /// # Arguments
+    ///
+    /// * `media_type` - Type of the Media Item
+    pub fn allows_type(&self, media_type: &MediaType) -> bool {
+        match self.media_type {
+            Some(ref value) => value == media_type,
+            None => true,
+        }
+    }
+
+    /// Checks if a File or Folder is excluded from this Share.
+    /// Glob Patterns without "/" are compared with the Name
+    /// of the Element only, all others with the whole Path.
+    /// Regular Expressions are searched inside the whole Path.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the File or Folder
+    pub fn is_excluded(&self, path: &str) -> bool {
+        let name = match Path::new(path).file_name() {
+            Some(value) => value.to_string_lossy().to_string(),
+            None => String::new(),
+        };
+
+        for pattern in &self.exclude_globs {
+            let target = if pattern.contains('/') { path } else { &name };
+
+            if glob_match(pattern.as_bytes(), target.as_bytes()) {
+                return true;
+            }
+        }
+
+        for regex in &self.exclude_regex {
+            if regex.is_match(path) {
+                return true;
+            }
+        }
+
+        false
+    }
+}
+
+/// Matches a Text against a simple Glob Pattern supporting
+/// "*" (any Number of Characters) and "?" (exactly one Character).
+///
+/// # Arguments
+///
+/// * `pattern` - The Glob Pattern
+/// * `text` - Text to match
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    let mut p: usize = 0;
+    let mut t: usize = 0;
+    let mut star: Option<usize> = None;
+    let mut star_t: usize = 0;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == b'?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == b'*' {
+            star = Some(p);
+            star_t = t;
+            p += 1;
+        } else if let Some(position) = star {
+            // Let the last Star consume one more Character
+            p = position + 1;
+            star_t += 1;
+            t = star_t;
+        } else {
+            return false;
+        }
+    }
+
+    while p < pattern.len() && pattern[p] == b'*' {
+        p += 1;
+    }
+
+    p == pattern.len()
+}
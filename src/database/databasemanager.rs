@@ -2,10 +2,14 @@ use std::fs::File;
 use std::io::{Write, Read};
 use std::path::Path;
 use std::fs;
+use std::sync::Arc;
 use std::time;
 
 use super::folder::Folder;
-use media::{Item, Container, MediaType, Stream, StreamType, Thumbnail, mediaparser};
+use super::photodates;
+use super::playedfolders::{self, Viewer};
+use configuration::ShareConfiguration;
+use media::{Bookmark, Item, Container, MediaType, Stream, StreamType, Thumbnail};
 use tools::{NameValuePair, XMLParser, XMLEntry, Logger, LogLevel};
 
 
@@ -31,9 +35,9 @@ use tools::{NameValuePair, XMLParser, XMLEntry, Logger, LogLevel};
 /// - Allow execution even if no Database is available / XML Errors occured
 pub struct DatabaseManager {
     path: String,
-    media_item: Vec<Item>,
-    media_folders: Vec<Folder>,
-    share_folders: Vec<String>,
+    media_item: Vec<Arc<Item>>,
+    media_folders: Vec<Arc<Folder>>,
+    share_folders: Vec<ShareConfiguration>,
     media_formats: Vec<Container>,
     latest_id: u64,
     logger: Logger,
@@ -50,8 +54,8 @@ impl DatabaseManager {
     /// # Arguments
     ///
     /// * `db_path` - Where to store the XML Database File
-    /// * `shares` - List of Pathes to share theough SLMS
-    pub fn load(&mut self, db_path: &str, shares: Vec<String>, logger: Logger) {
+    /// * `shares` - List of Shares to provide through SLMS
+    pub fn load(&mut self, db_path: &str, shares: Vec<ShareConfiguration>, logger: Logger) {
         self.path = db_path.to_string();
         self.share_folders = shares;
         self.latest_id = 1;
@@ -75,46 +79,45 @@ impl DatabaseManager {
         }
     }
 
+    /// Creates a Copy of the whole Database that can be handled
+    /// independently, e.g. as a Snapshot for Readers. Folders and
+    /// Items are shared with the Copy and only copied once one of
+    /// them is changed, so this does not depend on their Size.
+    pub fn clone(&self) -> DatabaseManager {
+        DatabaseManager {
+            path: self.path.clone(),
+            media_item: self.media_item.clone(),
+            media_folders: self.media_folders.clone(),
+            share_folders: self.share_folders.clone(),
+            media_formats: self.media_formats.clone(),
+            latest_id: self.latest_id,
+            logger: self.logger.clone(),
+        }
+    }
+
     /// This function will boot up the Media Database.
     /// It will search for an existing XML File and if
-    /// available parse its contents.
-    /// Once the last Status was loaded, all Shares will
-    /// be went through in Order to check if something
-    /// new is available or some Files may have changed.
-    /// Once everything has been parsed the new
-    /// Database will be written back to the File System.
+    /// available parse its contents. Everything the
+    /// current Share Settings do not cover anymore is
+    /// dropped right away.
     ///
-    /// As this function takes up a lot of time it is
-    /// exlusivly designed to be called once when the
-    /// Media Server starts up. If any changes happen while
-    /// running, other functions will handle that cases.
+    /// Afterwards the Database is ready to be used. Going
+    /// through the Shares in Order to check if something new
+    /// is available or some Files may have changed is done
+    /// by the Scanner in the Background, which will also
+    /// write the Database back to the File System.
     pub fn boot_up(&mut self) {
 
         // Load Database from File System
         self.load_database();
 
-        self.logger.write_log(
-            "DB: All Items loaded. Negotiating with the File System...",
-            LogLevel::INFORMATION,
-        );
-
-        // Parse all Shares and update Database
-        for share in &self.share_folders.clone() {
-            self.parse_folder(&share, 0);
-        }
-
-        self.logger.write_log(
-            "DB: Refreshed Database. Saving Changes...",
-            LogLevel::INFORMATION,
-        );
-
-        // Store Database to File System
-        self.save_database(true);
+        // Drop everything the current Share Settings do not allow anymore
+        self.remove_excluded();
 
         // Ouput Information
         self.logger.write_log(
             &format!(
-                "DB: Database Ready. There is a total of {} Folders and {} Files available.",
+                "DB: Database loaded. There is a total of {} Folders and {} Files available.",
                 self.media_folders.len(),
                 self.media_item.len()
             ),
@@ -122,240 +125,250 @@ impl DatabaseManager {
         );
     }
 
-    /// Parses a Folder with the given Format. Calls the same function
-    /// for all sub folders and tries to parse any File that is
-    /// located inside the Folder.
-    /// If the File is already present inside the Database, the
-    /// File is skipped as long as it has not been modified.
-    /// If a File is Unknown or was changed, the MediaParser
-    /// is used to parse the File and add it to the Meida
-    /// Database.
-    ///
-    /// Calling this function after the Database has been loaded
-    /// from File and for all shares, this ensures that every
-    /// Media laying under the shares will be part of the
-    /// Media Database.
+    /// Adds a Folder to the Database or updates it if it is already
+    /// known. Returns the Id of the Folder.
     ///
     /// # Arguments
     ///
-    /// * `path` - Path to the Folder that should be parsed
+    /// * `path` - Path to the Folder
     /// * `parent_id` - Id of the Parent Folder this one lays in
-    fn parse_folder(&mut self, path: &str, parent_id: u64) {
-        self.logger.write_log(
-            &format!("DB - parse_folder(): Parsing Folder: {}", path),
-            LogLevel::DEBUG,
-        );
-
-        // If the path does not exits -> return
-        if path.is_empty() || self.does_exist(path) == false {
-            self.logger.write_log(
-                &format!("DB - parse_folder(): Unable to parse Folder: {}", path),
-                LogLevel::ERROR,
-            );
-            return;
-        }
-
-        let mut is_new: bool = false;
-        let mut id: u64 = 0;
+    /// * `title` - Title to display for the Folder
+    pub fn update_folder(&mut self, path: &str, parent_id: u64, title: &str) -> u64 {
         let logg: Logger = self.logger.clone();
 
         // Check if that folder is in Database
-        match self.get_folder_from_path(path) {
-            Ok(folder) => {
-                if DatabaseManager::get_last_modified(path) > folder.last_modified {
-                    // If something changed update that folder
-                    folder.last_modified = DatabaseManager::get_last_modified(path);
-                    folder.element_count = DatabaseManager::get_elements(path);
-                    id = folder.id;
-                    logg.write_log(
-                        &format!(
-                            "DB - parse_folder(): Folder: {} was modified. Updated DB Entry...",
-                            path
-                        ),
-                        LogLevel::VERBOSE,
-                    );
-                }
-            }
-            Err(_) => {
-                is_new = true;
+        if let Ok(folder) = self.get_folder_from_path(path) {
+            folder.title = title.to_string();
+
+            if DatabaseManager::get_last_modified(path) > folder.last_modified {
+                // If something changed update that folder
+                folder.last_modified = DatabaseManager::get_last_modified(path);
+                folder.element_count = DatabaseManager::get_elements(path);
                 logg.write_log(
                     &format!(
-                        "DB - parse_folder(): Folder: {} is new. Creating new DB Entry...",
+                        "update_folder(): Folder: {} was modified. Updated DB Entry...",
                         path
                     ),
                     LogLevel::VERBOSE,
                 );
             }
+
+            return folder.id;
         }
 
-        // If not get information and add it to list
-        if is_new {
+        logg.write_log(
+            &format!(
+                "update_folder(): Folder: {} is new. Creating new DB Entry...",
+                path
+            ),
+            LogLevel::VERBOSE,
+        );
 
-            let mut folder = Folder::new();
-            folder.id = self.get_next_id();
-            folder.parent_id = parent_id;
+        let mut folder = Folder::new();
+        folder.id = self.get_next_id();
+        folder.parent_id = parent_id;
+        folder.title = title.to_string();
+        folder.path = path.to_string();
+        folder.last_modified = DatabaseManager::get_last_modified(path);
+        folder.element_count = DatabaseManager::get_elements(path);
 
-            if &path[path.len() - 1..] == "/" {
-                folder.title = match path[..path.len() - 1].split("/").last() {
-                    Some(value) => value.to_string(),
-                    None => {
-                        self.logger.write_log(
-                            &format!(
-                                "DB - parse_folder(): Unable to Split String: {}",
-                                path
-                            ),
-                            LogLevel::ERROR,
-                        );
-                        return;
-                    }
-                };
-            } else {
-                folder.title = match path.split("/").last() {
-                    Some(value) => value.to_string(),
-                    None => {
-                        self.logger.write_log(
-                            &format!(
-                                "DB - parse_folder(): Unable to Split String: {}",
-                                path
-                            ),
-                            LogLevel::ERROR,
-                        );
-                        return;
-                    }
-                };
-            }
+        let id = folder.id;
+        self.media_folders.push(Arc::new(folder));
 
-            // Skip Folders that are hidden
-            if &folder.title[..1] == "." {
-                self.logger.write_log(
-                    &format!("DB - parse_folder(): Skipping hidden Folder: {}", path),
-                    LogLevel::DEBUG,
-                );
-                return;
-            }
+        id
+    }
 
-            folder.path = path.to_string();
-            folder.last_modified = DatabaseManager::get_last_modified(path);
-            folder.element_count = DatabaseManager::get_elements(path);
-            id = folder.id;
-            self.media_folders.push(folder);
+    /// Checks if the File at the given Path needs to be parsed
+    /// because it is unknown or was modified since it was parsed
+    /// the last Time.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the File
+    pub fn needs_parsing(&self, path: &str) -> bool {
+        match self.media_item.iter().find(|item| item.file_path == path) {
+            Some(item) => DatabaseManager::get_last_modified(path) > item.last_modified,
+            None => true,
         }
+    }
 
-        // Go through all Elements inside this Folder and add them
-        let paths = match fs::read_dir(path) {
-            Ok(value) => value,
-            Err(e) => {
-                self.logger.write_log(
-                    &format!(
-                        "DB - parse_folder(): Unable to access Elements in: {} - Reason: {}",
-                        path,
-                        e
-                    ),
-                    LogLevel::ERROR,
-                );
-                return;
+    /// Inserts a freshly parsed Item into the Database. If there
+    /// already is an Item with the same Path it is replaced but
+    /// keeps its Id and Playback History. Otherwise a new Id is
+    /// assigned.
+    ///
+    /// # Arguments
+    ///
+    /// * `item` - The parsed Item
+    /// * `parent_id` - Id of the Folder the Item lays in
+    pub fn insert_item(&mut self, mut item: Item, parent_id: u64) {
+        item.parent_id = parent_id;
+
+        match self.media_item.iter().position(
+            |value| value.file_path == item.file_path,
+        ) {
+            Some(position) => {
+                item.id = self.media_item[position].id;
+                item.thumbnail.item_id = item.id;
+                item.play_count = self.media_item[position].play_count;
+                item.last_played = self.media_item[position].last_played;
+                item.bookmarks = self.media_item[position].bookmarks.clone();
+                self.media_item[position] = Arc::new(item);
+            }
+            None => {
+                item.id = self.get_next_id();
+                item.thumbnail.item_id = item.id;
+                self.media_item.push(Arc::new(item));
+            }
+        }
+    }
+
+    /// Removes the Item with the given Path from the Database
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the Item to remove
+    pub fn remove_item_from_path(&mut self, path: &str) {
+        self.media_item.retain(|item| item.file_path != path);
+    }
+
+    /// Records a Playback of the Item with the given Id. Returns
+    /// false if there is no such Item.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Id of the played Item
+    /// * `time` - UNIX Timestamp of the Playback
+    pub fn add_playback(&mut self, id: u64, time: u64) -> bool {
+        match self.media_item.iter_mut().find(|item| item.id == id) {
+            Some(item) => {
+                let item = Arc::make_mut(item);
+                item.play_count += 1;
+                item.last_played = time;
+                true
             }
+            None => false,
+        }
+    }
+
+    /// Sets the Position a Renderer stopped playing the Item with the
+    /// given Id at. The Bookmark is removed if the Position is 0 or
+    /// the Item was played almost to the End. Returns false if there
+    /// is no such Item.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Id of the Item
+    /// * `renderer` - Renderer the Bookmark belongs to. Empty for the shared one
+    /// * `position` - Position in Seconds
+    /// * `time` - UNIX Timestamp of the Change
+    pub fn set_bookmark(&mut self, id: u64, renderer: &str, position: u64, time: u64) -> bool {
+        let item = match self.media_item.iter_mut().find(|item| item.id == id) {
+            Some(value) => Arc::make_mut(value),
+            None => return false,
         };
 
-        for path in paths {
-            match path {
-                Ok(element) => {
-                    let ele_path = element.path();
-                    let ele_str = match ele_path.to_str() {
-                        Some(value) => value,
-                        None => {
-                            self.logger.write_log(
-                                &format!("DB - parse_folder(): Unable to convert to str"),
-                                LogLevel::ERROR,
-                            );
-                            continue;
-                        }
-                    };
+        item.bookmarks.retain(|bookmark| bookmark.renderer != renderer);
 
-                    // If this is another folder -> parse it too
-                    if element.path().is_dir() {
-                        self.parse_folder(ele_str, id);
-                    } else {
-                        // If this is a file -> use the media parser
-                        let mut is_new = false;
-
-                        // Check if already existing
-                        match self.get_item_from_path(ele_str) {
-                            Ok(some) => {
-                                // Check if something changed
-                                if DatabaseManager::get_last_modified(ele_str) >
-                                    some.last_modified
-                                {
-                                    logg.write_log(
-					                &format!("DB - parse_folder(): File: {} was modified. Update DB Entry...", ele_str),
-					                LogLevel::VERBOSE,
-					            );
-                                    // Reparse the item
-                                    if !mediaparser::parse_file(ele_str, some) {
-                                        logg.write_log(
-						                &format!("DB - parse_folder(): Unable to parse and update File: {} !", ele_str),
-						                LogLevel::ERROR,
-						            );
-                                    }
-                                }
-                            }
-                            Err(_) => {
-                                is_new = true;
-                                logg.write_log(
-					                &format!("DB - parse_folder(): Found new File: {}. Creating new DB Entry...", ele_str),
-					                LogLevel::VERBOSE,
-					            );
-                            }
-                        }
+        let finished = match item.get_duration_seconds() {
+            Some(duration) => position as f64 >= duration * 0.95,
+            None => false,
+        };
 
-                        // Parse if new and assign Ids
-                        if is_new {
-                            let mut item: Item = Item::new();
-                            if mediaparser::parse_file(ele_str, &mut item) {
-                                item.id = self.get_next_id();
+        if position > 0 && !finished {
+            item.bookmarks.push(Bookmark {
+                renderer: renderer.to_string(),
+                position,
+                updated: time,
+            });
+        }
 
-                                item.parent_id = id;
-                                // Skip hidden Files
-                                if item.meta_data.file_name.len() > 0 {
-                                    if &item.meta_data.file_name[..1] == "." {
-                                        self.logger.write_log(
-                                            &format!(
-                                                "DB - load_database(): Skipping hidden File: {}",
-                                                ele_str
-                                            ),
-                                            LogLevel::DEBUG,
-                                        );
+        true
+    }
 
-                                    } else {
-                                        self.media_item.push(item);
-                                    }
-                                } else {
-                                    self.logger.write_log(&format!("DB - load_database(): Unable to determine Filename for: {}", ele_str), LogLevel::ERROR);
-                                }
-                            } else {
-                                self.logger.write_log(
-                                    &format!(
-                                        "DB - load_database(): Unable to parse File: {}",
-                                        ele_str
-                                    ),
-                                    LogLevel::ERROR,
-                                );
-                            }
-                        }
-                    }
+    /// Returns the Number of Folders inside the Database
+    pub fn get_folder_count(&self) -> usize {
+        self.media_folders.len()
+    }
+
+    /// Returns the Number of Items inside the Database
+    pub fn get_item_count(&self) -> usize {
+        self.media_item.len()
+    }
+
+    /// Removes all Folders and Items from the Database that are no
+    /// longer covered by the configured Shares. This is the case if
+    /// their Share was removed, they are excluded, too deep or of a
+    /// Media Type the Share does not provide anymore.
+    fn remove_excluded(&mut self) {
+        let shares = self.share_folders.clone();
+        let folder_count = self.media_folders.len();
+        let item_count = self.media_item.len();
+
+        self.media_folders.retain(|folder| {
+            DatabaseManager::is_allowed(&shares, &folder.path, None)
+        });
+        self.media_item.retain(|item| {
+            DatabaseManager::is_allowed(&shares, &item.file_path, Some(&item.media_type))
+        });
+
+        self.logger.write_log(
+            &format!(
+                "remove_excluded(): Removed {} Folders and {} Files not allowed by the Share Settings.",
+                folder_count - self.media_folders.len(),
+                item_count - self.media_item.len()
+            ),
+            LogLevel::DEBUG,
+        );
+    }
+
+    /// Checks if a Folder or File at the given Path is provided by
+    /// one of the Shares using the Shares Settings.
+    ///
+    /// # Arguments
+    ///
+    /// * `shares` - List of configured Shares
+    /// * `path` - Path of the Folder or File
+    /// * `media_type` - Media Type if this is a File, None for Folders
+    fn is_allowed(shares: &[ShareConfiguration], path: &str, media_type: Option<&MediaType>) -> bool {
+        for share in shares {
+            if !share.contains(path) {
+                continue;
+            }
+
+            if let Some(value) = media_type {
+                if !share.allows_type(value) {
+                    return false;
                 }
-                Err(e) => {
-                    self.logger.write_log(
-                        &format!(
-                            "DB - load_database(): Unable to access Sub Elements! - Reason: {}",
-                            e
-                        ),
-                        LogLevel::ERROR,
-                    );
-                    continue;
+            }
+
+            // Files are located on the Level of their Folder
+            let mut depth = share.get_depth(path);
+            if media_type.is_some() && depth > 0 {
+                depth -= 1;
+            }
+
+            if !share.allows_depth(depth) {
+                return false;
+            }
+
+            // Check the Element and every Folder above it up to the Shares Root
+            let mut current = Path::new(path);
+            while current != Path::new(&share.path) {
+                if share.is_excluded(&current.to_string_lossy()) {
+                    return false;
                 }
+
+                current = match current.parent() {
+                    Some(value) => value,
+                    None => break,
+                };
             }
+
+            return true;
         }
+
+        false
     }
 
     /// Checks if a Folder at the given Path exists inside
@@ -370,31 +383,13 @@ impl DatabaseManager {
     fn get_folder_from_path(&mut self, path: &str) -> Result<&mut Folder, ()> {
         for folder in &mut self.media_folders {
             if folder.path == path {
-                return Ok(folder);
+                return Ok(Arc::make_mut(folder));
             }
         }
 
         Err(())
     }
 
-    /// Checks if a Item with the given Path already exits
-    /// inside the Database and returns it if available.
-    /// Err if not.
-    /// This is important in Order to check if a given Item
-    /// is already part of the Media Database.
-    ///
-    /// # Arguments
-    ///
-    /// * `path` - Path to the Item to check
-    fn get_item_from_path(&mut self, path: &str) -> Result<&mut Item, ()> {
-        for item in &mut self.media_item {
-            if item.file_path == path {
-                return Ok(item);
-            }
-        }
-        Err(())
-    }
-
     /// Returns the Number of Elements inside the given
     /// Path. If something went wrong, 0 is returned.
     ///
@@ -435,7 +430,7 @@ impl DatabaseManager {
     /// # Arguments
     ///
     /// * `check_changed` - Do or do not check if Files / Folders has changed since last save
-    fn save_database(&self, check_changed: bool) {
+    pub fn save_database(&self, check_changed: bool) {
         // Create ROOT Folder
         let mut root_attr: Vec<NameValuePair> = Vec::new();
         root_attr.push(NameValuePair::new("id", "0"));
@@ -493,6 +488,11 @@ impl DatabaseManager {
                 xml_parser.open_tag("thumbnail", &item.thumbnail.get_name_value_pairs(), false);
             }
 
+            // Write Bookmarks
+            for bookmark in &item.bookmarks {
+                xml_parser.open_tag("bookmark", &bookmark.get_name_value_pairs(), false);
+            }
+
             // Write Meta Tags
             let meta_attr = item.meta_data.get_name_value_pairs();
 
@@ -532,7 +532,7 @@ impl DatabaseManager {
             Err(e) => {
                 self.logger.write_log(
                     &format!(
-                        "DB - load_database(): Unable to create DB File: {} - Reason: {}",
+                        "load_database(): Unable to create DB File: {} - Reason: {}",
                         self.path,
                         e
                     ),
@@ -547,7 +547,7 @@ impl DatabaseManager {
             Err(e) => {
                 self.logger.write_log(
                     &format!(
-                        "DB - load_database(): Unable to write DB File: {} - Reason: {}",
+                        "load_database(): Unable to write DB File: {} - Reason: {}",
                         self.path,
                         e
                     ),
@@ -586,7 +586,7 @@ impl DatabaseManager {
     fn load_database(&mut self) {
         // Open Database File
         self.logger.write_log(
-            &format!("DB - load_database(): Open Database File: {}", self.path),
+            &format!("load_database(): Open Database File: {}", self.path),
             LogLevel::DEBUG,
         );
         let mut db_file = match File::open(&self.path) {
@@ -594,7 +594,7 @@ impl DatabaseManager {
             Err(e) => {
                 self.logger.write_log(
                     &format!(
-                        "DB - load_database(): Unable to Open Database File: {} - Reason: {}",
+                        "load_database(): Unable to Open Database File: {} - Reason: {}",
                         self.path,
                         e
                     ),
@@ -605,7 +605,7 @@ impl DatabaseManager {
         };
 
         self.logger.write_log(
-            &format!("DB - load_database(): Reading Database File Content"),
+            "load_database(): Reading Database File Content",
             LogLevel::VERBOSE,
         );
 
@@ -615,7 +615,7 @@ impl DatabaseManager {
             Err(e) => {
                 self.logger.write_log(
                     &format!(
-                        "DB - load_database(): Unable to read from Database File: {} - Reason: {}",
+                        "load_database(): Unable to read from Database File: {} - Reason: {}",
                         self.path,
                         e
                     ),
@@ -626,7 +626,7 @@ impl DatabaseManager {
         }
 
         self.logger.write_log(
-            &format!("DB - load_database(): Content loaded. Parsing XML..."),
+            "load_database(): Content loaded. Parsing XML...",
             LogLevel::VERBOSE,
         );
 
@@ -635,7 +635,7 @@ impl DatabaseManager {
 
         self.logger.write_log(
             &format!(
-                "DB - load_database(): XML loaded. Moving Root and Formats to Memory..."
+                "load_database(): XML loaded. Moving Root and Formats to Memory..."
             ),
             LogLevel::VERBOSE,
         );
@@ -646,7 +646,7 @@ impl DatabaseManager {
             match entry.tag.as_ref() {
                 "root" => {
                     self.logger.write_log(
-                        "DB - load_database(): Found \"root\"",
+                        "load_database(): Found \"root\"",
                         LogLevel::VERBOSE,
                     );
                     root_xml = entry;
@@ -660,7 +660,7 @@ impl DatabaseManager {
                             Err(_) => {
                                 self.logger.write_log(
                                     &format!(
-                        "DB - load_database(): Unable to parse Format Id...",
+                        "load_database(): Unable to parse Format Id...",
                     ),
                                     LogLevel::ERROR,
                                 );
@@ -692,7 +692,7 @@ impl DatabaseManager {
 
                     self.logger.write_log(
                         &format!(
-                        "DB - load_database(): Found Container: {}",
+                        "load_database(): Found Container: {}",
                         media_container.name,
                     ),
                         LogLevel::VERBOSE,
@@ -706,7 +706,7 @@ impl DatabaseManager {
         }
 
         self.logger.write_log(
-            &format!("DB - load_database(): Root and Formats loaded. Moving Folders and Items to Memory..."),
+            "load_database(): Root and Formats loaded. Moving Folders and Items to Memory...",
             LogLevel::VERBOSE,
         );
 
@@ -721,7 +721,7 @@ impl DatabaseManager {
                         Err(_) => {
                             self.logger.write_log(
                                 &format!(
-                        "DB - load_database(): Unable to parse Folder Element Count...",
+                        "load_database(): Unable to parse Folder Element Count...",
                     ),
                                 LogLevel::ERROR,
                             );
@@ -734,7 +734,7 @@ impl DatabaseManager {
                     Err(_) => {
                         self.logger.write_log(
                             &format!(
-                        "DB - load_database(): Unable to parse Folder Id...",
+                        "load_database(): Unable to parse Folder Id...",
                     ),
                             LogLevel::ERROR,
                         );
@@ -748,7 +748,7 @@ impl DatabaseManager {
                         Err(_) => {
                             self.logger.write_log(
                                 &format!(
-                        "DB - load_database(): Unable to parse Folder Parent Id...",
+                        "load_database(): Unable to parse Folder Parent Id...",
                     ),
                                 LogLevel::ERROR,
                             );
@@ -762,7 +762,7 @@ impl DatabaseManager {
                         Err(_) => {
                             self.logger.write_log(
                                 &format!(
-                        "DB - load_database(): Unable to parse Folder last modified Date..",
+                        "load_database(): Unable to parse Folder last modified Date..",
                     ),
                                 LogLevel::ERROR,
                             );
@@ -782,26 +782,26 @@ impl DatabaseManager {
                     {
                         self.logger.write_log(
                             &format!(
-                        "DB - load_database(): Folder: {} has not changed...",
+                        "load_database(): Folder: {} has not changed...",
                         tmp_folder.path,
                     ),
                             LogLevel::VERBOSE,
                         );
-                        self.media_folders.push(tmp_folder);
+                        self.media_folders.push(Arc::new(tmp_folder));
                     } else {
                         self.logger.write_log(
                             &format!(
-                        "DB - load_database(): Folder: {} has changed. Prepare to re-parse...",
+                        "load_database(): Folder: {} has changed. Prepare to re-parse...",
                         tmp_folder.path,
                     ),
                             LogLevel::VERBOSE,
                         );
-                        self.media_folders.push(tmp_folder);
+                        self.media_folders.push(Arc::new(tmp_folder));
                     }
                 } else {
                     self.logger.write_log(
                             &format!(
-                        "DB - load_database(): Folder: {} does not exist any longer. Remove from DB...",
+                        "load_database(): Folder: {} does not exist any longer. Remove from DB...",
                         tmp_folder.path,
                     ),
                             LogLevel::VERBOSE,
@@ -820,7 +820,7 @@ impl DatabaseManager {
                         Err(_) => {
                             self.logger.write_log(
                                 &format!(
-                        "DB - load_database(): Unable to parse Item Size...",
+                        "load_database(): Unable to parse Item Size...",
                     ),
                                 LogLevel::ERROR,
                             );
@@ -833,7 +833,7 @@ impl DatabaseManager {
                     Err(_) => {
                         self.logger.write_log(
                             &format!(
-                        "DB - load_database(): Unable to parse Item Id...",
+                        "load_database(): Unable to parse Item Id...",
                     ),
                             LogLevel::ERROR,
                         );
@@ -847,7 +847,7 @@ impl DatabaseManager {
                         Err(_) => {
                             self.logger.write_log(
                                 &format!(
-                        "DB - load_database(): Unable to parse Item last modified Date...",
+                        "load_database(): Unable to parse Item last modified Date...",
                     ),
                                 LogLevel::ERROR,
                             );
@@ -857,6 +857,14 @@ impl DatabaseManager {
                 tmp_item.media_type = MediaType::from_string(
                     &XMLParser::get_value_from_name(&folder.attributes, "type"),
                 );
+
+                // Databases of older Versions do not know the Playback History
+                tmp_item.play_count = XMLParser::get_value_from_name(&folder.attributes, "playCount")
+                    .parse::<u32>()
+                    .unwrap_or(0);
+                tmp_item.last_played = XMLParser::get_value_from_name(&folder.attributes, "lastPlayed")
+                    .parse::<u64>()
+                    .unwrap_or(0);
                 tmp_item.parent_id =
                     match XMLParser::get_value_from_name(&folder.attributes, "parentId")
                         .parse::<u64>() {
@@ -864,7 +872,7 @@ impl DatabaseManager {
                         Err(_) => {
                             self.logger.write_log(
                                 &format!(
-                        "DB - load_database(): Unable to parse Item Parent Id...",
+                        "load_database(): Unable to parse Item Parent Id...",
                     ),
                                 LogLevel::ERROR,
                             );
@@ -886,7 +894,7 @@ impl DatabaseManager {
                                 Err(_) => {
                                     self.logger.write_log(
                                         &format!(
-                        "DB - load_database(): Unable to Stream Audio Channel Number...",
+                        "load_database(): Unable to Stream Audio Channel Number...",
                     ),
                                         LogLevel::ERROR,
                                     );
@@ -901,7 +909,7 @@ impl DatabaseManager {
                                 Err(_) => {
                                     self.logger.write_log(
                                         &format!(
-                        "DB - load_database(): Unable to parse Stream Bit Depth...",
+                        "load_database(): Unable to parse Stream Bit Depth...",
                     ),
                                         LogLevel::ERROR,
                                     );
@@ -916,7 +924,7 @@ impl DatabaseManager {
                                 Err(_) => {
                                     self.logger.write_log(
                                         &format!(
-                        "DB - load_database(): Unable to parse Stream Bit Rate...",
+                        "load_database(): Unable to parse Stream Bit Rate...",
                     ),
                                         LogLevel::ERROR,
                                     );
@@ -932,7 +940,7 @@ impl DatabaseManager {
                                     Err(_) => {
                                         self.logger.write_log(
                                             &format!(
-                        "DB - load_database(): Unable to parse Stream Index...",
+                        "load_database(): Unable to parse Stream Index...",
                     ),
                                             LogLevel::ERROR,
                                         );
@@ -947,7 +955,7 @@ impl DatabaseManager {
                                 Err(_) => {
                                     self.logger.write_log(
                                         &format!(
-                        "DB - load_database(): Unable to parse Stream is_default...",
+                        "load_database(): Unable to parse Stream is_default...",
                     ),
                                         LogLevel::ERROR,
                                     );
@@ -962,7 +970,7 @@ impl DatabaseManager {
                                 Err(_) => {
                                     self.logger.write_log(
                                         &format!(
-                        "DB - load_database(): Unable to parse Stream is_forced...",
+                        "load_database(): Unable to parse Stream is_forced...",
                     ),
                                         LogLevel::ERROR,
                                     );
@@ -978,7 +986,7 @@ impl DatabaseManager {
                                     Err(_) => {
                                         self.logger.write_log(
                                             &format!(
-                        "DB - load_database(): Unable to parse Stream width...",
+                        "load_database(): Unable to parse Stream width...",
                     ),
                                             LogLevel::ERROR,
                                         );
@@ -993,7 +1001,7 @@ impl DatabaseManager {
                                 Err(_) => {
                                     self.logger.write_log(
                                         &format!(
-                        "DB - load_database(): Unable to parse Stream height...",
+                        "load_database(): Unable to parse Stream height...",
                     ),
                                         LogLevel::ERROR,
                                     );
@@ -1008,7 +1016,7 @@ impl DatabaseManager {
                                 Err(_) => {
                                     self.logger.write_log(
                                         &format!(
-                        "DB - load_database(): Unable to parse Stream Frequenzy...",
+                        "load_database(): Unable to parse Stream Frequenzy...",
                     ),
                                         LogLevel::ERROR,
                                     );
@@ -1033,7 +1041,7 @@ impl DatabaseManager {
                                     Err(_) => {
                                         self.logger.write_log(
                                             &format!(
-                        "DB - load_database(): Unable to parse Thumbnail Size...",
+                        "load_database(): Unable to parse Thumbnail Size...",
                     ),
                                             LogLevel::ERROR,
                                         );
@@ -1048,7 +1056,7 @@ impl DatabaseManager {
                                 Err(_) => {
                                     self.logger.write_log(
                                         &format!(
-                        "DB - load_database(): Unable to parse Thumbnail Height...",
+                        "load_database(): Unable to parse Thumbnail Height...",
                     ),
                                         LogLevel::ERROR,
                                     );
@@ -1062,7 +1070,7 @@ impl DatabaseManager {
                                     Err(_) => {
                                         self.logger.write_log(
                                             &format!(
-                        "DB - load_database(): Unable to parse Thumbnail Width...",
+                        "load_database(): Unable to parse Thumbnail Width...",
                     ),
                                             LogLevel::ERROR,
                                         );
@@ -1077,7 +1085,7 @@ impl DatabaseManager {
                                 Err(_) => {
                                     self.logger.write_log(
                                         &format!(
-                        "DB - load_database(): Unable to parse Thumbnail Item Id...",
+                        "load_database(): Unable to parse Thumbnail Item Id...",
                     ),
                                         LogLevel::ERROR,
                                     );
@@ -1086,9 +1094,34 @@ impl DatabaseManager {
                             };
                             tmp_thumb.mime_type =
                                 XMLParser::get_value_from_name(&stream.attributes, "mimeType");
+                            tmp_thumb.offset =
+                                XMLParser::get_value_from_name(&stream.attributes, "offset")
+                                    .parse::<u64>()
+                                    .unwrap_or(0);
 
                             tmp_item.thumbnail = tmp_thumb;
                         }
+                        "bookmark" => {
+                            let position =
+                                XMLParser::get_value_from_name(&stream.attributes, "position")
+                                    .parse::<u64>()
+                                    .unwrap_or(0);
+
+                            if position > 0 {
+                                tmp_item.bookmarks.push(Bookmark {
+                                    renderer: XMLParser::get_value_from_name(
+                                        &stream.attributes,
+                                        "renderer",
+                                    ),
+                                    position,
+                                    updated: XMLParser::get_value_from_name(
+                                        &stream.attributes,
+                                        "updated",
+                                    ).parse::<u64>()
+                                        .unwrap_or(0),
+                                });
+                            }
+                        }
                         "meta" => {
                             tmp_item.insert_meta_data(
                                 &XMLParser::get_value_from_name(&stream.attributes, "name"),
@@ -1103,16 +1136,16 @@ impl DatabaseManager {
                 if self.does_exist(&tmp_item.file_path) {
                     self.logger.write_log(
                         &format!(
-                            "DB - load_database(): File {} does still exist...",
+                            "load_database(): File {} does still exist...",
                             tmp_item.file_path
                         ),
                         LogLevel::VERBOSE,
                     );
-                    self.media_item.push(tmp_item);
+                    self.media_item.push(Arc::new(tmp_item));
                 } else {
                     self.logger.write_log(
                         &format!(
-                            "DB - load_database(): File {} does not exist anymore. Ignoring...",
+                            "load_database(): File {} does not exist anymore. Ignoring...",
                             tmp_item.file_path
                         ),
                         LogLevel::VERBOSE,
@@ -1122,7 +1155,7 @@ impl DatabaseManager {
         }
 
         self.logger.write_log(
-            &format!("DB - load_database(): All Data loaded into Memory."),
+            "load_database(): All Data loaded into Memory.",
             LogLevel::VERBOSE,
         );
     }
@@ -1191,17 +1224,28 @@ impl DatabaseManager {
         if path.exists() { true } else { false }
     }
 
-    /// Returns the List of Folders that got the given Id as Parent Folder
+    /// Returns the List of Folders that got the given Id as Parent Folder.
+    /// The virtual Photos by Date and Played Containers are included.
     ///
     /// # Arguments
     ///
     /// * `parent_id` - Id of the Element to get the Child-Folders for
-    pub fn get_folder_from_parent(&self, parent_id: u64) -> Vec<Folder> {
-        let mut res_vec: Vec<Folder> = Vec::new();
+    /// * `viewer` - The Viewer the Played Containers are generated for
+    pub fn get_folder_from_parent(&self, parent_id: u64, viewer: &Viewer) -> Vec<Folder> {
+        if photodates::is_date_folder(parent_id) {
+            return photodates::get_folders(parent_id, &self.media_item);
+        }
+
+        if playedfolders::is_played_folder(parent_id) {
+            return Vec::new();
+        }
+
+        let mut res_vec: Vec<Folder> = photodates::get_folders(parent_id, &self.media_item);
+        res_vec.extend(playedfolders::get_folders(parent_id, &self.media_item, viewer));
 
         for folder in &self.media_folders {
             if folder.parent_id == parent_id {
-                res_vec.push(folder.clone());
+                res_vec.push(Folder::clone(folder));
             }
         }
 
@@ -1213,27 +1257,56 @@ impl DatabaseManager {
     /// # Arguments
     ///
     /// * `parent_id` - Id of the Element to get the Child-Item for
-    pub fn get_items_from_parent(&self, parent_id: u64) -> Vec<Item> {
+    /// * `viewer` - The Viewer the Played Containers are listed for
+    pub fn get_items_from_parent(&self, parent_id: u64, viewer: &Viewer) -> Vec<Item> {
+        if photodates::is_date_folder(parent_id) {
+            return photodates::get_items(parent_id, &self.media_item);
+        }
+
+        if playedfolders::is_played_folder(parent_id) {
+            return playedfolders::get_items(parent_id, &self.media_item, viewer);
+        }
+
         let mut res_vec: Vec<Item> = Vec::new();
 
         for item in &self.media_item {
             if item.parent_id == parent_id {
-                res_vec.push(item.clone());
+                res_vec.push(Item::clone(item));
             }
         }
 
         return res_vec;
     }
 
+    /// Checks if the Items of the Folder are returned in a meaningful
+    /// Order that should be kept unless a different one is requested,
+    /// e.g. the most recently played Items first.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Id of the Folder
+    pub fn is_ordered_folder(&self, id: u64) -> bool {
+        playedfolders::is_played_folder(id)
+    }
+
     /// Directly returns the Folder with the given Id
     ///
     /// # Arguments
     ///
     /// * `id` - Id of the Folder to get the Values for
-    pub fn get_folder_direct(&self, id: u64) -> Result<Folder, ()> {
+    /// * `viewer` - The Viewer the Played Containers are generated for
+    pub fn get_folder_direct(&self, id: u64, viewer: &Viewer) -> Result<Folder, ()> {
+        if photodates::is_date_folder(id) {
+            return photodates::get_folder(id, &self.media_item).ok_or(());
+        }
+
+        if playedfolders::is_played_folder(id) {
+            return playedfolders::get_folder(id, &self.media_item, viewer).ok_or(());
+        }
+
         for folder in &self.media_folders {
             if folder.id == id {
-                return Ok(folder.clone());
+                return Ok(Folder::clone(folder));
             }
         }
 
@@ -1248,10 +1321,47 @@ impl DatabaseManager {
     pub fn get_item_direct(&self, id: u64) -> Result<Item, ()> {
         for item in &self.media_item {
             if item.id == id {
-                return Ok(item.clone());
+                return Ok(Item::clone(item));
             }
         }
 
         Err(())
     }
+
+    /// Directly returns the Folder at the given Path
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the Folder
+    pub fn get_folder_by_path(&self, path: &str) -> Result<Folder, ()> {
+        match self.media_folders.iter().find(|folder| folder.path == path) {
+            Some(folder) => Ok(Folder::clone(folder)),
+            None => Err(()),
+        }
+    }
+
+    /// Directly returns the Item of the File at the given Path
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the File
+    pub fn get_item_by_path(&self, path: &str) -> Result<Item, ()> {
+        match self.media_item.iter().find(|item| item.file_path == path) {
+            Some(item) => Ok(Item::clone(item)),
+            None => Err(()),
+        }
+    }
+
+    /// Returns the Number of Items of the given Media Type and the
+    /// Size of their Files in Bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `media_type` - Media Type to count
+    pub fn get_media_statistics(&self, media_type: &MediaType) -> (usize, u64) {
+        self.media_item
+            .iter()
+            .filter(|item| item.media_type == *media_type)
+            .fold((0, 0), |(count, size), item| (count + 1, size + item.file_size))
+    }
 }
@@ -1,9 +1,10 @@
-use tools::NameValuePair;
+use tools::{NameValuePair, XMLParser};
 
 /// # Folder
 ///
 /// This Structure holds all Information required for
 /// a Folder and its database representation.
+#[derive(Clone)]
 pub struct Folder {
     pub id: u64,
     pub parent_id: u64,
@@ -44,27 +45,14 @@ impl Folder {
         np_list
     }
 
-    /// This will create a clone of the Folder Structure
-    /// that can be handled indepentandly.
-    pub fn clone(&self) -> Folder {
-        Folder {
-            id: self.id,
-            parent_id: self.parent_id,
-            title: self.title.clone(),
-            path: self.path.clone(),
-            element_count: self.element_count,
-            last_modified: self.last_modified,
-        }
-    }
-
     /// Generates the UPnP XML Description of the Folder, ready to be sent to the Renderer Device
     pub fn generate_upnp_xml(&self) -> String {
         format!(
-            "&lt;container id=\"{}\" childCount=\"{}\" parentID=\"{}\" restricted=\"1\"&gt;&lt;dc:title&gt;{}&lt;/dc:title&gt;&lt;dc:date&gt;{}&lt;/dc:date&gt;&lt;upnp:storageMedium&gt;HDD&lt;/upnp:storageMedium&gt;&lt;upnp:class&gt;object.container.storageFolder&lt;/upnp:class&gt;&lt;/container&gt;",
+            "<container id=\"{}\" childCount=\"{}\" parentID=\"{}\" restricted=\"1\"><dc:title>{}</dc:title><dc:date>{}</dc:date><upnp:storageMedium>HDD</upnp:storageMedium><upnp:class>object.container.storageFolder</upnp:class></container>",
             self.id,
             self.element_count,
             self.parent_id,
-            self.title,
+            XMLParser::escape(&self.title),
             self.last_modified
         )
     }
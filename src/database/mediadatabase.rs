@@ -0,0 +1,209 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::databasemanager::DatabaseManager;
+
+/// Shortest Time between two Snapshots published while Changes are made
+const PUBLISH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Time small Changes are collected before the Database File is written
+const SAVE_DELAY: Duration = Duration::from_secs(10);
+
+/// The Copy of the Database all Changes are made to
+struct WorkingCopy {
+    db: DatabaseManager,
+    changed: bool,
+    published: Instant,
+}
+
+/// # MediaDatabase
+///
+/// Shares the DatabaseManager between the Threads answering
+/// Requests and the ones keeping it up to date.
+///
+/// Readers get an immutable Snapshot of the Database. Taking
+/// one only clones a Pointer, so Readers never wait for each
+/// other and can keep their Snapshot as long as they need.
+/// Writers change a private Working Copy instead. It is
+/// published as the new Snapshot once the Writer is done or
+/// every few Seconds while Changes keep coming in, so Readers
+/// always see a consistent State.
+///
+/// Locks poisoned by a panicking Thread are taken over, as a
+/// single failed Request must not stop the whole Server.
+///
+/// The Time Writers wait for the Working Copy is recorded, so
+/// contention between Scans and other Changes can be watched.
+///
+/// Small Changes made while answering Requests, e.g. Playbacks
+/// and Bookmarks, are written to the File System by a Thread in
+/// the Background. It waits a few Seconds to collect further
+/// Changes, so the File is not rewritten for every single one.
+pub struct MediaDatabase {
+    snapshot: RwLock<Arc<DatabaseManager>>,
+    working: Mutex<WorkingCopy>,
+    saving: Mutex<()>,
+    save_pending: Mutex<bool>,
+    save_requested: Condvar,
+    locks: AtomicU64,
+    lock_wait: AtomicU64,
+}
+
+impl MediaDatabase {
+    /// Creates a new MediaDatabase holding an empty DatabaseManager
+    pub fn new() -> MediaDatabase {
+        MediaDatabase {
+            snapshot: RwLock::new(Arc::new(DatabaseManager::new())),
+            working: Mutex::new(WorkingCopy {
+                db: DatabaseManager::new(),
+                changed: false,
+                published: Instant::now(),
+            }),
+            saving: Mutex::new(()),
+            save_pending: Mutex::new(false),
+            save_requested: Condvar::new(),
+            locks: AtomicU64::new(0),
+            lock_wait: AtomicU64::new(0),
+        }
+    }
+
+    /// Number of Times the Working Copy was locked
+    pub fn get_locks(&self) -> u64 {
+        self.locks.load(Ordering::Relaxed)
+    }
+
+    /// Time spent waiting for the Working Copy in total
+    pub fn get_lock_wait(&self) -> Duration {
+        Duration::from_micros(self.lock_wait.load(Ordering::Relaxed))
+    }
+
+    /// Returns the latest published Snapshot of the Database
+    pub fn read(&self) -> Arc<DatabaseManager> {
+        match self.snapshot.read() {
+            Ok(value) => value.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+
+    /// Applies a Change to the Working Copy and returns its Result.
+    /// The Change becomes visible to Readers with the next Snapshot,
+    /// which is published right away if the last one is old enough.
+    ///
+    /// # Arguments
+    ///
+    /// * `change` - Function changing the Database
+    pub fn update<F, R>(&self, change: F) -> R
+    where
+        F: FnOnce(&mut DatabaseManager) -> R,
+    {
+        let mut working = self.lock_working();
+        let result = change(&mut working.db);
+
+        working.changed = true;
+
+        if working.published.elapsed() >= PUBLISH_INTERVAL {
+            self.publish_working(&mut working);
+        }
+
+        result
+    }
+
+    /// Publishes all Changes made so far as the new Snapshot
+    pub fn publish(&self) {
+        let mut working = self.lock_working();
+
+        if working.changed {
+            self.publish_working(&mut working);
+        }
+    }
+
+    /// Writes the latest Snapshot to the File System. Only one
+    /// Thread writes the File at a Time.
+    ///
+    /// # Arguments
+    ///
+    /// * `check_changed` - Leave out Files that were removed or modified
+    pub fn save(&self, check_changed: bool) {
+        let _saving = match self.saving.lock() {
+            Ok(value) => value,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        self.read().save_database(check_changed);
+    }
+
+    /// Asks the Background Thread to write the latest Snapshot to
+    /// the File System. Returns immediately.
+    pub fn save_later(&self) {
+        let mut pending = match self.save_pending.lock() {
+            Ok(value) => value,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        *pending = true;
+        self.save_requested.notify_one();
+    }
+
+    /// Starts the Thread writing the Database File in the Background
+    /// once save_later() was called.
+    pub fn start_saver(&'static self) {
+        thread::spawn(move || loop {
+            {
+                let mut pending = match self.save_pending.lock() {
+                    Ok(value) => value,
+                    Err(poisoned) => poisoned.into_inner(),
+                };
+
+                while !*pending {
+                    pending = match self.save_requested.wait(pending) {
+                        Ok(value) => value,
+                        Err(poisoned) => poisoned.into_inner(),
+                    };
+                }
+            }
+
+            // Collect further Changes before the File is written
+            thread::sleep(SAVE_DELAY);
+
+            match self.save_pending.lock() {
+                Ok(mut value) => *value = false,
+                Err(poisoned) => *poisoned.into_inner() = false,
+            }
+
+            self.save(false);
+        });
+    }
+
+    /// Replaces the Snapshot by a Copy of the Working Copy
+    ///
+    /// # Arguments
+    ///
+    /// * `working` - The locked Working Copy
+    fn publish_working(&self, working: &mut WorkingCopy) {
+        let snapshot = Arc::new(working.db.clone());
+
+        match self.snapshot.write() {
+            Ok(mut value) => *value = snapshot,
+            Err(poisoned) => *poisoned.into_inner() = snapshot,
+        }
+
+        working.changed = false;
+        working.published = Instant::now();
+    }
+
+    /// Locks the Working Copy
+    fn lock_working(&self) -> MutexGuard<'_, WorkingCopy> {
+        let begin = Instant::now();
+        let working = match self.working.lock() {
+            Ok(value) => value,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        self.locks.fetch_add(1, Ordering::Relaxed);
+        self.lock_wait.fetch_add(begin.elapsed().as_micros() as u64, Ordering::Relaxed);
+
+        working
+    }
+}
@@ -1,5 +1,17 @@
 mod databasemanager;
 pub use self::databasemanager::DatabaseManager;
 
+mod mediadatabase;
+pub use self::mediadatabase::MediaDatabase;
+
 mod folder;
 pub use self::folder::Folder;
+
+mod photodates;
+
+mod playedfolders;
+pub use self::playedfolders::Viewer;
+
+mod scanner;
+pub use self::scanner::Scanner;
+pub use self::scanner::ScanProgress;
@@ -0,0 +1,202 @@
+use chrono::{Datelike, Local, TimeZone};
+use std::sync::Arc;
+
+use super::folder::Folder;
+use media::{Item, MediaType};
+
+/// Id of the "Photos by Date" Container. The Ids of the Year and
+/// Month Containers below are derived from it, they can not
+/// collide with the Ids of the Database which count up from 1.
+const DATE_FOLDER_ID: u64 = 0x8000_0000_0000_0000;
+
+/// Title of the "Photos by Date" Container
+const DATE_FOLDER_TITLE: &str = "Photos by Date";
+
+/// Checks if the Id belongs to one of the Date Containers
+///
+/// # Arguments
+///
+/// * `id` - Id to check
+pub fn is_date_folder(id: u64) -> bool {
+    id & DATE_FOLDER_ID != 0
+}
+
+/// Returns the virtual Containers below the given Parent. The
+/// Root gets the "Photos by Date" Container if there are any
+/// Pictures, which holds one Container per Year and these one
+/// per Month.
+///
+/// # Arguments
+///
+/// * `parent_id` - Id of the Parent Container
+/// * `items` - All Items of the Database
+pub fn get_folders(parent_id: u64, items: &[Arc<Item>]) -> Vec<Folder> {
+    let months = get_months(items);
+    let mut folders: Vec<Folder> = Vec::new();
+
+    if parent_id == 0 {
+        if !months.is_empty() {
+            let mut years: Vec<u32> = months.iter().map(|month| month.0).collect();
+            years.dedup();
+
+            folders.push(new_folder(DATE_FOLDER_ID, 0, DATE_FOLDER_TITLE, years.len()));
+        }
+    } else if parent_id == DATE_FOLDER_ID {
+        let mut years: Vec<u32> = months.iter().map(|month| month.0).collect();
+        years.dedup();
+
+        for year in years {
+            let count = months.iter().filter(|month| month.0 == year).count();
+            folders.push(new_folder(get_year_id(year), parent_id, &year.to_string(), count));
+        }
+    } else if let (Some(year), None) = split_id(parent_id) {
+        for &(month_year, month, count) in &months {
+            if month_year == year {
+                // "YYYY-MM" keeps the Months in Order when sorted by Title
+                let title = format!("{}-{:02}", year, month);
+                folders.push(new_folder(get_year_id(year) + u64::from(month), parent_id, &title, count));
+            }
+        }
+    }
+
+    folders
+}
+
+/// Returns the virtual Container with the given Id
+///
+/// # Arguments
+///
+/// * `id` - Id of the Container
+/// * `items` - All Items of the Database
+pub fn get_folder(id: u64, items: &[Arc<Item>]) -> Option<Folder> {
+    let parent_id = match split_id(id) {
+        (None, _) => 0,
+        (Some(_), None) => DATE_FOLDER_ID,
+        (Some(year), Some(_)) => get_year_id(year),
+    };
+
+    get_folders(parent_id, items).into_iter().find(|folder| folder.id == id)
+}
+
+/// Returns the Pictures taken in the Month of a Month Container.
+/// Their Parent is set to the Container.
+///
+/// # Arguments
+///
+/// * `parent_id` - Id of the Month Container
+/// * `items` - All Items of the Database
+pub fn get_items(parent_id: u64, items: &[Arc<Item>]) -> Vec<Item> {
+    let (year, month) = match split_id(parent_id) {
+        (Some(year), Some(month)) => (year, month),
+        _ => return Vec::new(),
+    };
+
+    let mut result: Vec<Item> = Vec::new();
+
+    for item in items {
+        if get_month(item) == Some((year, month)) {
+            let mut value = Item::clone(item);
+            value.parent_id = parent_id;
+            result.push(value);
+        }
+    }
+
+    result
+}
+
+/// Returns the Year and Month a Picture was taken. This is the Date
+/// from its EXIF or XMP Information, or its Modification Time if
+/// there is none. Returns None for everything else than Pictures.
+///
+/// # Arguments
+///
+/// * `item` - Item to get the Month for
+fn get_month(item: &Item) -> Option<(u32, u32)> {
+    if item.media_type != MediaType::PICTURE {
+        return None;
+    }
+
+    let date = &item.meta_data.date;
+
+    let year = date.get(..4).and_then(|value| value.parse::<u32>().ok());
+    let month = date.get(5..7).and_then(|value| value.parse::<u32>().ok());
+
+    if let (Some(year), Some(month)) = (year, month) {
+        if (1..=12).contains(&month) {
+            return Some((year, month));
+        }
+    }
+
+    let modified = Local.timestamp_opt(item.last_modified as i64, 0).single()?;
+
+    Some((modified.year() as u32, modified.month()))
+}
+
+/// Returns the sorted List of Months that got Pictures, each with
+/// the Number of Pictures taken in it.
+///
+/// # Arguments
+///
+/// * `items` - All Items of the Database
+fn get_months(items: &[Arc<Item>]) -> Vec<(u32, u32, usize)> {
+    let mut months: Vec<(u32, u32)> = items.iter().filter_map(|item| get_month(item)).collect();
+    months.sort();
+
+    let mut result: Vec<(u32, u32, usize)> = Vec::new();
+
+    for (year, month) in months {
+        match result.last_mut() {
+            Some(last) if last.0 == year && last.1 == month => last.2 += 1,
+            _ => result.push((year, month, 1)),
+        }
+    }
+
+    result
+}
+
+/// Returns the Id of a Year Container
+///
+/// # Arguments
+///
+/// * `year` - Year of the Container
+fn get_year_id(year: u32) -> u64 {
+    DATE_FOLDER_ID + u64::from(year) * 100
+}
+
+/// Splits the Id of a Date Container into its Year and Month
+///
+/// # Arguments
+///
+/// * `id` - Id of the Container
+fn split_id(id: u64) -> (Option<u32>, Option<u32>) {
+    let value = id & !DATE_FOLDER_ID;
+
+    if value == 0 {
+        return (None, None);
+    }
+
+    let year = (value / 100) as u32;
+
+    match (value % 100) as u32 {
+        0 => (Some(year), None),
+        month => (Some(year), Some(month)),
+    }
+}
+
+/// Creates a virtual Container
+///
+/// # Arguments
+///
+/// * `id` - Id of the Container
+/// * `parent_id` - Id of the Parent Container
+/// * `title` - Title to display
+/// * `count` - Number of Children
+fn new_folder(id: u64, parent_id: u64, title: &str, count: usize) -> Folder {
+    let mut folder = Folder::new();
+    folder.id = id;
+    folder.parent_id = parent_id;
+    folder.title = title.to_string();
+    folder.element_count = count as u32;
+
+    folder
+}
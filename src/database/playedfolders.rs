@@ -0,0 +1,201 @@
+use std::cmp::Reverse;
+use std::sync::Arc;
+
+use super::folder::Folder;
+use media::{Bookmark, Item, MediaType};
+
+/// Id of the "Recently Played" Container. Like the Photos by Date
+/// Containers it uses a Bit the Ids of the Database never reach.
+const RECENT_FOLDER_ID: u64 = 0x4000_0000_0000_0001;
+
+/// Id of the "Most Played" Container
+const MOST_FOLDER_ID: u64 = 0x4000_0000_0000_0002;
+
+/// Id of the "Continue Watching" Container
+const CONTINUE_FOLDER_ID: u64 = 0x4000_0000_0000_0003;
+
+/// Title of the "Recently Played" Container
+const RECENT_FOLDER_TITLE: &str = "Recently Played";
+
+/// Title of the "Most Played" Container
+const MOST_FOLDER_TITLE: &str = "Most Played";
+
+/// Title of the "Continue Watching" Container
+const CONTINUE_FOLDER_TITLE: &str = "Continue Watching";
+
+/// Largest Number of Items inside each Container
+const MAX_ITEMS: usize = 50;
+
+/// # Viewer
+///
+/// The Renderer or Browser the Played Containers are generated for.
+/// They only count and list the Items it is able to see, so their
+/// Child Count matches the Items returned by a Browse. "Continue
+/// Watching" only holds the Videos it has a Bookmark of.
+pub struct Viewer<'a> {
+    pub is_visible: Box<dyn Fn(&Item) -> bool + 'a>, // Checks if the Item is shown to the Viewer
+    pub bookmark_key: Option<&'a str>, // Key of the Bookmarks of the Viewer. None for the Bookmarks of all Renderers
+}
+
+impl<'a> Viewer<'a> {
+    /// Creates a Viewer that sees every Item
+    pub fn anyone() -> Viewer<'a> {
+        Viewer {
+            is_visible: Box::new(|_| true),
+            bookmark_key: None,
+        }
+    }
+}
+
+/// Checks if the Id belongs to one of the Played Containers
+///
+/// # Arguments
+///
+/// * `id` - Id to check
+pub fn is_played_folder(id: u64) -> bool {
+    id == RECENT_FOLDER_ID || id == MOST_FOLDER_ID || id == CONTINUE_FOLDER_ID
+}
+
+/// Returns the virtual Containers below the given Parent. The Root
+/// gets the "Recently Played" and "Most Played" Containers once any
+/// Item was played and the "Continue Watching" Container once any
+/// Video was partially watched.
+///
+/// # Arguments
+///
+/// * `parent_id` - Id of the Parent Container
+/// * `items` - All Items of the Database
+/// * `viewer` - The Viewer the Containers are generated for
+pub fn get_folders(parent_id: u64, items: &[Arc<Item>], viewer: &Viewer) -> Vec<Folder> {
+    if parent_id != 0 {
+        return Vec::new();
+    }
+
+    let mut folders: Vec<Folder> = Vec::new();
+    let count = items
+        .iter()
+        .filter(|item| item.play_count > 0 && (viewer.is_visible)(item))
+        .count()
+        .min(MAX_ITEMS);
+
+    if count > 0 {
+        folders.push(new_folder(RECENT_FOLDER_ID, RECENT_FOLDER_TITLE, count));
+        folders.push(new_folder(MOST_FOLDER_ID, MOST_FOLDER_TITLE, count));
+    }
+
+    let count = items
+        .iter()
+        .filter(|item| is_partially_watched(item, viewer) && (viewer.is_visible)(item))
+        .count()
+        .min(MAX_ITEMS);
+
+    if count > 0 {
+        folders.push(new_folder(CONTINUE_FOLDER_ID, CONTINUE_FOLDER_TITLE, count));
+    }
+
+    folders
+}
+
+/// Returns the virtual Container with the given Id
+///
+/// # Arguments
+///
+/// * `id` - Id of the Container
+/// * `items` - All Items of the Database
+/// * `viewer` - The Viewer the Container is generated for
+pub fn get_folder(id: u64, items: &[Arc<Item>], viewer: &Viewer) -> Option<Folder> {
+    get_folders(0, items, viewer).into_iter().find(|folder| folder.id == id)
+}
+
+/// Returns the Items of a Container, the most recently or most often
+/// played ones or the most recently watched Videos first. Their
+/// Parent is set to the Container.
+///
+/// # Arguments
+///
+/// * `parent_id` - Id of the Container
+/// * `items` - All Items of the Database
+/// * `viewer` - The Viewer the Items are listed for
+pub fn get_items(parent_id: u64, items: &[Arc<Item>], viewer: &Viewer) -> Vec<Item> {
+    let mut played: Vec<&Arc<Item>> = match parent_id {
+        CONTINUE_FOLDER_ID => items.iter().filter(|item| is_partially_watched(item, viewer)).collect(),
+        _ => items.iter().filter(|item| item.play_count > 0).collect(),
+    };
+
+    // Hidden Items must not take the Places of visible ones
+    played.retain(|item| (viewer.is_visible)(item));
+
+    match parent_id {
+        RECENT_FOLDER_ID => played.sort_by_key(|item| Reverse(item.last_played)),
+        MOST_FOLDER_ID => {
+            played.sort_by(|a, b| {
+                b.play_count.cmp(&a.play_count).then_with(|| b.last_played.cmp(&a.last_played))
+            })
+        }
+        CONTINUE_FOLDER_ID => played.sort_by_key(|item| Reverse(get_last_bookmark(item, viewer))),
+        _ => return Vec::new(),
+    }
+
+    played
+        .into_iter()
+        .take(MAX_ITEMS)
+        .map(|item| {
+            let mut value = Item::clone(item);
+            value.parent_id = parent_id;
+            value
+        })
+        .collect()
+}
+
+/// Checks if the Item is a Video with a Bookmark of the Viewer
+///
+/// # Arguments
+///
+/// * `item` - Item to check
+/// * `viewer` - The Viewer the Bookmark has to belong to
+fn is_partially_watched(item: &Item, viewer: &Viewer) -> bool {
+    item.media_type == MediaType::VIDEO && item.bookmarks.iter().any(|bookmark| is_own_bookmark(bookmark, viewer))
+}
+
+/// Returns the UNIX Timestamp the newest Bookmark of the Viewer
+/// was set at
+///
+/// # Arguments
+///
+/// * `item` - Item to check
+/// * `viewer` - The Viewer the Bookmark has to belong to
+fn get_last_bookmark(item: &Item, viewer: &Viewer) -> u64 {
+    item.bookmarks
+        .iter()
+        .filter(|bookmark| is_own_bookmark(bookmark, viewer))
+        .map(|bookmark| bookmark.updated)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Checks if the Bookmark belongs to the Viewer
+///
+/// # Arguments
+///
+/// * `bookmark` - Bookmark to check
+/// * `viewer` - The Viewer to check for
+fn is_own_bookmark(bookmark: &Bookmark, viewer: &Viewer) -> bool {
+    viewer.bookmark_key.is_none_or(|key| bookmark.renderer == key)
+}
+
+/// Creates a virtual Container below the Root
+///
+/// # Arguments
+///
+/// * `id` - Id of the Container
+/// * `title` - Title to display
+/// * `count` - Number of Children
+fn new_folder(id: u64, title: &str, count: usize) -> Folder {
+    let mut folder = Folder::new();
+    folder.id = id;
+    folder.parent_id = 0;
+    folder.title = title.to_string();
+    folder.element_count = count as u32;
+
+    folder
+}
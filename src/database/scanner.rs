@@ -0,0 +1,701 @@
+use std::collections::HashSet;
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicUsize, AtomicU64, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::thread;
+use std::time;
+
+use super::mediadatabase::MediaDatabase;
+use configuration::ShareConfiguration;
+use media::{Item, MediaType, mediaparser};
+use tools::{NameValuePair, Logger, LogLevel};
+
+/// Number of parsed Files after which the Progress is written to the Log
+const PROGRESS_LOG_INTERVAL: usize = 100;
+
+/// # ScanProgress
+///
+/// Keeps track of a running Library Scan. All Counters can be
+/// updated and read from any Thread without locking, so the
+/// Status can be reported while the Scan is still running.
+pub struct ScanProgress {
+    running: AtomicBool,
+    found: AtomicUsize,
+    unchanged: AtomicUsize,
+    parsed: AtomicUsize,
+    failed: AtomicUsize,
+    started: AtomicU64,
+    finished: AtomicU64,
+    scans: AtomicU64,
+    scan_time: AtomicU64,
+    last_scan_time: AtomicU64,
+}
+
+impl ScanProgress {
+    /// Creates a new ScanProgress with all Counters set to Zero
+    pub fn new() -> ScanProgress {
+        ScanProgress {
+            running: AtomicBool::new(false),
+            found: AtomicUsize::new(0),
+            unchanged: AtomicUsize::new(0),
+            parsed: AtomicUsize::new(0),
+            failed: AtomicUsize::new(0),
+            started: AtomicU64::new(0),
+            finished: AtomicU64::new(0),
+            scans: AtomicU64::new(0),
+            scan_time: AtomicU64::new(0),
+            last_scan_time: AtomicU64::new(0),
+        }
+    }
+
+    /// Marks a new Scan as running and resets all Counters. Checking
+    /// and marking happen in a single Step, so only one of several
+    /// concurrent Callers succeeds. Returns false if a Scan is
+    /// already running.
+    fn start(&self) -> bool {
+        if self.running
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            return false;
+        }
+
+        self.found.store(0, Ordering::SeqCst);
+        self.unchanged.store(0, Ordering::SeqCst);
+        self.parsed.store(0, Ordering::SeqCst);
+        self.failed.store(0, Ordering::SeqCst);
+        self.started.store(ScanProgress::now(), Ordering::SeqCst);
+        self.finished.store(0, Ordering::SeqCst);
+
+        true
+    }
+
+    /// Marks the Scan as done
+    ///
+    /// # Arguments
+    ///
+    /// * `duration` - Time the Scan took
+    fn finish(&self, duration: time::Duration) {
+        let millis = duration.as_millis() as u64;
+
+        self.scans.fetch_add(1, Ordering::SeqCst);
+        self.scan_time.fetch_add(millis, Ordering::SeqCst);
+        self.last_scan_time.store(millis, Ordering::SeqCst);
+        self.finished.store(ScanProgress::now(), Ordering::SeqCst);
+        self.running.store(false, Ordering::SeqCst);
+    }
+
+    /// Returns true while a Scan is in Progress
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    /// Number of Files found inside the Shares
+    pub fn get_found(&self) -> usize {
+        self.found.load(Ordering::SeqCst)
+    }
+
+    /// Number of Files that are already up to date inside the Database
+    pub fn get_unchanged(&self) -> usize {
+        self.unchanged.load(Ordering::SeqCst)
+    }
+
+    /// Number of new or modified Files that were parsed successfully
+    pub fn get_parsed(&self) -> usize {
+        self.parsed.load(Ordering::SeqCst)
+    }
+
+    /// Number of Files that could not be parsed
+    pub fn get_failed(&self) -> usize {
+        self.failed.load(Ordering::SeqCst)
+    }
+
+    /// UNIX Timestamp the last Scan was started at. 0 if never
+    pub fn get_started(&self) -> u64 {
+        self.started.load(Ordering::SeqCst)
+    }
+
+    /// UNIX Timestamp the last Scan was finished at. 0 if still running or never started
+    pub fn get_finished(&self) -> u64 {
+        self.finished.load(Ordering::SeqCst)
+    }
+
+    /// Number of Scans finished since the Server was started
+    pub fn get_scans(&self) -> u64 {
+        self.scans.load(Ordering::SeqCst)
+    }
+
+    /// Time all Scans finished since the Server was started took together
+    pub fn get_scan_time(&self) -> time::Duration {
+        time::Duration::from_millis(self.scan_time.load(Ordering::SeqCst))
+    }
+
+    /// Time the last finished Scan took
+    pub fn get_last_scan_time(&self) -> time::Duration {
+        time::Duration::from_millis(self.last_scan_time.load(Ordering::SeqCst))
+    }
+
+    /// Get a List of Name-Value Pairs representing the current Progress
+    pub fn get_name_value_pairs(&self) -> Vec<NameValuePair> {
+        vec![
+            NameValuePair::new("running", &self.is_running().to_string()),
+            NameValuePair::new("found", &self.get_found().to_string()),
+            NameValuePair::new("unchanged", &self.get_unchanged().to_string()),
+            NameValuePair::new("parsed", &self.get_parsed().to_string()),
+            NameValuePair::new("failed", &self.get_failed().to_string()),
+            NameValuePair::new("started", &self.get_started().to_string()),
+            NameValuePair::new("finished", &self.get_finished().to_string()),
+        ]
+    }
+
+    /// Current Time as UNIX Timestamp
+    fn now() -> u64 {
+        match time::SystemTime::now().duration_since(time::UNIX_EPOCH) {
+            Ok(value) => value.as_secs(),
+            Err(_) => 0,
+        }
+    }
+}
+
+/// A single File that needs to be parsed by the Workers
+struct ScanJob {
+    path: String,
+    parent_id: u64,
+    media_type: Option<MediaType>,
+}
+
+/// A Share or a single File or Folder inside of it to scan
+struct ScanTarget {
+    share: ShareConfiguration,
+    path: String,
+}
+
+/// # Scanner
+///
+/// Walks all Shares in the Background and brings the Media Database
+/// up to date with the File System. Walking the Folders happens in
+/// a single Thread, while new or modified Files are handed to a
+/// bounded Pool of Workers that run the MediaParser.
+///
+/// The Changes are published as Snapshots every few Seconds, so the
+/// Server can answer Requests with whatever is already known while
+/// the Scan is running.
+pub struct Scanner {
+    db: &'static MediaDatabase,
+    progress: &'static ScanProgress,
+    logger: Logger,
+}
+
+impl Scanner {
+    /// Starts a Scan of the given Shares in a new Thread and returns
+    /// immediately. Does nothing and returns false if a Scan is
+    /// already running.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The Media Database to update
+    /// * `progress` - Progress Structure to report to
+    /// * `shares` - Shares to scan
+    /// * `workers` - Number of parallel MediaParser Workers
+    /// * `logger` - Logger to use
+    pub fn start(
+        db: &'static MediaDatabase,
+        progress: &'static ScanProgress,
+        shares: Vec<ShareConfiguration>,
+        workers: usize,
+        logger: Logger,
+    ) -> bool {
+        let targets = shares
+            .into_iter()
+            .map(|share| ScanTarget { path: share.path.clone(), share })
+            .collect();
+
+        Scanner::spawn(db, progress, targets, workers, logger)
+    }
+
+    /// Starts a Scan of a single File or Folder inside a Share in a
+    /// new Thread and returns immediately. Does nothing and returns
+    /// false if a Scan is already running.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The Media Database to update
+    /// * `progress` - Progress Structure to report to
+    /// * `share` - Share the Path lays in
+    /// * `path` - Path of the File or Folder to scan
+    /// * `workers` - Number of parallel MediaParser Workers
+    /// * `logger` - Logger to use
+    pub fn start_path(
+        db: &'static MediaDatabase,
+        progress: &'static ScanProgress,
+        share: ShareConfiguration,
+        path: &str,
+        workers: usize,
+        logger: Logger,
+    ) -> bool {
+        let targets = vec![ScanTarget { share, path: path.to_string() }];
+
+        Scanner::spawn(db, progress, targets, workers, logger)
+    }
+
+    /// Runs the Scan of the given Targets in a new Thread
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The Media Database to update
+    /// * `progress` - Progress Structure to report to
+    /// * `targets` - Shares or Paths to scan
+    /// * `workers` - Number of parallel MediaParser Workers
+    /// * `logger` - Logger to use
+    fn spawn(
+        db: &'static MediaDatabase,
+        progress: &'static ScanProgress,
+        targets: Vec<ScanTarget>,
+        workers: usize,
+        logger: Logger,
+    ) -> bool {
+        if !progress.start() {
+            logger.write_log(
+                "Scanner: Scan is already running. Ignoring Request.",
+                LogLevel::DEBUG,
+            );
+            return false;
+        }
+
+        thread::spawn(move || {
+            let scanner = Scanner {
+                db,
+                progress,
+                logger,
+            };
+            scanner.run(&targets, workers);
+        });
+
+        true
+    }
+
+    /// Performs the Scan. Spawns the Workers, walks all Shares and
+    /// waits for every queued File to be parsed before the Database
+    /// is written back to the File System.
+    fn run(&self, targets: &[ScanTarget], workers: usize) {
+        let begin = time::Instant::now();
+        let workers = if workers == 0 { 1 } else { workers };
+        let (sender, receiver) = sync_channel::<ScanJob>(workers * 4);
+        let receiver = Arc::new(Mutex::new(receiver));
+        let mut handles = Vec::new();
+
+        self.logger.write_log(
+            &format!("Scanner: Scanning {} Locations using {} Workers...", targets.len(), workers),
+            LogLevel::INFORMATION,
+        );
+
+        for _ in 0..workers {
+            let receiver = receiver.clone();
+            let db = self.db;
+            let progress = self.progress;
+            let logger = self.logger.clone();
+
+            handles.push(thread::spawn(move || {
+                Scanner::parse_worker(&receiver, db, progress, &logger);
+            }));
+        }
+
+        // Only the Workers may hold the Queue, so it is closed once all of them died
+        drop(receiver);
+
+        // Folders reached through several symbolic Links are only walked once
+        let mut visited: HashSet<PathBuf> = HashSet::new();
+
+        for target in targets {
+            self.scan_target(target, &mut visited, &sender);
+        }
+
+        // Close the Queue and wait until everything is parsed
+        drop(sender);
+        for handle in handles {
+            if handle.join().is_err() {
+                self.logger.write_log("Scanner: A Worker Thread crashed!", LogLevel::ERROR);
+            }
+        }
+
+        // Readers get the final State, while it is written to the File System
+        self.db.publish();
+
+        self.db.save(true);
+        let db = self.db.read();
+        let (folders, items) = (db.get_folder_count(), db.get_item_count());
+
+        self.progress.finish(begin.elapsed());
+
+        self.logger.write_log(
+            &format!(
+                "Scanner: Scan finished in {}s. Found {} Files: {} unchanged, {} parsed, {} failed. There is a total of {} Folders and {} Files available.",
+                self.progress.get_finished() - self.progress.get_started(),
+                self.progress.get_found(),
+                self.progress.get_unchanged(),
+                self.progress.get_parsed(),
+                self.progress.get_failed(),
+                folders,
+                items
+            ),
+            LogLevel::INFORMATION,
+        );
+    }
+
+    /// Scans a Target. A whole Share is walked from its Root, while
+    /// a single Folder is walked below its known Parent Folder and a
+    /// single File is parsed again right away. If the Parent Folder
+    /// is not known yet the whole Share is walked instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The Share or Path to scan
+    /// * `visited` - Canonical Paths of all Folders walked during this Scan
+    /// * `jobs` - Queue of the Workers
+    fn scan_target(&self, target: &ScanTarget, visited: &mut HashSet<PathBuf>, jobs: &SyncSender<ScanJob>) {
+        let share = &target.share;
+        let path = Path::new(&target.path);
+
+        if target.path == share.path {
+            self.scan_folder(&share.path, 0, share, visited, jobs);
+            return;
+        }
+
+        let parent_path = match path.parent() {
+            Some(value) => value.to_string_lossy().to_string(),
+            None => String::new(),
+        };
+
+        let parent_id = match self.db.read().get_folder_by_path(&parent_path) {
+            Ok(folder) => folder.id,
+            Err(_) => {
+                self.logger.write_log(
+                    &format!("Scanner: Folder: {} is unknown. Scanning the whole Share instead.", parent_path),
+                    LogLevel::INFORMATION,
+                );
+                self.scan_folder(&share.path, 0, share, visited, jobs);
+                return;
+            }
+        };
+
+        if path.is_dir() {
+            self.scan_folder(&target.path, parent_id, share, visited, jobs);
+            return;
+        }
+
+        if !path.exists() || share.is_excluded(&target.path) {
+            self.logger.write_log(
+                &format!("Scanner: Removing File: {}", target.path),
+                LogLevel::VERBOSE,
+            );
+            self.db.update(|db| db.remove_item_from_path(&target.path));
+            return;
+        }
+
+        self.progress.found.fetch_add(1, Ordering::SeqCst);
+
+        let job = ScanJob {
+            path: target.path.clone(),
+            parent_id,
+            media_type: share.media_type.clone(),
+        };
+
+        if jobs.send(job).is_err() {
+            self.logger.write_log(
+                "Scanner: All Workers are gone. Aborting Scan!",
+                LogLevel::ERROR,
+            );
+        }
+    }
+
+    /// Walks a Folder and all of its Sub Folders. The Folder itself
+    /// is added to the Database right away, while every new or
+    /// modified File is queued for the Workers.
+    ///
+    /// The Settings of the Share the Folder belongs to are honoured:
+    /// Excluded Elements, Folders below the maximum Depth and (if
+    /// disabled) symbolic Links are skipped. Folders already walked
+    /// during this Scan are skipped as well, so symbolic Links
+    /// pointing at each other can not cause an endless Scan.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the Folder that should be parsed
+    /// * `parent_id` - Id of the Parent Folder this one lays in
+    /// * `share` - Settings of the Share the Folder belongs to
+    /// * `visited` - Canonical Paths of all Folders walked during this Scan
+    /// * `jobs` - Queue of the Workers
+    fn scan_folder(
+        &self,
+        path: &str,
+        parent_id: u64,
+        share: &ShareConfiguration,
+        visited: &mut HashSet<PathBuf>,
+        jobs: &SyncSender<ScanJob>,
+    ) {
+        self.logger.write_log(
+            &format!("Scanner: Parsing Folder: {}", path),
+            LogLevel::DEBUG,
+        );
+
+        // If the path does not exits -> return
+        if path.is_empty() || !Path::new(path).exists() {
+            self.logger.write_log(
+                &format!("Scanner: Unable to parse Folder: {}", path),
+                LogLevel::ERROR,
+            );
+            return;
+        }
+
+        // Apply the Share Settings to everything but the Shares Root
+        let is_root = parent_id == 0;
+
+        if !is_root {
+            if !share.allows_depth(share.get_depth(path)) {
+                self.logger.write_log(
+                    &format!("Scanner: Skipping Folder below maximum Depth: {}", path),
+                    LogLevel::DEBUG,
+                );
+                return;
+            }
+
+            if share.is_excluded(path) {
+                self.logger.write_log(
+                    &format!("Scanner: Skipping excluded Folder: {}", path),
+                    LogLevel::DEBUG,
+                );
+                return;
+            }
+
+            // Skip Folders that are hidden
+            if Scanner::get_file_name(path).starts_with('.') {
+                self.logger.write_log(
+                    &format!("Scanner: Skipping hidden Folder: {}", path),
+                    LogLevel::DEBUG,
+                );
+                return;
+            }
+        }
+
+        let canonical = match fs::canonicalize(path) {
+            Ok(value) => value,
+            Err(e) => {
+                self.logger.write_log(
+                    &format!("Scanner: Unable to resolve Folder: {} - Reason: {}", path, e),
+                    LogLevel::ERROR,
+                );
+                return;
+            }
+        };
+
+        if !visited.insert(canonical) {
+            self.logger.write_log(
+                &format!("Scanner: Skipping Folder that was already scanned: {}", path),
+                LogLevel::DEBUG,
+            );
+            return;
+        }
+
+        let title = if is_root {
+            share.get_title()
+        } else {
+            Scanner::get_file_name(path)
+        };
+
+        let id = self.db.update(|db| db.update_folder(path, parent_id, &title));
+
+        // Go through all Elements inside this Folder and add them
+        let paths = match fs::read_dir(path) {
+            Ok(value) => value,
+            Err(e) => {
+                self.logger.write_log(
+                    &format!(
+                        "Scanner: Unable to access Elements in: {} - Reason: {}",
+                        path,
+                        e
+                    ),
+                    LogLevel::ERROR,
+                );
+                return;
+            }
+        };
+
+        for path in paths {
+            let element = match path {
+                Ok(value) => value,
+                Err(e) => {
+                    self.logger.write_log(
+                        &format!("Scanner: Unable to access Sub Elements! - Reason: {}", e),
+                        LogLevel::ERROR,
+                    );
+                    continue;
+                }
+            };
+            let ele_path = element.path();
+            let ele_str = match ele_path.to_str() {
+                Some(value) => value,
+                None => {
+                    self.logger.write_log("Scanner: Unable to convert to str", LogLevel::ERROR);
+                    continue;
+                }
+            };
+
+            // Check symbolic Links
+            let is_symlink = match element.file_type() {
+                Ok(value) => value.is_symlink(),
+                Err(_) => false,
+            };
+
+            if is_symlink && !share.follow_symlinks {
+                self.logger.write_log(
+                    &format!("Scanner: Skipping symbolic Link: {}", ele_str),
+                    LogLevel::DEBUG,
+                );
+                continue;
+            }
+
+            // If this is another folder -> parse it too
+            if ele_path.is_dir() {
+                self.scan_folder(ele_str, id, share, visited, jobs);
+                continue;
+            }
+
+            // Skip hidden and excluded Files
+            if Scanner::get_file_name(ele_str).starts_with('.') {
+                self.logger.write_log(
+                    &format!("Scanner: Skipping hidden File: {}", ele_str),
+                    LogLevel::DEBUG,
+                );
+                continue;
+            }
+
+            if share.is_excluded(ele_str) {
+                self.logger.write_log(
+                    &format!("Scanner: Skipping excluded File: {}", ele_str),
+                    LogLevel::DEBUG,
+                );
+                continue;
+            }
+
+            self.progress.found.fetch_add(1, Ordering::SeqCst);
+
+            if !self.db.read().needs_parsing(ele_str) {
+                self.progress.unchanged.fetch_add(1, Ordering::SeqCst);
+                continue;
+            }
+
+            let job = ScanJob {
+                path: ele_str.to_string(),
+                parent_id: id,
+                media_type: share.media_type.clone(),
+            };
+
+            if jobs.send(job).is_err() {
+                self.logger.write_log(
+                    "Scanner: All Workers are gone. Aborting Scan!",
+                    LogLevel::ERROR,
+                );
+                return;
+            }
+        }
+    }
+
+    /// Takes Jobs from the Queue until it is closed, parses the Files
+    /// and inserts them into the Database.
+    ///
+    /// # Arguments
+    ///
+    /// * `receiver` - The shared Job Queue
+    /// * `db` - The Media Database to update
+    /// * `progress` - Progress Structure to report to
+    /// * `logger` - Logger to use
+    fn parse_worker(
+        receiver: &Arc<Mutex<Receiver<ScanJob>>>,
+        db: &'static MediaDatabase,
+        progress: &'static ScanProgress,
+        logger: &Logger,
+    ) {
+        loop {
+            let job = match receiver.lock() {
+                Ok(value) => {
+                    match value.recv() {
+                        Ok(job) => job,
+                        Err(_) => break,
+                    }
+                }
+                Err(_) => break,
+            };
+
+            let mut item: Item = Item::new();
+
+            // A broken File must not take the Worker down with it
+            let parsed = match panic::catch_unwind(AssertUnwindSafe(|| mediaparser::parse_file(&job.path, &mut item))) {
+                Ok(value) => value,
+                Err(_) => {
+                    logger.write_log(
+                        &format!("Scanner: Parsing crashed on File: {}", job.path),
+                        LogLevel::ERROR,
+                    );
+                    false
+                }
+            };
+
+            if parsed {
+                let allowed = match job.media_type {
+                    Some(ref media_type) => *media_type == item.media_type,
+                    None => true,
+                };
+
+                if allowed {
+                    db.update(|value| value.insert_item(item, job.parent_id));
+                } else {
+                    logger.write_log(
+                        &format!(
+                            "Scanner: Skipping File of other Media Type: {}",
+                            job.path
+                        ),
+                        LogLevel::DEBUG,
+                    );
+                    db.update(|value| value.remove_item_from_path(&job.path));
+                }
+                progress.parsed.fetch_add(1, Ordering::SeqCst);
+            } else {
+                logger.write_log(
+                    &format!("Scanner: Unable to parse File: {}", job.path),
+                    LogLevel::ERROR,
+                );
+                progress.failed.fetch_add(1, Ordering::SeqCst);
+            }
+
+            let done = progress.get_parsed() + progress.get_failed();
+            if done.is_multiple_of(PROGRESS_LOG_INTERVAL) {
+                logger.write_log(
+                    &format!(
+                        "Scanner: Progress: {} Files found, {} unchanged, {} parsed, {} failed",
+                        progress.get_found(),
+                        progress.get_unchanged(),
+                        progress.get_parsed(),
+                        progress.get_failed()
+                    ),
+                    LogLevel::INFORMATION,
+                );
+            }
+        }
+    }
+
+    /// Returns the last Component of the given Path or an empty
+    /// String if there is none.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to get the Name from
+    fn get_file_name(path: &str) -> String {
+        match Path::new(path).file_name() {
+            Some(value) => value.to_string_lossy().to_string(),
+            None => String::new(),
+        }
+    }
+}
@@ -1,8 +1,10 @@
 #[macro_use]
 extern crate lazy_static;
 extern crate daemonize;
+extern crate libc;
 extern crate chrono;
 extern crate pnet;
+extern crate regex;
 extern crate sys_info;
 extern crate uuid;
 
@@ -13,14 +15,17 @@ mod configuration;
 mod upnp;
 mod server;
 mod provider;
+mod web;
 
 use std::env;
+use std::process;
 use server::MediaServer;
 
 fn main() {
     // Set some defaults
     let mut helpscreen: bool = false;
     let mut daemonize: bool = true;
+    let mut check_config: bool = false;
     let mut cfg_path: String = String::from("/etc/slms/server.cfg");
     let args: Vec<String> = env::args().collect();
 
@@ -34,9 +39,20 @@ fn main() {
             daemonize = false;
         } else if args[index] == "-h" || args[index] == "--help" {
             helpscreen = true;
+        } else if args[index] == "--check-config" {
+            check_config = true;
         }
     }
 
+    // Only validate the Configuration if requested
+    if check_config && !helpscreen {
+        if MediaServer::check_configuration(&cfg_path) {
+            process::exit(0);
+        }
+
+        process::exit(1);
+    }
+
     // Run the Media Server
     MediaServer::run(&cfg_path, daemonize, helpscreen);
 }
@@ -0,0 +1,240 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::time;
+use uuid::Uuid;
+
+use super::item::{Item, MediaType};
+
+/// # ImageProfile
+///
+/// The DLNA JPEG Profiles a Picture can be scaled to. Each one
+/// limits the Width and Height of the Image.
+#[derive(Clone, Copy, PartialEq)]
+pub enum ImageProfile {
+    Thumbnail,
+    Small,
+    Medium,
+    Large,
+}
+
+impl ImageProfile {
+    /// Returns all Profiles from the smallest to the largest one
+    pub fn all() -> [ImageProfile; 4] {
+        [
+            ImageProfile::Thumbnail,
+            ImageProfile::Small,
+            ImageProfile::Medium,
+            ImageProfile::Large,
+        ]
+    }
+
+    /// Returns the DLNA Profile Name as used for DLNA.ORG_PN
+    pub fn get_name(&self) -> &'static str {
+        match *self {
+            ImageProfile::Thumbnail => "JPEG_TN",
+            ImageProfile::Small => "JPEG_SM",
+            ImageProfile::Medium => "JPEG_MED",
+            ImageProfile::Large => "JPEG_LRG",
+        }
+    }
+
+    /// Converts a DLNA Profile Name back to the enumeration
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - DLNA Profile Name, e.g. JPEG_SM
+    pub fn from_name(name: &str) -> Option<ImageProfile> {
+        ImageProfile::all().iter().cloned().find(|profile| profile.get_name() == name)
+    }
+
+    /// Returns the largest Width and Height allowed by the Profile.
+    /// The Limits apply to Portrait Images the other Way round.
+    pub fn get_max_size(&self) -> (u16, u16) {
+        match *self {
+            ImageProfile::Thumbnail => (160, 160),
+            ImageProfile::Small => (640, 480),
+            ImageProfile::Medium => (1024, 768),
+            ImageProfile::Large => (4096, 4096),
+        }
+    }
+
+    /// Returns the Size a Picture of the given Size has once it is
+    /// scaled to the Profile. Pictures are never enlarged and keep
+    /// their Aspect Ratio.
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - Width of the Picture as displayed
+    /// * `height` - Height of the Picture as displayed
+    pub fn get_scaled_size(&self, width: u16, height: u16) -> (u16, u16) {
+        let (mut max_width, mut max_height) = self.get_max_size();
+
+        if height > width {
+            ::std::mem::swap(&mut max_width, &mut max_height);
+        }
+
+        if width <= max_width && height <= max_height {
+            return (width, height);
+        }
+
+        let factor = (f64::from(max_width) / f64::from(width)).min(f64::from(max_height) / f64::from(height));
+
+        (
+            ((f64::from(width) * factor).round() as u16).max(1),
+            ((f64::from(height) * factor).round() as u16).max(1),
+        )
+    }
+}
+
+/// Returns the Profiles worth offering for a Picture. These are the
+/// ones smaller than the Picture, or all of them if the Picture is
+/// no JPEG and Renderers might not be able to decode it at all.
+/// The Thumbnail is always offered.
+///
+/// # Arguments
+///
+/// * `item` - The Picture
+pub fn get_profiles(item: &Item) -> Vec<ImageProfile> {
+    let (width, height) = match item.get_display_size() {
+        Some(value) if item.media_type == MediaType::PICTURE => value,
+        _ => return Vec::new(),
+    };
+
+    let is_jpeg = item.get_mime_type() == "image/jpeg";
+
+    ImageProfile::all()
+        .iter()
+        .cloned()
+        .filter(|profile| {
+            *profile == ImageProfile::Thumbnail || !is_jpeg || profile.get_scaled_size(width, height) != (width, height)
+        })
+        .collect()
+}
+
+/// Returns the Path of the cached, scaled Picture inside the
+/// Thumbnail Directory.
+///
+/// # Arguments
+///
+/// * `item` - The Picture
+/// * `profile` - Profile the Picture is scaled to
+/// * `thumbnail_dir` - Directory holding the cached Pictures
+pub fn get_cache_path(item: &Item, profile: ImageProfile, thumbnail_dir: &str) -> String {
+    Path::new(thumbnail_dir)
+        .join(format!("{}_{}.jpg", item.id, profile.get_name()))
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Returns the Size of the cached, scaled Picture if it was already
+/// created and is up to date.
+///
+/// # Arguments
+///
+/// * `item` - The Picture
+/// * `profile` - Profile the Picture is scaled to
+/// * `thumbnail_dir` - Directory holding the cached Pictures
+pub fn get_cached_size(item: &Item, profile: ImageProfile, thumbnail_dir: &str) -> Option<u64> {
+    let metadata = fs::metadata(get_cache_path(item, profile, thumbnail_dir)).ok()?;
+
+    let modified = metadata
+        .modified()
+        .ok()?
+        .duration_since(time::UNIX_EPOCH)
+        .ok()?
+        .as_secs();
+
+    if modified < item.last_modified {
+        return None;
+    }
+
+    Some(metadata.len())
+}
+
+/// Returns the Path of the Picture scaled to the Profile. The Picture
+/// is scaled by ffmpeg and stored inside the Thumbnail Directory
+/// if there is no up to date Copy yet. The EXIF Orientation is
+/// applied while scaling, so the Result is always upright.
+/// Returns None if the Picture could not be scaled.
+///
+/// # Arguments
+///
+/// * `item` - The Picture
+/// * `profile` - Profile the Picture is scaled to
+/// * `thumbnail_dir` - Directory holding the cached Pictures
+pub fn get_scaled_image(item: &Item, profile: ImageProfile, thumbnail_dir: &str) -> Option<String> {
+    let path = get_cache_path(item, profile, thumbnail_dir);
+
+    if get_cached_size(item, profile, thumbnail_dir).is_some() {
+        return Some(path);
+    }
+
+    let (width, height) = item.get_display_size()?;
+    let (scaled_width, scaled_height) = profile.get_scaled_size(width, height);
+
+    let mut filters: Vec<String> = get_orientation_filters(item.meta_data.orientation)
+        .iter()
+        .map(|filter| filter.to_string())
+        .collect();
+
+    filters.push(format!("scale={}:{}", scaled_width, scaled_height));
+
+    // Write to a temporary File first, so concurrent Requests never see half a Picture
+    let temporary_path = format!("{}.{}.part", path, Uuid::new_v4());
+
+    let status = Command::new("ffmpeg")
+        .args([
+            "-v",
+            "quiet",
+            "-y",
+            "-noautorotate",
+            "-i",
+            &item.file_path,
+            "-vf",
+            &filters.join(","),
+            "-frames:v",
+            "1",
+            "-f",
+            "image2",
+            "-c:v",
+            "mjpeg",
+            &temporary_path,
+        ])
+        .status();
+
+    match status {
+        Ok(value) if value.success() => (),
+        _ => {
+            fs::remove_file(&temporary_path).unwrap_or(());
+            return None;
+        }
+    }
+
+    match fs::rename(&temporary_path, &path) {
+        Ok(_) => Some(path),
+        Err(_) => {
+            fs::remove_file(&temporary_path).unwrap_or(());
+            None
+        }
+    }
+}
+
+/// Returns the ffmpeg Filters turning a Picture with the given
+/// EXIF Orientation upright.
+///
+/// # Arguments
+///
+/// * `orientation` - EXIF Orientation (1 to 8, 0 if unknown)
+fn get_orientation_filters(orientation: u8) -> Vec<&'static str> {
+    match orientation {
+        2 => vec!["hflip"],
+        3 => vec!["hflip", "vflip"],
+        4 => vec!["vflip"],
+        5 => vec!["transpose=0"],
+        6 => vec!["transpose=1"],
+        7 => vec!["transpose=3"],
+        8 => vec!["transpose=2"],
+        _ => Vec::new(),
+    }
+}
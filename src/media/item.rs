@@ -1,14 +1,19 @@
+use std::cmp::Ordering;
+use chrono::{Local, TimeZone};
+
 use super::container::Container;
 use super::stream::Stream;
 use super::thumbnail::Thumbnail;
-use tools::NameValuePair;
+use super::imagescaler::{self, ImageProfile};
+use tools::{NameValuePair, XMLParser};
+use provider::http;
 use configuration::{RendererConfiguration, ServerConfiguration};
 
 /// # MediaType
 ///
 /// This enumartion is used to set what kind of
 /// Media a File is. E.g. a Movie, Music, etc.
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 pub enum MediaType {
     UNKNOWN,
     AUDIO,
@@ -29,6 +34,17 @@ impl MediaType {
         }
     }
 
+    /// Returns the lower case Name of the Media Type as used
+    /// by the Admin API.
+    pub fn get_name(&self) -> &'static str {
+        match *self {
+            MediaType::AUDIO => "audio",
+            MediaType::PICTURE => "picture",
+            MediaType::VIDEO => "video",
+            MediaType::UNKNOWN => "unknown",
+        }
+    }
+
     /// Convert a printable String back to the enumeration.
     /// Used to load the Information from the
     /// Media Database.
@@ -46,6 +62,28 @@ impl MediaType {
     }
 }
 
+/// # Bookmark
+///
+/// Position a Renderer stopped playing an Item at, so it can be
+/// resumed later on.
+#[derive(Clone)]
+pub struct Bookmark {
+    pub renderer: String, // Renderer the Bookmark belongs to. Empty if it is shared by all Renderers
+    pub position: u64, // Position in Seconds
+    pub updated: u64, // UNIX Timestamp the Bookmark was set at
+}
+
+impl Bookmark {
+    /// Returns the Bookmark as Name Value Pairs to store it in the Database
+    pub fn get_name_value_pairs(&self) -> Vec<NameValuePair> {
+        vec![
+            NameValuePair::new("renderer", &self.renderer),
+            NameValuePair::new("position", &self.position.to_string()),
+            NameValuePair::new("updated", &self.updated.to_string()),
+        ]
+    }
+}
+
 /// # MetaData
 ///
 /// This structure holds all Meta Information that
@@ -54,7 +92,7 @@ impl MediaType {
 #[derive(Clone)]
 pub struct MetaData {
     pub title: String,
-    pub genre: String,
+    pub genres: Vec<String>,
     pub description: String,
     pub long_description: String,
     pub producer: String,
@@ -65,12 +103,21 @@ pub struct MetaData {
     pub languages: Vec<String>,
     pub artists: Vec<String>,
     pub album: String,
+    pub album_artist: String,
     pub track_number: String,
+    pub track_total: String,
+    pub disc_number: String,
+    pub disc_total: String,
     pub playlist: String,
     pub contributor: String,
     pub date: String,
     pub copyrights: Vec<String>,
     pub composer: String,
+    pub orientation: u8,
+    pub camera_make: String,
+    pub camera_model: String,
+    pub latitude: String,
+    pub longitude: String,
     pub file_name: String,
     pub file_extension: String,
 }
@@ -80,7 +127,7 @@ impl MetaData {
     pub fn new() -> MetaData {
         MetaData {
             title: String::new(),
-            genre: String::new(),
+            genres: Vec::new(),
             description: String::new(),
             long_description: String::new(),
             producer: String::new(),
@@ -91,12 +138,21 @@ impl MetaData {
             languages: Vec::new(),
             artists: Vec::new(),
             album: String::new(),
+            album_artist: String::new(),
             track_number: String::new(),
+            track_total: String::new(),
+            disc_number: String::new(),
+            disc_total: String::new(),
             playlist: String::new(),
             contributor: String::new(),
             date: String::new(),
             copyrights: Vec::new(),
             composer: String::new(),
+            orientation: 0,
+            camera_make: String::new(),
+            camera_model: String::new(),
+            latitude: String::new(),
+            longitude: String::new(),
             file_name: String::new(),
             file_extension: String::new(),
         }
@@ -120,10 +176,6 @@ impl MetaData {
             pair_vec.push(NameValuePair::new("fileExtension", &self.file_extension));
         }
 
-        if self.genre.len() > 0 {
-            pair_vec.push(NameValuePair::new("genre", &self.genre));
-        }
-
         if self.description.len() > 0 {
             pair_vec.push(NameValuePair::new("description", &self.description));
         }
@@ -159,10 +211,26 @@ impl MetaData {
             pair_vec.push(NameValuePair::new("album", &self.album));
         }
 
+        if !self.album_artist.is_empty() {
+            pair_vec.push(NameValuePair::new("albumArtist", &self.album_artist));
+        }
+
         if self.track_number.len() > 0 {
             pair_vec.push(NameValuePair::new("trackNumber", &self.track_number));
         }
 
+        if !self.track_total.is_empty() {
+            pair_vec.push(NameValuePair::new("trackTotal", &self.track_total));
+        }
+
+        if !self.disc_number.is_empty() {
+            pair_vec.push(NameValuePair::new("discNumber", &self.disc_number));
+        }
+
+        if !self.disc_total.is_empty() {
+            pair_vec.push(NameValuePair::new("discTotal", &self.disc_total));
+        }
+
         if self.playlist.len() > 0 {
             pair_vec.push(NameValuePair::new("playlist", &self.playlist));
         }
@@ -179,6 +247,27 @@ impl MetaData {
             pair_vec.push(NameValuePair::new("composer", &self.composer));
         }
 
+        if self.orientation != 0 {
+            pair_vec.push(NameValuePair::new("orientation", &self.orientation.to_string()));
+        }
+
+        if !self.camera_make.is_empty() {
+            pair_vec.push(NameValuePair::new("cameraMake", &self.camera_make));
+        }
+
+        if !self.camera_model.is_empty() {
+            pair_vec.push(NameValuePair::new("cameraModel", &self.camera_model));
+        }
+
+        if !self.latitude.is_empty() && !self.longitude.is_empty() {
+            pair_vec.push(NameValuePair::new("latitude", &self.latitude));
+            pair_vec.push(NameValuePair::new("longitude", &self.longitude));
+        }
+
+        for genre in &self.genres {
+            pair_vec.push(NameValuePair::new("genre", genre));
+        }
+
         for language in &self.languages {
             pair_vec.push(NameValuePair::new("language", language));
         }
@@ -199,110 +288,124 @@ impl MetaData {
     pub fn generate_upnp_xml(&self) -> String {
         let mut xml: String = String::new();
 
-        if self.genre.len() > 0 {
+        for genre in &self.genres {
             xml.push_str(&format!(
-                "&lt;upnp:genre&gt;{}&lt;/upnp:genre&gt;",
-                self.genre
+                "<upnp:genre>{}</upnp:genre>",
+                XMLParser::escape(genre)
             ));
         }
 
         if self.description.len() > 0 {
             xml.push_str(&format!(
-                "&lt;dc:description&gt;{}&lt;/dc:description&gt;",
-                self.description
+                "<dc:description>{}</dc:description>",
+                XMLParser::escape(&self.description)
             ));
         }
 
         if self.long_description.len() > 0 {
             xml.push_str(&format!(
-                "&lt;upnp:longDescription&gt;{}&lt;/upnp:longDescription&gt;",
-                self.long_description
+                "<upnp:longDescription>{}</upnp:longDescription>",
+                XMLParser::escape(&self.long_description)
             ));
         }
 
         if self.producer.len() > 0 {
             xml.push_str(&format!(
-                "&lt;upnp:producer&gt;{}&lt;/upnp:producer&gt;",
-                self.producer
+                "<upnp:producer>{}</upnp:producer>",
+                XMLParser::escape(&self.producer)
             ));
         }
 
         if self.rating.len() > 0 {
             xml.push_str(&format!(
-                "&lt;upnp:rating&gt;{}&lt;/upnp:rating&gt;",
-                self.rating
+                "<upnp:rating>{}</upnp:rating>",
+                XMLParser::escape(&self.rating)
             ));
         }
 
         if self.actor.len() > 0 {
             xml.push_str(&format!(
-                "&lt;upnp:actor&gt;{}&lt;/upnp:actor&gt;",
-                self.actor
+                "<upnp:actor>{}</upnp:actor>",
+                XMLParser::escape(&self.actor)
             ));
         }
 
         if self.director.len() > 0 {
             xml.push_str(&format!(
-                "&lt;upnp:director&gt;{}&lt;/upnp:director&gt;",
-                self.director
+                "<upnp:director>{}</upnp:director>",
+                XMLParser::escape(&self.director)
             ));
         }
 
         if self.publisher.len() > 0 {
             xml.push_str(&format!(
-                "&lt;dc:publisher&gt;{}&lt;/dc:publisher&gt;",
-                self.publisher
+                "<dc:publisher>{}</dc:publisher>",
+                XMLParser::escape(&self.publisher)
             ));
         }
 
         if self.album.len() > 0 {
             xml.push_str(&format!(
-                "&lt;upnp:album&gt;{}&lt;/upnp:album&gt;",
-                self.album
+                "<upnp:album>{}</upnp:album>",
+                XMLParser::escape(&self.album)
             ));
         }
 
         if self.track_number.len() > 0 {
             xml.push_str(&format!(
-                "&lt;upnp:originalTrackNumber&gt;{}&lt;/upnp:originalTrackNumber&gt;",
-                self.track_number
+                "<upnp:originalTrackNumber>{}</upnp:originalTrackNumber>",
+                XMLParser::escape(&self.track_number)
+            ));
+        }
+
+        if !self.disc_number.is_empty() {
+            xml.push_str(&format!(
+                "<upnp:originalDiscNumber>{}</upnp:originalDiscNumber>",
+                XMLParser::escape(&self.disc_number)
             ));
         }
 
         if self.playlist.len() > 0 {
             xml.push_str(&format!(
-                "&lt;upnp:playlist&gt;{}&lt;/upnp:playlist&gt;",
-                self.playlist
+                "<upnp:playlist>{}</upnp:playlist>",
+                XMLParser::escape(&self.playlist)
             ));
         }
 
         if self.contributor.len() > 0 {
             xml.push_str(&format!(
-                "&lt;dc:contributor&gt;{}&lt;/upnp:contributor&gt;",
-                self.contributor
+                "<dc:contributor>{}</dc:contributor>",
+                XMLParser::escape(&self.contributor)
             ));
         }
 
         if self.date.len() > 0 {
-            xml.push_str(&format!("&lt;upnp:date&gt;{}&lt;/upnp:date&gt;", self.date));
+            xml.push_str(&format!("<dc:date>{}</dc:date>", XMLParser::escape(&self.date)));
         }
 
         for language in &self.languages {
             xml.push_str(&format!(
-                "&lt;dc:language&gt;{}&lt;/dc:language&gt;",
-                language
+                "<dc:language>{}</dc:language>",
+                XMLParser::escape(language)
             ));
         }
 
         for artist in &self.artists {
             xml.push_str(&format!(
-                "&lt;upnp:artist&gt;{}&lt;/upnp:artist&gt;",
-                artist
+                "<upnp:artist>{}</upnp:artist>",
+                XMLParser::escape(artist)
+            ));
+        }
+
+        if !self.album_artist.is_empty() {
+            xml.push_str(&format!(
+                "<upnp:artist role=\"AlbumArtist\">{}</upnp:artist>",
+                XMLParser::escape(&self.album_artist)
             ));
         }
 
         for copyright in &self.copyrights {
-            xml.push_str(&format!("&lt;dc:rights&gt;{}&lt;/dc:rights&gt;", copyright));
+            xml.push_str(&format!("<dc:rights>{}</dc:rights>", XMLParser::escape(copyright)));
         }
 
         xml
@@ -330,6 +433,9 @@ pub struct Item {
     pub media_tracks: Vec<Stream>,
     pub thumbnail: Thumbnail,
     pub format_container: Container,
+    pub play_count: u32,
+    pub last_played: u64,
+    pub bookmarks: Vec<Bookmark>,
 }
 
 impl Item {
@@ -347,6 +453,9 @@ impl Item {
             media_tracks: Vec::new(),
             thumbnail: Thumbnail::new(),
             format_container: Container::new(),
+            play_count: 0,
+            last_played: 0,
+            bookmarks: Vec::new(),
         }
     }
 
@@ -363,7 +472,7 @@ impl Item {
             "title" => self.meta_data.title = value.to_string(),
             "fileName" => self.meta_data.file_name = value.to_string(),
             "fileExtension" => self.meta_data.file_extension = value.to_string(),
-            "genre" => self.meta_data.genre = value.to_string(),
+            "genre" => self.meta_data.genres.push(value.to_string()),
             "description" => self.meta_data.description = value.to_string(),
             "longDescription" => self.meta_data.long_description = value.to_string(),
             "producer" => self.meta_data.producer = value.to_string(),
@@ -372,11 +481,20 @@ impl Item {
             "director" => self.meta_data.director = value.to_string(),
             "publisher" => self.meta_data.publisher = value.to_string(),
             "album" => self.meta_data.album = value.to_string(),
+            "albumArtist" => self.meta_data.album_artist = value.to_string(),
             "trackNumber" => self.meta_data.track_number = value.to_string(),
+            "trackTotal" => self.meta_data.track_total = value.to_string(),
+            "discNumber" => self.meta_data.disc_number = value.to_string(),
+            "discTotal" => self.meta_data.disc_total = value.to_string(),
             "playlist" => self.meta_data.playlist = value.to_string(),
             "contributor" => self.meta_data.contributor = value.to_string(),
             "date" => self.meta_data.date = value.to_string(),
             "composer" => self.meta_data.composer = value.to_string(),
+            "orientation" => self.meta_data.orientation = value.parse::<u8>().unwrap_or(0),
+            "cameraMake" => self.meta_data.camera_make = value.to_string(),
+            "cameraModel" => self.meta_data.camera_model = value.to_string(),
+            "latitude" => self.meta_data.latitude = value.to_string(),
+            "longitude" => self.meta_data.longitude = value.to_string(),
             "language" => self.meta_data.languages.push(value.to_string()),
             "artist" => self.meta_data.artists.push(value.to_string()),
             "copyright" => self.meta_data.copyrights.push(value.to_string()),
@@ -396,6 +514,8 @@ impl Item {
                 NameValuePair::new("duration", &self.duration.to_string()),
                 NameValuePair::new("size", &self.file_size.to_string()),
                 NameValuePair::new("containerId", &self.format_container.id.to_string()),
+                NameValuePair::new("playCount", &self.play_count.to_string()),
+                NameValuePair::new("lastPlayed", &self.last_played.to_string()),
             ];
 
         pair_vec
@@ -416,7 +536,7 @@ impl Item {
         // Generat first set of Parameters
         let mut title: String = self.meta_data.file_name.clone();
         let mut xml: String = format!(
-            "&lt;item id=\"{}\" parentID=\"{}\" restricted=\"1\"&gt;",
+            "<item id=\"{}\" parentID=\"{}\" restricted=\"1\">",
             self.id,
             self.parent_id
         );
@@ -432,11 +552,13 @@ impl Item {
 
         }
 
-        // Replace some special chars to avoid some renderer problems
-        title = title.replace("&amp;", " u. ").replace("&", " u. ");
-
         // Add title,res String and Mime Type
-        xml.push_str(&format!("&lt;dc:title&gt;{}&lt;/dc:title&gt;&lt;res xmlns:dlna=\"urn:schemas-dlna-org:metadata-1-0/\" protocolInfo=\"http-get:*:{}:DLNA.ORG_OP=11;DLNA.ORG_CI=0\" ", title, self.get_mime_type()));
+        xml.push_str(&format!(
+            "<dc:title>{}</dc:title><res xmlns:dlna=\"urn:schemas-dlna-org:metadata-1-0/\" protocolInfo=\"http-get:*:{}:{}\" ",
+            XMLParser::escape(&title),
+            self.get_mime_type(),
+            http::get_dlna_features(self.get_duration_seconds().is_some())
+        ));
 
         // Add MediaType specifig Values
         match self.media_type {
@@ -467,7 +589,7 @@ impl Item {
 
         // Add Values always required
         xml.push_str(
-            &format!("size=\"{}\"&gt;http://{}:{}/stream/{}&lt;/res&gt;",
+            &format!("size=\"{}\">http://{}:{}/stream/{}</res>",
             self.file_size,
             server_cfg.server_ip,
             server_cfg.server_port,
@@ -475,31 +597,205 @@ impl Item {
             ),
         );
 
+        // Add scaled Versions of Pictures
+        for profile in imagescaler::get_profiles(self) {
+            xml.push_str(&self.generate_image_res(profile, server_cfg));
+        }
+
         // Add UPnP Media Type
         match self.media_type {
             MediaType::UNKNOWN => {
-                xml.push_str("&lt;upnp:class&gt;object.item.imageItem&lt;/upnp:class&gt;")
+                xml.push_str("<upnp:class>object.item.imageItem</upnp:class>")
             }
             MediaType::AUDIO => {
-                xml.push_str("&lt;upnp:class&gt;object.item.audioItem&lt;/upnp:class&gt;")
+                xml.push_str("<upnp:class>object.item.audioItem</upnp:class>")
             }
             MediaType::PICTURE => {
-                xml.push_str("&lt;upnp:class&gt;object.item.imageItem&lt;/upnp:class&gt;")
+                xml.push_str("<upnp:class>object.item.imageItem</upnp:class>")
             }
             MediaType::VIDEO => {
-                xml.push_str("&lt;upnp:class&gt;object.item.videoItem&lt;/upnp:class&gt;")
+                xml.push_str("<upnp:class>object.item.videoItem</upnp:class>")
             }
         }
 
+        // Add embedded or generated Cover Art
+        if self.media_type == MediaType::PICTURE {
+            xml.push_str(&format!(
+                "<upnp:albumArtURI xmlns:dlna=\"urn:schemas-dlna-org:metadata-1-0/\" dlna:profileID=\"{}\">http://{}:{}/image/{}/{}</upnp:albumArtURI>",
+                ImageProfile::Thumbnail.get_name(),
+                server_cfg.server_ip,
+                server_cfg.server_port,
+                self.id,
+                ImageProfile::Thumbnail.get_name()
+            ));
+        } else if self.thumbnail.is_available() {
+            xml.push_str(&format!(
+                "<upnp:albumArtURI>http://{}:{}/thumbnail/{}</upnp:albumArtURI>",
+                server_cfg.server_ip,
+                server_cfg.server_port,
+                self.id
+            ));
+        }
+
+        // Add how often and when the Item was played
+        xml.push_str(&format!(
+            "<upnp:playbackCount>{}</upnp:playbackCount>",
+            self.play_count
+        ));
+
+        if let Some(time) = self.get_last_played_time() {
+            xml.push_str(&format!(
+                "<upnp:lastPlaybackTime>{}</upnp:lastPlaybackTime>",
+                time
+            ));
+        }
+
+        // Add the Position to resume at. Samsung Renderers read it from dcmInfo
+        let position = self.get_bookmark(server_cfg.get_bookmark_key(renderer_cfg));
+
+        if position > 0 {
+            xml.push_str(&format!(
+                "<upnp:lastPlaybackPosition>{}:{:02}:{:02}</upnp:lastPlaybackPosition>",
+                position / 3600,
+                position / 60 % 60,
+                position % 60
+            ));
+            xml.push_str(&format!(
+                "<sec:dcmInfo>CREATIONDATE=0,BM={}</sec:dcmInfo>",
+                position
+            ));
+        }
+
         // Add additional Meta Data and close Tag
         xml.push_str(&format!(
-            "{}&lt;/item&gt;",
+            "{}</item>",
             self.meta_data.generate_upnp_xml()
         ));
 
         xml
     }
 
+    /// Generates the res Element of a Picture scaled to the given
+    /// DLNA Profile. The Size is only known once the scaled Picture
+    /// was created and is left out before.
+    ///
+    /// # Arguments
+    ///
+    /// * `profile` - Profile the Picture is scaled to
+    /// * `server_cfg` - The Servers Configuration
+    fn generate_image_res(&self, profile: ImageProfile, server_cfg: &ServerConfiguration) -> String {
+        let (width, height) = match self.get_display_size() {
+            Some((width, height)) => profile.get_scaled_size(width, height),
+            None => return String::new(),
+        };
+
+        let size = match imagescaler::get_cached_size(self, profile, &server_cfg.thumbnail_dir) {
+            Some(value) => format!("size=\"{}\" ", value),
+            None => String::new(),
+        };
+
+        format!(
+            "<res xmlns:dlna=\"urn:schemas-dlna-org:metadata-1-0/\" protocolInfo=\"http-get:*:image/jpeg:DLNA.ORG_PN={};DLNA.ORG_OP=01;DLNA.ORG_CI=1\" resolution=\"{}x{}\" {}>http://{}:{}/image/{}/{}</res>",
+            profile.get_name(),
+            width,
+            height,
+            size,
+            server_cfg.server_ip,
+            server_cfg.server_port,
+            self.id,
+            profile.get_name()
+        )
+    }
+
+    /// Compares this Item with another one in the default Order used
+    /// when a Renderer does not request a specific one. Items of the
+    /// same Album are ordered by Disc and Track, Pictures by the Date
+    /// they were taken and everything else by File Name.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - Item to compare with
+    pub fn compare(&self, other: &Item) -> Ordering {
+        self.compare_by(other, "upnp:album")
+            .then_with(|| self.compare_by(other, "upnp:originalDiscNumber"))
+            .then_with(|| self.compare_by(other, "upnp:originalTrackNumber"))
+            .then_with(|| self.compare_by(other, "dc:date"))
+            .then_with(|| self.meta_data.file_name.cmp(&other.meta_data.file_name))
+    }
+
+    /// Compares this Item with another one by a single UPnP Property
+    /// as used inside a SortCriteria. Texts are compared case
+    /// insensitive, Track and Disc Numbers by their Value.
+    /// Unsupported Properties compare as equal.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - Item to compare with
+    /// * `property` - UPnP Property Name, e.g. "upnp:album"
+    pub fn compare_by(&self, other: &Item, property: &str) -> Ordering {
+        match property {
+            "dc:title" => compare_text(self.get_sort_title(), other.get_sort_title()),
+            "dc:date" => self.meta_data.date.cmp(&other.meta_data.date),
+            "upnp:album" => compare_text(&self.meta_data.album, &other.meta_data.album),
+            "upnp:artist" => {
+                compare_text(
+                    self.meta_data.artists.first().map_or("", |value| value.as_str()),
+                    other.meta_data.artists.first().map_or("", |value| value.as_str()),
+                )
+            }
+            "upnp:genre" => {
+                compare_text(
+                    self.meta_data.genres.first().map_or("", |value| value.as_str()),
+                    other.meta_data.genres.first().map_or("", |value| value.as_str()),
+                )
+            }
+            "upnp:originalTrackNumber" => {
+                parse_number(&self.meta_data.track_number).cmp(&parse_number(&other.meta_data.track_number))
+            }
+            "upnp:originalDiscNumber" => {
+                parse_number(&self.meta_data.disc_number).cmp(&parse_number(&other.meta_data.disc_number))
+            }
+            "upnp:playbackCount" => self.play_count.cmp(&other.play_count),
+            "upnp:lastPlaybackTime" => self.last_played.cmp(&other.last_played),
+            _ => Ordering::Equal,
+        }
+    }
+
+    /// Returns the Time the Item was played last in the Format used
+    /// by UPnP, e.g. 2024-05-01T20:15:00. None if it was never played.
+    pub fn get_last_played_time(&self) -> Option<String> {
+        if self.last_played == 0 {
+            return None;
+        }
+
+        let time = Local.timestamp_opt(self.last_played as i64, 0).single()?;
+
+        Some(time.format("%Y-%m-%dT%H:%M:%S").to_string())
+    }
+
+    /// Returns the Position in Seconds playing the Item stopped at.
+    /// 0 if there is no Bookmark.
+    ///
+    /// # Arguments
+    ///
+    /// * `renderer` - Renderer the Bookmark belongs to. Empty for the shared one
+    pub fn get_bookmark(&self, renderer: &str) -> u64 {
+        match self.bookmarks.iter().find(|bookmark| bookmark.renderer == renderer) {
+            Some(bookmark) => bookmark.position,
+            None => 0,
+        }
+    }
+
+    /// Returns the Title used for sorting. This is the Title Tag
+    /// if available or the File Name otherwise.
+    fn get_sort_title(&self) -> &str {
+        if self.meta_data.title.is_empty() {
+            &self.meta_data.file_name
+        } else {
+            &self.meta_data.title
+        }
+    }
+
     /// Calculates the total Bitrate this Item provides summing each Streams individual Bitrate
     fn get_bitrate(&self) -> u64 {
         let mut rate: u64 = 0;
@@ -544,13 +840,53 @@ impl Item {
     /// # TO-DO
     /// - Match with the requested Stream instead default one
     fn get_resolution(&self) -> String {
+        match self.get_display_size() {
+            Some((width, height)) => format!("{}x{}", width, height),
+            None => String::new(),
+        }
+    }
+
+    /// Returns Width and Height of the default Video or Image Stream
+    /// as it is displayed. Pictures rotated by 90 Degrees through
+    /// their EXIF Orientation (5 to 8) swap Width and Height.
+    pub fn get_display_size(&self) -> Option<(u16, u16)> {
         for stream in &self.media_tracks {
             if stream.frame_width != 0 && stream.frame_height != 0 {
-                return format!("{}x{}", stream.frame_width, stream.frame_height.to_string());
+                if self.media_type == MediaType::PICTURE && self.meta_data.orientation >= 5 {
+                    return Some((stream.frame_height, stream.frame_width));
+                }
+
+                return Some((stream.frame_width, stream.frame_height));
             }
         }
 
-        String::new()
+        None
+    }
+
+    /// Returns the Play Time of Audio and Video Items in Seconds.
+    /// Returns None if it is unknown.
+    pub fn get_duration_seconds(&self) -> Option<f64> {
+        if self.media_type != MediaType::AUDIO && self.media_type != MediaType::VIDEO {
+            return None;
+        }
+
+        // Stored as "hh:mm:ss.ms"
+        let parts: Vec<&str> = self.duration.split(':').collect();
+
+        if parts.len() != 3 {
+            return None;
+        }
+
+        let hours = parts[0].parse::<f64>().ok()?;
+        let minutes = parts[1].parse::<f64>().ok()?;
+        let seconds = parts[2].parse::<f64>().ok()?;
+        let duration = hours * 3600.0 + minutes * 60.0 + seconds;
+
+        if duration > 0.0 {
+            Some(duration)
+        } else {
+            None
+        }
     }
 
     /// Returns the Mime Type used for this Item
@@ -582,6 +918,10 @@ impl Item {
                 match self.meta_data.file_extension.to_lowercase().as_str() {
                     "jpg" | "jpeg" | "jpe" => return "image/jpeg".to_string(),
                     "png" => return "image/png".to_string(),
+                    "tif" | "tiff" => return "image/tiff".to_string(),
+                    "heic" => return "image/heic".to_string(),
+                    "heif" => return "image/heif".to_string(),
+                    "avif" => return "image/avif".to_string(),
                     _ => return "image/*".to_string(),
                 }
             }
@@ -590,3 +930,25 @@ impl Item {
 
     }
 }
+
+/// Compares two Texts ignoring their Case
+///
+/// # Arguments
+///
+/// * `a` - First Text
+/// * `b` - Second Text
+fn compare_text(a: &str, b: &str) -> Ordering {
+    a.to_lowercase().cmp(&b.to_lowercase())
+}
+
+/// Parses the leading Number of a Track or Disc Number like "3" or
+/// "3/12". Returns None if there is no Number.
+///
+/// # Arguments
+///
+/// * `value` - Text holding the Number
+fn parse_number(value: &str) -> Option<u32> {
+    let digits: String = value.trim().chars().take_while(|c| c.is_ascii_digit()).collect();
+
+    digits.parse::<u32>().ok()
+}
@@ -10,8 +10,15 @@ pub use self::thumbnail::Thumbnail;
 
 mod item;
 pub use self::item::Item;
+pub use self::item::Bookmark;
 pub use self::item::MetaData;
 pub use self::item::MediaType;
 
+mod probe;
+pub use self::probe::get_ffprobe_failures;
+
+pub mod imagescaler;
+pub use self::imagescaler::ImageProfile;
+
 pub mod mediaparser;
 pub use self::mediaparser::*;
@@ -0,0 +1,239 @@
+use std::process::Command;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use media::{Item, Stream, StreamType};
+use tools::{XMLParser, XMLEntry};
+use super::{MediaProber, convert_duration};
+use super::tags::insert_meta_data;
+
+/// # FfprobeProber
+///
+/// Fallback Prober calling the external ffprobe Tool and parsing
+/// its XML Output. Used for all Formats the native Probers do not
+/// understand. Requires ffprobe to be installed.
+pub struct FfprobeProber;
+
+/// Number of Files ffprobe was unable to read since the Server was started
+static FAILURES: AtomicU64 = AtomicU64::new(0);
+
+/// Returns the Number of Files ffprobe was unable to read since
+/// the Server was started
+pub fn get_ffprobe_failures() -> u64 {
+    FAILURES.load(Ordering::Relaxed)
+}
+
+impl MediaProber for FfprobeProber {
+    fn can_probe(&self, _header: &[u8], _extension: &str) -> bool {
+        true
+    }
+
+    fn probe(&self, path: &str, target: &mut Item) -> bool {
+        if FfprobeProber::read_output(path, target) {
+            return true;
+        }
+
+        FAILURES.fetch_add(1, Ordering::Relaxed);
+        false
+    }
+}
+
+impl FfprobeProber {
+    /// Calls ffprobe for the File and reads its Output into the Item
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the File to probe
+    /// * `target` - Item to store the Information in
+    fn read_output(path: &str, target: &mut Item) -> bool {
+        let output = match Command::new("ffprobe")
+            .args(
+                [
+                    "-v",
+                    "quiet",
+                    "-print_format",
+                    "xml",
+                    "-show_format",
+                    "-show_streams",
+                    "-unit",
+                    path,
+                ],
+            )
+            .output() {
+            Ok(value) => value,
+            Err(_) => return false,
+        };
+
+        // Convert Output to String
+        let xml_out: String = match String::from_utf8(output.stdout) {
+            Ok(value) => value,
+            Err(_) => return false,
+        };
+
+        // Check if  everything we need is there
+        if xml_out.is_empty() || xml_out.find("format").is_none() || xml_out.find("streams").is_none() {
+            return false;
+        }
+
+        // Parse the XML Output
+        let xml_parser: XMLParser = XMLParser::open(&xml_out);
+
+        // Parse the Format
+        let format_entry: XMLEntry = XMLParser::find_tag(&xml_parser.xml_entries, "format");
+
+        // Get the Format Attributes
+        for attr in format_entry.attributes {
+            match attr.name.as_ref() {
+                "format_name" => target.format_container.name = attr.value,
+                "duration" => {
+                    let duration = attr.value[..match attr.value.find(" ") {
+                                                  Some(value) => value,
+                                                  None => continue,
+                                              }].to_string();
+                    target.duration = convert_duration(&duration);
+                }
+                "size" => {
+                    let file_size = attr.value[..match attr.value.find(" ") {
+                                                   Some(value) => value,
+                                                   None => continue,
+                                               }].to_string();
+                    target.file_size = match file_size.parse::<u64>() {
+                        Ok(value) => value,
+                        Err(_) => continue,
+                    };
+                }
+                _ => (),
+            }
+        }
+
+        // Get the Format Tags
+        for sub_tag in format_entry.sub_tags {
+            if sub_tag.tag == "tag" {
+                let tag_name = match sub_tag.attributes.first() {
+                    Some(value) => value,
+                    None => continue,
+                };
+                let tag_value = match sub_tag.attributes.get(1) {
+                    Some(value) => value,
+                    None => continue,
+                };
+                insert_meta_data(&tag_name.value, &tag_value.value, target);
+            }
+        }
+
+        // Parse the Streams
+        let streams_entry: XMLEntry = XMLParser::find_tag(&xml_parser.xml_entries, "streams");
+
+        for stream_entry in streams_entry.sub_tags {
+            let mut stream: Stream = Stream::new();
+
+            // Parse the Streams attributes
+            for attr in stream_entry.attributes {
+
+                match attr.name.as_ref() {
+                    "index" => {
+                        stream.index = match attr.value.parse::<u8>() {
+                            Ok(value) => value,
+                            Err(_) => continue,
+                        }
+                    }
+                    "codec_name" => stream.codec_name = attr.value,
+                    "codec_type" => {
+                        match attr.value.as_ref() {
+                            "audio" => stream.stream_type = StreamType::AUDIO,
+                            "video" => stream.stream_type = StreamType::VIDEO,
+                            "image" => stream.stream_type = StreamType::IMAGE,
+                            "picture" => stream.stream_type = StreamType::IMAGE,
+                            "subtitle" => stream.stream_type = StreamType::SUBTITLE,
+                            _ => stream.stream_type = StreamType::UNKNOWN,
+                        }
+                    }
+                    "width" => {
+                        stream.frame_width = match attr.value.parse::<u16>() {
+                            Ok(value) => value,
+                            Err(_) => continue,
+                        }
+                    }
+                    "height" => {
+                        stream.frame_height = match attr.value.parse::<u16>() {
+                            Ok(value) => value,
+                            Err(_) => continue,
+                        }
+                    }
+                    "bits_per_sample" => {
+                        stream.bit_depth = match attr.value.parse::<u8>() {
+                            Ok(value) => value,
+                            Err(_) => continue,
+                        }
+                    }
+                    "sample_rate" => {
+                        stream.sample_rate = match attr.value[..match attr.value.find(" ") {
+                                                                    Some(value) => value,
+                                                                    None => continue,
+                                                                }].to_string()
+                            .parse::<u32>() {
+                            Ok(value) => value,
+                            Err(_) => continue,
+                        }
+                    }
+                    "channels" => {
+                        stream.audio_channels = match attr.value.parse::<u8>() {
+                            Ok(value) => value,
+                            Err(_) => continue,
+                        }
+                    }
+                    "bit_rate" => {
+                        stream.bitrate = match attr.value[..match attr.value.find(" ") {
+                                                                Some(value) => value,
+                                                                None => continue,
+                                                            }].to_string()
+                            .parse::<u64>() {
+                            Ok(value) => value,
+                            Err(_) => continue,
+                        }
+                    }
+                    _ => (),
+                }
+            }
+
+            // Parse the Streams disposition
+            for sub_stream in stream_entry.sub_tags {
+                match sub_stream.tag.as_ref() {
+                    "disposition" => {
+                        for attr in sub_stream.attributes {
+
+                            match attr.name.as_ref() {
+                                "default" => stream.is_default = attr.value != "0",
+                                "forced" => stream.is_forced = attr.value != "0",
+                                _ => (),
+                            }
+                        }
+                    }
+                    "tag" => {
+                        let tag = match sub_stream.attributes.first() {
+                            Some(value) => value,
+                            None => continue,
+                        };
+                        if tag.value == "language" {
+                            let tag_value = match sub_stream.attributes.get(1) {
+                                Some(value) => value,
+                                None => continue,
+                            };
+                            stream.language = tag_value.value.clone();
+                        }
+                    }
+                    _ => (),
+                }
+            }
+
+            // Add the Stream to the Media Item if not unknown
+            match stream.stream_type {
+                StreamType::UNKNOWN => {}
+                _ => {
+                    target.media_tracks.push(stream);
+                }
+            }
+        }
+
+        !target.media_tracks.is_empty()
+    }
+}
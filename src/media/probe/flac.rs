@@ -0,0 +1,151 @@
+use media::{Item, Stream, StreamType};
+use super::{MediaProber, format_duration, average_bitrate};
+use super::tags::{id3, vorbis, set_picture};
+use super::reader::{ProbeReader, read_be};
+
+/// Metadata Block Types used by the Prober
+const BLOCK_STREAMINFO: u8 = 0;
+const BLOCK_VORBIS_COMMENT: u8 = 4;
+const BLOCK_PICTURE: u8 = 6;
+
+/// Largest Vorbis Comment Block that will be read
+const MAX_COMMENT_SIZE: usize = 1024 * 1024;
+
+/// Number of Bytes of a Picture Block read to find the Picture Data
+const PICTURE_HEADER_SIZE: usize = 64 * 1024;
+
+/// # FlacProber
+///
+/// Reads FLAC Files. The Stream Information is taken from the
+/// mandatory STREAMINFO Block, the Tags from the Vorbis Comment
+/// Block and the Cover Art from the Picture Blocks.
+pub struct FlacProber;
+
+impl MediaProber for FlacProber {
+    fn can_probe(&self, header: &[u8], extension: &str) -> bool {
+        header.starts_with(b"fLaC") || (extension == "flac" && id3::get_tag_size(header).is_some())
+    }
+
+    fn probe(&self, path: &str, target: &mut Item) -> bool {
+        let mut reader = match ProbeReader::open(path) {
+            Some(value) => value,
+            None => return false,
+        };
+
+        // Some Tools put an ID3v2 Tag in front of the Stream
+        let mut pos: u64 = id3::get_tag_size(&reader.read_up_to(0, id3::HEADER_SIZE)).unwrap_or(0);
+
+        match reader.read_at(pos, 4) {
+            Some(ref value) if &value[..] == b"fLaC" => pos += 4,
+            _ => return false,
+        }
+
+        let mut stream: Option<Stream> = None;
+        let mut seconds: f64 = 0.0;
+
+        // Walk the Metadata Blocks up to the last one
+        loop {
+            let header = match reader.read_at(pos, 4) {
+                Some(value) => value,
+                None => return false,
+            };
+
+            let is_last = header[0] & 0x80 != 0;
+            let block_type = header[0] & 0x7F;
+            let size = read_be(&header, 1, 3).unwrap_or(0) as usize;
+            pos += 4;
+
+            if block_type == BLOCK_STREAMINFO {
+                let data = match reader.read_at(pos, size) {
+                    Some(value) => value,
+                    None => return false,
+                };
+
+                // 20 Bit Sample Rate, 3 Bit Channels, 5 Bit Bit Depth, 36 Bit Samples
+                let info = match read_be(&data, 10, 8) {
+                    Some(value) => value,
+                    None => return false,
+                };
+
+                let sample_rate = (info >> 44) as u32;
+                let total_samples = info & 0xF_FFFF_FFFF;
+
+                if sample_rate > 0 {
+                    seconds = total_samples as f64 / f64::from(sample_rate);
+                }
+
+                let mut value: Stream = Stream::new();
+                value.stream_type = StreamType::AUDIO;
+                value.codec_name = "flac".to_string();
+                value.sample_rate = sample_rate;
+                value.audio_channels = (((info >> 41) & 0x07) + 1) as u8;
+                value.bit_depth = (((info >> 36) & 0x1F) + 1) as u8;
+                value.is_default = true;
+                stream = Some(value);
+            } else if block_type == BLOCK_VORBIS_COMMENT && size <= MAX_COMMENT_SIZE {
+                if let Some(data) = reader.read_at(pos, size) {
+                    vorbis::read_comments(&data, target);
+                }
+            } else if block_type == BLOCK_PICTURE {
+                let data = reader.read_up_to(pos, size.min(PICTURE_HEADER_SIZE));
+                read_picture(&data, pos, target);
+            }
+
+            pos += size as u64;
+
+            if is_last {
+                break;
+            }
+        }
+
+        let mut stream = match stream {
+            Some(value) => value,
+            None => return false,
+        };
+
+        stream.bitrate = average_bitrate(reader.get_size().saturating_sub(pos), seconds);
+
+        target.media_tracks.push(stream);
+        target.format_container.name = "flac".to_string();
+        target.duration = format_duration(seconds);
+        target.file_size = reader.get_size();
+
+        true
+    }
+}
+
+/// Reads the Header of a Picture Block and uses the Picture as
+/// Thumbnail. The Picture Data itself is not read.
+///
+/// # Arguments
+///
+/// * `data` - Start of the Picture Block
+/// * `position` - Position of the Block inside the File
+/// * `target` - Item to store the Thumbnail in
+fn read_picture(data: &[u8], position: u64, target: &mut Item) {
+    // Picture Type, Mime Type and Description with their Lengths
+    let picture_type = read_be(data, 0, 4).unwrap_or(0) as u32;
+    let mime_size = read_be(data, 4, 4).unwrap_or(0) as usize;
+
+    let mime = match data.get(8..8 + mime_size) {
+        Some(value) => String::from_utf8_lossy(value).to_string(),
+        None => return,
+    };
+
+    let description_size = read_be(data, 8 + mime_size, 4).unwrap_or(0) as usize;
+
+    // Width, Height, Color Depth, Number of Colors and Data Length follow
+    let pos = 12 + mime_size + description_size;
+
+    let size = match read_be(data, pos + 16, 4) {
+        Some(value) => value,
+        None => return,
+    };
+
+    set_picture(target, picture_type, &mime, position + pos as u64 + 20, size);
+
+    if target.thumbnail.offset == position + pos as u64 + 20 {
+        target.thumbnail.width = read_be(data, pos, 4).unwrap_or(0).min(u64::from(u16::MAX)) as u16;
+        target.thumbnail.height = read_be(data, pos + 4, 4).unwrap_or(0).min(u64::from(u16::MAX)) as u16;
+    }
+}
@@ -0,0 +1,364 @@
+use media::{Item, Stream, StreamType};
+use super::MediaProber;
+use super::mp4::{get_atoms, find_atom};
+use super::tags::{exif, xmp};
+use super::reader::{ProbeReader, read_be};
+
+/// Brands of the "ftyp" Atom marking a HEIF / AVIF Image
+const HEIF_BRANDS: [&[u8; 4]; 8] = [b"heic", b"heix", b"heim", b"heis", b"hevc", b"hevx", b"mif1", b"avif"];
+
+/// Largest "meta" Atom that will be read into Memory
+const MAX_META_SIZE: u64 = 4 * 1024 * 1024;
+
+/// Largest EXIF or XMP Item that will be read into Memory
+const MAX_ITEM_SIZE: u64 = 1024 * 1024;
+
+/// Location of an Item inside the File
+struct ItemLocation {
+    id: u64,
+    offset: u64,
+    length: u64,
+}
+
+/// # HeifProber
+///
+/// Reads HEIF Images (HEIC and AVIF). The Image Items are
+/// described by the top level "meta" Atom. Dimensions are
+/// taken from the Properties of the primary Item, the EXIF
+/// and XMP Information from the Items holding them.
+pub struct HeifProber;
+
+impl MediaProber for HeifProber {
+    fn can_probe(&self, header: &[u8], _extension: &str) -> bool {
+        match (header.get(4..8), header.get(8..12)) {
+            (Some(atom), Some(brand)) => atom == b"ftyp" && HEIF_BRANDS.iter().any(|value| &value[..] == brand),
+            _ => false,
+        }
+    }
+
+    fn probe(&self, path: &str, target: &mut Item) -> bool {
+        let mut reader = match ProbeReader::open(path) {
+            Some(value) => value,
+            None => return false,
+        };
+
+        let meta = match find_meta(&mut reader) {
+            Some(value) => value,
+            None => return false,
+        };
+
+        // "meta" is a Full Atom with Version and Flags
+        let children = match meta.get(4..) {
+            Some(value) => value,
+            None => return false,
+        };
+
+        let primary_id = find_atom(children, b"pitm")
+            .and_then(|pitm| if pitm.first() == Some(&0) { read_be(pitm, 4, 2) } else { read_be(pitm, 4, 4) })
+            .unwrap_or(0);
+
+        let items = find_atom(children, b"iinf").map(get_item_infos).unwrap_or_default();
+        let locations = find_atom(children, b"iloc").map(get_item_locations).unwrap_or_default();
+
+        let codec = match items.iter().find(|item| item.0 == primary_id).map(|item| &item.1[..]) {
+            Some(b"hvc1") => "hevc",
+            Some(b"av01") => "av1",
+            Some(b"jpeg") => "mjpeg",
+            Some(b"grid") => "hevc",
+            _ => return false,
+        };
+
+        // Dimensions and Rotation of the primary Item
+        let (width, height, rotation) = match find_atom(children, b"iprp") {
+            Some(iprp) => read_properties(iprp, primary_id),
+            None => (0, 0, None),
+        };
+
+        // EXIF and XMP are stored as Items of their own
+        for &(id, ref item_type, ref content_type) in &items {
+            let is_xmp = &item_type[..] == b"mime" && content_type == "application/rdf+xml";
+
+            if &item_type[..] != b"Exif" && !is_xmp {
+                continue;
+            }
+
+            let location = match locations.iter().find(|location| location.id == id) {
+                Some(value) if value.length <= MAX_ITEM_SIZE => value,
+                _ => continue,
+            };
+
+            let data = match reader.read_at(location.offset, location.length as usize) {
+                Some(value) => value,
+                None => continue,
+            };
+
+            if is_xmp {
+                xmp::read_xmp(&data, target);
+            } else if let Some(offset) = read_be(&data, 0, 4) {
+                // The EXIF Item starts with the Offset of the TIFF Header
+                if let Some(tiff) = data.get(4 + offset as usize..) {
+                    exif::read_exif(tiff, target);
+                }
+            }
+        }
+
+        // Rotation Properties are what Decoders apply, so they win over EXIF
+        if let Some(value) = rotation {
+            target.meta_data.orientation = value;
+        }
+
+        let mut stream: Stream = Stream::new();
+        stream.stream_type = StreamType::IMAGE;
+        stream.codec_name = codec.to_string();
+        stream.frame_width = width.min(u64::from(u16::MAX)) as u16;
+        stream.frame_height = height.min(u64::from(u16::MAX)) as u16;
+        stream.bit_depth = 8;
+        stream.is_default = true;
+
+        target.media_tracks.push(stream);
+        target.format_container.name = "heif".to_string();
+        target.file_size = reader.get_size();
+
+        true
+    }
+}
+
+/// Walks the Top Level Atoms of the File and returns the
+/// Content of the "meta" Atom.
+///
+/// # Arguments
+///
+/// * `reader` - Reader of the opened File
+fn find_meta(reader: &mut ProbeReader) -> Option<Vec<u8>> {
+    let mut pos: u64 = 0;
+
+    while let Some(header) = reader.read_at(pos, 8) {
+        let size = read_be(&header, 0, 4)?;
+
+        // Images keep the "meta" Atom in Front, 64 Bit Sizes are not expected there
+        if size < 8 {
+            return None;
+        }
+
+        if &header[4..8] == b"meta" {
+            if size > MAX_META_SIZE {
+                return None;
+            }
+
+            return reader.read_at(pos + 8, (size - 8) as usize);
+        }
+
+        pos += size;
+    }
+
+    None
+}
+
+/// Reads the Item Information Atom. Returns the Id, the Type
+/// and the Content Type (for "mime" Items) of each Item.
+///
+/// # Arguments
+///
+/// * `iinf` - Content of the "iinf" Atom
+fn get_item_infos(iinf: &[u8]) -> Vec<(u64, Vec<u8>, String)> {
+    let mut items: Vec<(u64, Vec<u8>, String)> = Vec::new();
+    let entries_pos = if iinf.first() == Some(&0) { 6 } else { 8 };
+
+    for (atom_type, infe) in get_atoms(iinf.get(entries_pos..).unwrap_or(&[])) {
+        // Only Version 2 and 3 Entries carry an Item Type
+        let id_size = match (atom_type, infe.first()) {
+            (b"infe", Some(&2)) => 2,
+            (b"infe", Some(&3)) => 4,
+            _ => continue,
+        };
+
+        let id = match read_be(infe, 4, id_size) {
+            Some(value) => value,
+            None => continue,
+        };
+
+        // Item Protection Index in Front of the Type
+        let type_pos = 4 + id_size + 2;
+
+        let item_type = match infe.get(type_pos..type_pos + 4) {
+            Some(value) => value.to_vec(),
+            None => continue,
+        };
+
+        // Item Name and Content Type are zero terminated
+        let mut content_type = String::new();
+
+        if &item_type[..] == b"mime" {
+            let strings: Vec<&[u8]> = infe[type_pos + 4..].split(|byte| *byte == 0).collect();
+
+            if let Some(value) = strings.get(1) {
+                content_type = String::from_utf8_lossy(value).to_string();
+            }
+        }
+
+        items.push((id, item_type, content_type));
+    }
+
+    items
+}
+
+/// Reads the Item Location Atom. Only Items stored inside the
+/// File are returned, using their first Extent.
+///
+/// # Arguments
+///
+/// * `iloc` - Content of the "iloc" Atom
+fn get_item_locations(iloc: &[u8]) -> Vec<ItemLocation> {
+    let mut locations: Vec<ItemLocation> = Vec::new();
+
+    let version = match iloc.first() {
+        Some(value) => *value,
+        None => return locations,
+    };
+
+    let sizes = read_be(iloc, 4, 2).unwrap_or(0) as usize;
+    let offset_size = (sizes >> 12) & 0x0F;
+    let length_size = (sizes >> 8) & 0x0F;
+    let base_offset_size = (sizes >> 4) & 0x0F;
+    let index_size = if version == 1 || version == 2 { sizes & 0x0F } else { 0 };
+    let id_size = if version < 2 { 2 } else { 4 };
+
+    let count = read_be(iloc, 6, id_size).unwrap_or(0);
+    let mut pos = 6 + id_size;
+
+    for _ in 0..count {
+        let id = match read_be(iloc, pos, id_size) {
+            Some(value) => value,
+            None => break,
+        };
+
+        pos += id_size;
+
+        // Construction Method 0 means inside the File, everything else is skipped
+        let mut method = 0;
+
+        if version == 1 || version == 2 {
+            method = read_be(iloc, pos, 2).unwrap_or(0) & 0x0F;
+            pos += 2;
+        }
+
+        // Data Reference Index
+        pos += 2;
+
+        let base_offset = read_be(iloc, pos, base_offset_size).unwrap_or(0);
+        pos += base_offset_size;
+
+        let extent_count = match read_be(iloc, pos, 2) {
+            Some(value) => value as usize,
+            None => break,
+        };
+
+        pos += 2;
+
+        let extent_pos = pos + index_size;
+        pos += extent_count * (index_size + offset_size + length_size);
+
+        if method != 0 || extent_count == 0 {
+            continue;
+        }
+
+        if let (Some(offset), Some(length)) = (
+            read_be(iloc, extent_pos, offset_size),
+            read_be(iloc, extent_pos + offset_size, length_size),
+        ) {
+            locations.push(ItemLocation {
+                id,
+                offset: base_offset + offset,
+                length,
+            });
+        }
+    }
+
+    locations
+}
+
+/// Reads the Item Properties associated with an Item. Returns its
+/// Width, Height and the EXIF Orientation matching its Rotation.
+///
+/// # Arguments
+///
+/// * `iprp` - Content of the "iprp" Atom
+/// * `item_id` - Id of the Item
+fn read_properties(iprp: &[u8], item_id: u64) -> (u64, u64, Option<u8>) {
+    let properties = find_atom(iprp, b"ipco").map(get_atoms).unwrap_or_default();
+    let mut indices: Vec<usize> = Vec::new();
+
+    if let Some(ipma) = find_atom(iprp, b"ipma") {
+        let version = ipma.first().cloned().unwrap_or(0);
+        let large_index = ipma.get(3).is_some_and(|flags| flags & 0x01 != 0);
+        let id_size = if version < 1 { 2 } else { 4 };
+        let index_size = if large_index { 2 } else { 1 };
+
+        let count = read_be(ipma, 4, 4).unwrap_or(0);
+        let mut pos: usize = 8;
+
+        for _ in 0..count {
+            let (id, associations) = match (read_be(ipma, pos, id_size), read_be(ipma, pos + id_size, 1)) {
+                (Some(id), Some(associations)) => (id, associations as usize),
+                _ => break,
+            };
+
+            pos += id_size + 1;
+
+            for index in 0..associations {
+                // The highest Bit marks essential Properties
+                let value = read_be(ipma, pos + index * index_size, index_size).unwrap_or(0);
+                let mask = if large_index { 0x7FFF } else { 0x7F };
+
+                if id == item_id {
+                    indices.push((value & mask) as usize);
+                }
+            }
+
+            pos += associations * index_size;
+        }
+    }
+
+    let mut width: u64 = 0;
+    let mut height: u64 = 0;
+    let mut rotation: Option<u8> = None;
+
+    // Property Indices start at 1
+    for index in indices {
+        let (property_type, body) = match properties.get(index.wrapping_sub(1)) {
+            Some(value) => *value,
+            None => continue,
+        };
+
+        match property_type {
+            b"ispe" => {
+                width = read_be(body, 4, 4).unwrap_or(0);
+                height = read_be(body, 8, 4).unwrap_or(0);
+            }
+            b"irot" => {
+                // Counter clockwise Rotation in Steps of 90 Degrees
+                rotation = match body.first().map(|value| value & 0x03) {
+                    Some(1) => Some(8),
+                    Some(2) => Some(3),
+                    Some(3) => Some(6),
+                    _ => Some(1),
+                };
+            }
+            _ => (),
+        }
+    }
+
+    // Fall back to the largest Image Size if the Associations are missing
+    if width == 0 || height == 0 {
+        for &(property_type, body) in &properties {
+            let value_width = read_be(body, 4, 4).unwrap_or(0);
+
+            if property_type == b"ispe" && value_width > width {
+                width = value_width;
+                height = read_be(body, 8, 4).unwrap_or(0);
+            }
+        }
+    }
+
+    (width, height, rotation)
+}
@@ -0,0 +1,185 @@
+use media::{Item, Stream, StreamType};
+use super::MediaProber;
+use super::tags::{exif, xmp};
+use super::reader::{ProbeReader, read_be};
+
+/// Signature every PNG File starts with
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+/// How far into a JPEG File the Frame Header is searched for.
+/// Large EXIF Blocks with embedded Previews come before it.
+const JPEG_SEARCH_SIZE: u64 = 4 * 1024 * 1024;
+
+/// Headers of the APP1 Segments holding EXIF and XMP
+const EXIF_HEADER: &[u8] = b"Exif\0\0";
+const XMP_HEADER: &[u8] = b"http://ns.adobe.com/xap/1.0/\0";
+
+/// Number of Bytes read from the Start of a TIFF File. The
+/// Directories of the first Image are expected inside of them.
+const TIFF_READ_SIZE: usize = 1024 * 1024;
+
+/// # ImageProber
+///
+/// Reads the Dimensions and Bit Depth of JPEG, PNG and TIFF
+/// Images from their Headers. The EXIF and XMP Information of
+/// JPEG and TIFF Images is read as well.
+pub struct ImageProber;
+
+impl MediaProber for ImageProber {
+    fn can_probe(&self, header: &[u8], _extension: &str) -> bool {
+        header.starts_with(&[0xFF, 0xD8, 0xFF]) || header.starts_with(&PNG_SIGNATURE) ||
+            header.starts_with(b"II*\0") || header.starts_with(b"MM\0*")
+    }
+
+    fn probe(&self, path: &str, target: &mut Item) -> bool {
+        let mut reader = match ProbeReader::open(path) {
+            Some(value) => value,
+            None => return false,
+        };
+
+        let header = reader.read_up_to(0, PNG_SIGNATURE.len());
+
+        let stream = if header.starts_with(&PNG_SIGNATURE) {
+            target.format_container.name = "png_pipe".to_string();
+            probe_png(&mut reader)
+        } else if header.starts_with(&[0xFF, 0xD8]) {
+            target.format_container.name = "jpeg_pipe".to_string();
+            probe_jpeg(&mut reader, target)
+        } else {
+            target.format_container.name = "tiff_pipe".to_string();
+            probe_tiff(&mut reader, target)
+        };
+
+        match stream {
+            Some(value) => target.media_tracks.push(value),
+            None => return false,
+        }
+
+        target.file_size = reader.get_size();
+
+        true
+    }
+}
+
+/// Reads the IHDR Chunk following the PNG Signature
+///
+/// # Arguments
+///
+/// * `reader` - Reader of the opened File
+fn probe_png(reader: &mut ProbeReader) -> Option<Stream> {
+    let data = reader.read_at(PNG_SIGNATURE.len() as u64, 8 + 13)?;
+
+    if &data[4..8] != b"IHDR" {
+        return None;
+    }
+
+    Some(new_image_stream(
+        "png",
+        read_be(&data, 8, 4)?,
+        read_be(&data, 12, 4)?,
+        data[16],
+    ))
+}
+
+/// Walks the JPEG Markers up to the first Start Of Frame
+/// Marker which holds the Dimensions of the Image. The EXIF
+/// and XMP Segments in Front of it are read on the Way.
+///
+/// # Arguments
+///
+/// * `reader` - Reader of the opened File
+/// * `target` - Item to store the Meta Data in
+fn probe_jpeg(reader: &mut ProbeReader, target: &mut Item) -> Option<Stream> {
+    let mut pos: u64 = 2;
+
+    while pos < JPEG_SEARCH_SIZE {
+        let marker = reader.read_at(pos, 4)?;
+
+        if marker[0] != 0xFF {
+            return None;
+        }
+
+        match marker[1] {
+            // Fill Bytes in front of a Marker
+            0xFF => {
+                pos += 1;
+                continue;
+            }
+            // Markers without a Segment
+            0x01 | 0xD0..=0xD7 => {
+                pos += 2;
+                continue;
+            }
+            // End of Image or Start of Scan without a Frame Header
+            0xD9 | 0xDA => return None,
+            _ => (),
+        }
+
+        let size = read_be(&marker, 2, 2)?;
+
+        // All Start Of Frame Markers except DHT, JPG and DAC
+        match marker[1] {
+            0xC0..=0xC3 | 0xC5..=0xC7 | 0xC9..=0xCB | 0xCD..=0xCF => {
+                let frame = reader.read_at(pos + 4, 5)?;
+
+                return Some(new_image_stream(
+                    "mjpeg",
+                    read_be(&frame, 3, 2)?,
+                    read_be(&frame, 1, 2)?,
+                    frame[0],
+                ));
+            }
+            // APP1 holds either EXIF or XMP
+            0xE1 if size > 2 => {
+                let segment = reader.read_up_to(pos + 4, size as usize - 2);
+
+                if segment.starts_with(EXIF_HEADER) {
+                    exif::read_exif(&segment[EXIF_HEADER.len()..], target);
+                } else if segment.starts_with(XMP_HEADER) {
+                    xmp::read_xmp(&segment[XMP_HEADER.len()..], target);
+                }
+
+                pos += 2 + size;
+            }
+            _ => pos += 2 + size,
+        }
+    }
+
+    None
+}
+
+/// Reads the Dimensions of the first Image inside a TIFF File
+/// and its EXIF Information.
+///
+/// # Arguments
+///
+/// * `reader` - Reader of the opened File
+/// * `target` - Item to store the Meta Data in
+fn probe_tiff(reader: &mut ProbeReader, target: &mut Item) -> Option<Stream> {
+    let data = reader.read_up_to(0, TIFF_READ_SIZE);
+    let (width, height, bit_depth) = exif::read_dimensions(&data)?;
+
+    exif::read_exif(&data, target);
+
+    Some(new_image_stream("tiff", width, height, bit_depth))
+}
+
+/// Creates the single Image Stream of a Picture
+///
+/// # Arguments
+///
+/// * `codec` - ffmpeg Codec Name
+/// * `width` - Width in Pixel
+/// * `height` - Height in Pixel
+/// * `bit_depth` - Bits per Sample
+fn new_image_stream(codec: &str, width: u64, height: u64, bit_depth: u8) -> Stream {
+    let mut stream: Stream = Stream::new();
+    stream.stream_type = StreamType::IMAGE;
+    stream.codec_name = codec.to_string();
+    stream.frame_width = width.min(u64::from(u16::MAX)) as u16;
+    stream.frame_height = height.min(u64::from(u16::MAX)) as u16;
+    stream.bit_depth = bit_depth;
+    stream.is_default = true;
+
+    stream
+}
@@ -0,0 +1,495 @@
+use media::{Item, Stream, StreamType};
+use super::{MediaProber, format_duration};
+use super::tags::insert_meta_data;
+use super::reader::{ProbeReader, read_be, read_text};
+
+/// EBML Element IDs used by the Prober
+const ID_EBML: u64 = 0x1A45_DFA3;
+const ID_DOC_TYPE: u64 = 0x4282;
+const ID_SEGMENT: u64 = 0x1853_8067;
+const ID_INFO: u64 = 0x1549_A966;
+const ID_TIMESTAMP_SCALE: u64 = 0x2A_D7B1;
+const ID_DURATION: u64 = 0x4489;
+const ID_TITLE: u64 = 0x7BA9;
+const ID_TRACKS: u64 = 0x1654_AE6B;
+const ID_TRACK_ENTRY: u64 = 0xAE;
+const ID_TRACK_TYPE: u64 = 0x83;
+const ID_FLAG_DEFAULT: u64 = 0x88;
+const ID_FLAG_FORCED: u64 = 0x55AA;
+const ID_LANGUAGE: u64 = 0x22_B59C;
+const ID_LANGUAGE_BCP47: u64 = 0x22_B59D;
+const ID_CODEC_ID: u64 = 0x86;
+const ID_VIDEO: u64 = 0xE0;
+const ID_PIXEL_WIDTH: u64 = 0xB0;
+const ID_PIXEL_HEIGHT: u64 = 0xBA;
+const ID_AUDIO: u64 = 0xE1;
+const ID_SAMPLING_FREQUENCY: u64 = 0xB5;
+const ID_CHANNELS: u64 = 0x9F;
+const ID_BIT_DEPTH: u64 = 0x6264;
+const ID_CLUSTER: u64 = 0x1F43_B675;
+const ID_SEEK_HEAD: u64 = 0x114D_9B74;
+const ID_SEEK: u64 = 0x4DBB;
+const ID_SEEK_ID: u64 = 0x53AB;
+const ID_SEEK_POSITION: u64 = 0x53AC;
+const ID_TAGS: u64 = 0x1254_C367;
+const ID_TAG: u64 = 0x7373;
+const ID_SIMPLE_TAG: u64 = 0x67C8;
+const ID_TAG_NAME: u64 = 0x45A3;
+const ID_TAG_STRING: u64 = 0x4487;
+
+/// Matroska Track Types
+const TRACK_VIDEO: u64 = 1;
+const TRACK_AUDIO: u64 = 2;
+const TRACK_SUBTITLE: u64 = 17;
+
+/// Largest Info / Tracks Element that will be read into Memory
+const MAX_ELEMENT_SIZE: u64 = 16 * 1024 * 1024;
+
+/// # MatroskaProber
+///
+/// Reads Matroska and WebM Files. The EBML Elements of the
+/// Segment are walked up to the first Cluster, reading the
+/// Segment Info and the Track Entries on the Way. The Tags
+/// usually follow the Clusters and are found through the
+/// Seek Head.
+pub struct MatroskaProber;
+
+impl MediaProber for MatroskaProber {
+    fn can_probe(&self, header: &[u8], _extension: &str) -> bool {
+        read_be(header, 0, 4) == Some(ID_EBML)
+    }
+
+    fn probe(&self, path: &str, target: &mut Item) -> bool {
+        let mut reader = match ProbeReader::open(path) {
+            Some(value) => value,
+            None => return false,
+        };
+
+        // EBML Header with the Document Type
+        let (id, header_size, size) = match read_element_header(&mut reader, 0) {
+            Some(value) => value,
+            None => return false,
+        };
+
+        let size = match size {
+            Some(value) if id == ID_EBML && value <= 4096 => value,
+            _ => return false,
+        };
+
+        let ebml = reader.read_up_to(header_size, size as usize);
+        let doc_type = match get_elements(&ebml).into_iter().find(|element| element.0 == ID_DOC_TYPE) {
+            Some(value) => read_text(value.1),
+            None => "matroska".to_string(),
+        };
+
+        if doc_type != "matroska" && doc_type != "webm" {
+            return false;
+        }
+
+        // The Segment holds everything else
+        let mut pos = header_size + size;
+
+        let (id, header_size, size) = match read_element_header(&mut reader, pos) {
+            Some(value) => value,
+            None => return false,
+        };
+
+        if id != ID_SEGMENT {
+            return false;
+        }
+
+        pos += header_size;
+
+        // Seek Positions are relative to the Start of the Segments Content
+        let segment_start = pos;
+        let segment_end = match size {
+            Some(value) => (pos + value).min(reader.get_size()),
+            None => reader.get_size(),
+        };
+
+        let mut seconds: f64 = 0.0;
+        let mut has_info = false;
+        let mut has_tracks = false;
+        let mut has_tags = false;
+        let mut tags_pos: Option<u64> = None;
+
+        while pos < segment_end && !(has_info && has_tracks) {
+            let (id, header_size, size) = match read_element_header(&mut reader, pos) {
+                Some(value) => value,
+                None => break,
+            };
+
+            // Elements of unknown Size can not be skipped
+            let size = match size {
+                Some(value) => value,
+                None => break,
+            };
+
+            match id {
+                ID_INFO | ID_TRACKS | ID_SEEK_HEAD | ID_TAGS if size <= MAX_ELEMENT_SIZE => {
+                    let data = match reader.read_at(pos + header_size, size as usize) {
+                        Some(value) => value,
+                        None => break,
+                    };
+
+                    match id {
+                        ID_INFO => {
+                            seconds = read_info(&data, target);
+                            has_info = true;
+                        }
+                        ID_TRACKS => {
+                            read_tracks(&data, target);
+                            has_tracks = true;
+                        }
+                        ID_SEEK_HEAD => {
+                            tags_pos = read_seek_position(&data, ID_TAGS)
+                                .and_then(|value| segment_start.checked_add(value));
+                        }
+                        _ => {
+                            read_tags(&data, target);
+                            has_tags = true;
+                        }
+                    }
+                }
+                // Media Data follows, the Track Entries should be known by now
+                ID_CLUSTER if has_tracks => break,
+                _ => (),
+            }
+
+            pos += header_size + size;
+        }
+
+        if target.media_tracks.is_empty() {
+            return false;
+        }
+
+        if let Some(tags_pos) = tags_pos.filter(|_| !has_tags) {
+            if let Some((ID_TAGS, header_size, Some(size))) = read_element_header(&mut reader, tags_pos) {
+                if size <= MAX_ELEMENT_SIZE {
+                    if let Some(data) = reader.read_at(tags_pos + header_size, size as usize) {
+                        read_tags(&data, target);
+                    }
+                }
+            }
+        }
+
+        target.format_container.name = "matroska,webm".to_string();
+        target.duration = format_duration(seconds);
+        target.file_size = reader.get_size();
+
+        true
+    }
+}
+
+/// Reads the ID and Size of the Element starting at the given
+/// Position of the File. Returns the ID, the Size of the Element
+/// Header and the Size of the Content (None if unknown).
+///
+/// # Arguments
+///
+/// * `reader` - Reader of the opened File
+/// * `pos` - Position of the Element
+fn read_element_header(reader: &mut ProbeReader, pos: u64) -> Option<(u64, u64, Option<u64>)> {
+    let data = reader.read_up_to(pos, 12);
+    let (id, id_size) = read_id(&data, 0)?;
+    let (size, size_size) = read_size(&data, id_size)?;
+
+    Some((id, (id_size + size_size) as u64, size))
+}
+
+/// Reads an Element ID. The Length Marker is kept as Part of the ID.
+///
+/// # Arguments
+///
+/// * `data` - Buffer to read from
+/// * `pos` - Position of the ID
+fn read_id(data: &[u8], pos: usize) -> Option<(u64, usize)> {
+    let length = data.get(pos)?.leading_zeros() as usize + 1;
+
+    if length > 4 {
+        return None;
+    }
+
+    Some((read_be(data, pos, length)?, length))
+}
+
+/// Reads a variable Size Integer used for Element Sizes.
+/// Returns None as Size if all Bits are set (unknown Size).
+///
+/// # Arguments
+///
+/// * `data` - Buffer to read from
+/// * `pos` - Position of the Size
+fn read_size(data: &[u8], pos: usize) -> Option<(Option<u64>, usize)> {
+    let length = data.get(pos)?.leading_zeros() as usize + 1;
+
+    if length > 8 {
+        return None;
+    }
+
+    let mask: u64 = (1 << (7 * length)) - 1;
+    let value = read_be(data, pos, length)? & mask;
+
+    if value == mask {
+        Some((None, length))
+    } else {
+        Some((Some(value), length))
+    }
+}
+
+/// Splits the Content of a Master Element into its Child Elements.
+/// Returns a List of the Element IDs and their Content.
+///
+/// # Arguments
+///
+/// * `data` - Content of the Master Element
+fn get_elements(data: &[u8]) -> Vec<(u64, &[u8])> {
+    let mut elements: Vec<(u64, &[u8])> = Vec::new();
+    let mut pos: usize = 0;
+
+    while pos < data.len() {
+        let (id, id_size) = match read_id(data, pos) {
+            Some(value) => value,
+            None => break,
+        };
+
+        let (size, size_size) = match read_size(data, pos + id_size) {
+            Some(value) => value,
+            None => break,
+        };
+
+        let start = pos + id_size + size_size;
+        let end = match size {
+            Some(value) => start.saturating_add(value as usize),
+            None => data.len(),
+        };
+
+        if end > data.len() {
+            break;
+        }
+
+        elements.push((id, &data[start..end]));
+        pos = end;
+    }
+
+    elements
+}
+
+/// Reads an unsigned Integer Element
+///
+/// # Arguments
+///
+/// * `data` - Content of the Element
+fn read_uint(data: &[u8]) -> u64 {
+    read_be(data, 0, data.len().min(8)).unwrap_or(0)
+}
+
+/// Reads a Float Element stored in 4 or 8 Bytes
+///
+/// # Arguments
+///
+/// * `data` - Content of the Element
+fn read_float(data: &[u8]) -> f64 {
+    match data.len() {
+        4 => f64::from(f32::from_bits(read_uint(data) as u32)),
+        8 => f64::from_bits(read_uint(data)),
+        _ => 0.0,
+    }
+}
+
+/// Reads the Segment Info and returns the Duration in Seconds
+///
+/// # Arguments
+///
+/// * `data` - Content of the Info Element
+/// * `target` - Item to store the Title in
+fn read_info(data: &[u8], target: &mut Item) -> f64 {
+    let mut scale: u64 = 1_000_000;
+    let mut duration: f64 = 0.0;
+
+    for (id, body) in get_elements(data) {
+        match id {
+            ID_TIMESTAMP_SCALE => scale = read_uint(body),
+            ID_DURATION => duration = read_float(body),
+            ID_TITLE => insert_meta_data("title", &read_text(body), target),
+            _ => (),
+        }
+    }
+
+    duration * scale as f64 / 1_000_000_000.0
+}
+
+/// Returns the Position of the Element with the given ID listed
+/// inside the Seek Head, relative to the Start of the Segment
+///
+/// # Arguments
+///
+/// * `data` - Content of the Seek Head Element
+/// * `element_id` - ID of the Element to look for
+fn read_seek_position(data: &[u8], element_id: u64) -> Option<u64> {
+    get_elements(data)
+        .into_iter()
+        .filter(|seek| seek.0 == ID_SEEK)
+        .find_map(|seek| {
+            let children = get_elements(seek.1);
+            let id = children.iter().find(|child| child.0 == ID_SEEK_ID).map(|child| read_uint(child.1));
+            let position = children.iter().find(|child| child.0 == ID_SEEK_POSITION).map(|child| read_uint(child.1));
+
+            if id == Some(element_id) { position } else { None }
+        })
+}
+
+/// Reads the Simple Tags of all Tags. Only the Rating
+/// is taken, everything else is already known from the
+/// Segment Info.
+///
+/// # Arguments
+///
+/// * `data` - Content of the Tags Element
+/// * `target` - Item to store the Meta Data in
+fn read_tags(data: &[u8], target: &mut Item) {
+    for (id, tag) in get_elements(data) {
+        if id != ID_TAG {
+            continue;
+        }
+
+        for (id, simple_tag) in get_elements(tag) {
+            if id != ID_SIMPLE_TAG {
+                continue;
+            }
+
+            let children = get_elements(simple_tag);
+            let name = children.iter().find(|child| child.0 == ID_TAG_NAME).map(|child| read_text(child.1));
+            let value = children.iter().find(|child| child.0 == ID_TAG_STRING).map(|child| read_text(child.1));
+
+            if let (Some(name), Some(value)) = (name, value) {
+                if name.eq_ignore_ascii_case("LAW_RATING") {
+                    insert_meta_data("rating", &value, target);
+                }
+            }
+        }
+    }
+}
+
+/// Reads the Track Entries into Streams of the Item
+///
+/// # Arguments
+///
+/// * `data` - Content of the Tracks Element
+/// * `target` - Item to store the Streams in
+fn read_tracks(data: &[u8], target: &mut Item) {
+    let mut index: u8 = 0;
+
+    for (id, entry) in get_elements(data) {
+        if id != ID_TRACK_ENTRY {
+            continue;
+        }
+
+        let mut stream: Stream = Stream::new();
+        let mut track_type: u64 = 0;
+        let mut codec_id: String = String::new();
+        let mut language: String = "eng".to_string();
+
+        // Defaults of the Elements that might be left out
+        stream.is_default = true;
+
+        for (id, body) in get_elements(entry) {
+            match id {
+                ID_TRACK_TYPE => track_type = read_uint(body),
+                ID_FLAG_DEFAULT => stream.is_default = read_uint(body) != 0,
+                ID_FLAG_FORCED => stream.is_forced = read_uint(body) != 0,
+                ID_LANGUAGE => language = read_text(body),
+                ID_LANGUAGE_BCP47 => language = read_text(body),
+                ID_CODEC_ID => codec_id = read_text(body),
+                ID_VIDEO => {
+                    for (id, body) in get_elements(body) {
+                        match id {
+                            ID_PIXEL_WIDTH => stream.frame_width = read_uint(body) as u16,
+                            ID_PIXEL_HEIGHT => stream.frame_height = read_uint(body) as u16,
+                            _ => (),
+                        }
+                    }
+                }
+                ID_AUDIO => {
+                    stream.sample_rate = 8000;
+                    stream.audio_channels = 1;
+
+                    for (id, body) in get_elements(body) {
+                        match id {
+                            ID_SAMPLING_FREQUENCY => stream.sample_rate = read_float(body) as u32,
+                            ID_CHANNELS => stream.audio_channels = read_uint(body) as u8,
+                            ID_BIT_DEPTH => stream.bit_depth = read_uint(body) as u8,
+                            _ => (),
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        stream.stream_type = match track_type {
+            TRACK_VIDEO => StreamType::VIDEO,
+            TRACK_AUDIO => StreamType::AUDIO,
+            TRACK_SUBTITLE => StreamType::SUBTITLE,
+            _ => continue,
+        };
+
+        if language != "und" {
+            stream.language = language;
+        }
+
+        stream.codec_name = get_codec_name(&codec_id, stream.bit_depth);
+        stream.index = index;
+        index = index.saturating_add(1);
+
+        target.media_tracks.push(stream);
+    }
+}
+
+/// Returns the ffmpeg Codec Name for a Matroska Codec ID
+///
+/// # Arguments
+///
+/// * `codec_id` - Matroska Codec ID
+/// * `bit_depth` - Bits per Sample of Audio Tracks
+fn get_codec_name(codec_id: &str, bit_depth: u8) -> String {
+    let name = match codec_id {
+        "V_MPEG4/ISO/AVC" => "h264",
+        "V_MPEGH/ISO/HEVC" => "hevc",
+        "V_MPEG4/ISO/SP" | "V_MPEG4/ISO/ASP" | "V_MPEG4/ISO/AP" => "mpeg4",
+        "V_MPEG1" => "mpeg1video",
+        "V_MPEG2" => "mpeg2video",
+        "V_VP8" => "vp8",
+        "V_VP9" => "vp9",
+        "V_AV1" => "av1",
+        "V_THEORA" => "theora",
+        "V_MJPEG" => "mjpeg",
+        "A_MPEG/L3" => "mp3",
+        "A_MPEG/L2" => "mp2",
+        "A_AC3" => "ac3",
+        "A_EAC3" => "eac3",
+        "A_TRUEHD" => "truehd",
+        "A_FLAC" => "flac",
+        "A_VORBIS" => "vorbis",
+        "A_OPUS" => "opus",
+        "A_ALAC" => "alac",
+        "A_PCM/INT/LIT" => match bit_depth {
+            8 => "pcm_u8",
+            24 => "pcm_s24le",
+            32 => "pcm_s32le",
+            _ => "pcm_s16le",
+        },
+        "A_PCM/FLOAT/IEEE" => "pcm_f32le",
+        "S_TEXT/UTF8" => "subrip",
+        "S_TEXT/ASS" | "S_ASS" => "ass",
+        "S_TEXT/SSA" | "S_SSA" => "ssa",
+        "S_TEXT/WEBVTT" => "webvtt",
+        "S_HDMV/PGS" => "hdmv_pgs_subtitle",
+        "S_VOBSUB" => "dvd_subtitle",
+        _ if codec_id.starts_with("A_AAC") => "aac",
+        _ if codec_id.starts_with("A_DTS") => "dts",
+        _ => return codec_id.to_lowercase(),
+    };
+
+    name.to_string()
+}
@@ -0,0 +1,243 @@
+use media::Item;
+
+mod reader;
+mod tags;
+mod mp4;
+mod matroska;
+mod mpegaudio;
+mod flac;
+mod wav;
+mod image;
+mod heif;
+mod ffprobe;
+
+pub use self::ffprobe::FfprobeProber;
+pub use self::ffprobe::get_ffprobe_failures;
+
+/// Number of Bytes read from the Start of a File in order to
+/// decide which Prober is able to handle it.
+pub const HEADER_SIZE: usize = 64;
+
+/// Longest Duration in Seconds taken from a File (100000 Hours).
+/// Anything longer comes from a broken Header.
+const MAX_DURATION: f64 = 100_000.0 * 60.0 * 60.0;
+
+/// # MediaProber
+///
+/// A MediaProber reads the Container and Stream Information
+/// of a Media File and stores them inside an Item. Every
+/// supported Format has its own Prober reading the File
+/// directly. Anything not understood by them is handed to
+/// the FfprobeProber as a Fallback.
+pub trait MediaProber {
+    /// Checks if this Prober understands the File by looking
+    /// at its first Bytes and its File Extension.
+    ///
+    /// # Arguments
+    ///
+    /// * `header` - First Bytes of the File (up to HEADER_SIZE)
+    /// * `extension` - Lower Case File Extension
+    fn can_probe(&self, header: &[u8], extension: &str) -> bool;
+
+    /// Reads the Files Container, Stream and Tag Information
+    /// into the given Item. Returns true if the File could be
+    /// read and contains at least one usable Stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the File to probe
+    /// * `target` - Item to store the Information in
+    fn probe(&self, path: &str, target: &mut Item) -> bool;
+}
+
+/// Returns all Probers reading Files natively, ordered by
+/// how specific their Format Detection is.
+pub fn get_native_probers() -> Vec<Box<dyn MediaProber>> {
+    vec![
+        Box::new(flac::FlacProber),
+        Box::new(wav::WavProber),
+        Box::new(heif::HeifProber),
+        Box::new(mp4::Mp4Prober),
+        Box::new(matroska::MatroskaProber),
+        Box::new(image::ImageProber),
+        Box::new(mpegaudio::MpegAudioProber),
+    ]
+}
+
+/// Reads the first Bytes of a File used to select the Prober.
+/// Returns an empty Buffer if the File can not be read.
+///
+/// # Arguments
+///
+/// * `path` - Path to the File
+pub fn read_header(path: &str) -> Vec<u8> {
+    match reader::ProbeReader::open(path) {
+        Some(mut value) => value.read_up_to(0, HEADER_SIZE),
+        None => Vec::new(),
+    }
+}
+
+/// Takes a Duration in Seconds and converts it to the
+/// hh:mm:ss.ms format used for UPnP.
+///
+/// # Arguments
+///
+/// * `seconds` - Duration in Seconds
+pub fn format_duration(seconds: f64) -> String {
+    convert_duration(&format!("{:.3}", seconds))
+}
+
+/// Takes the Medias Duration in seconds and converts it to
+/// hh:mm:ss.ms format. That format than is later used
+/// for UPnP. Durations that are negative, not finite or
+/// longer than MAX_DURATION are returned as zero.
+///
+/// # Arguments
+///
+/// * `duration` - Duration as Second String in sssss.ms format
+pub fn convert_duration(duration: &str) -> String {
+    let seconds: f64 = match duration.parse::<f64>() {
+        Ok(value) if value.is_finite() && (0.0..=MAX_DURATION).contains(&value) => value,
+        _ => return "00:00:00.00".to_string(),
+    };
+    let hours: u32 = (seconds / (60.0 * 60.0)) as u32;
+    let minutes: u32 = ((seconds / 60.0) - (hours as f64 * 60.0)) as u32;
+    let seconds_dif: u32 = seconds as u32 - (hours * 60 * 60) - (minutes * 60);
+
+    let mut result: String = String::new();
+
+    if hours < 10 {
+        result.push('0');
+    }
+
+    result.push_str(&hours.to_string());
+    result.push(':');
+
+    if minutes < 10 {
+        result.push('0');
+    }
+
+    result.push_str(&minutes.to_string());
+    result.push(':');
+
+    if seconds_dif < 10 {
+        result.push('0');
+    }
+
+    result.push_str(&seconds_dif.to_string());
+
+    let ms = match duration.find('.') {
+        Some(value) => duration[value..].to_string(),
+        None => ".00".to_string(),
+    };
+
+    result.push_str(&ms[..3.min(ms.len())]);
+
+    result
+}
+
+/// Calculates the average Bitrate in Bit/s for the given Number
+/// of Bytes played back within the given Number of Seconds.
+///
+/// # Arguments
+///
+/// * `bytes` - Size of the Media Data
+/// * `seconds` - Playback Duration
+pub fn average_bitrate(bytes: u64, seconds: f64) -> u64 {
+    if seconds <= 0.0 {
+        return 0;
+    }
+
+    (bytes as f64 * 8.0 / seconds) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::process;
+
+    use configuration::RendererConfiguration;
+    use media::Item;
+    use super::{MediaProber, convert_duration, format_duration};
+    use super::matroska::MatroskaProber;
+    use super::tags::insert_meta_data;
+
+    /// Encodes an EBML Element with an 8 Byte Size
+    fn element(id: &[u8], body: &[u8]) -> Vec<u8> {
+        let mut data = id.to_vec();
+        data.push(0x01);
+        data.extend_from_slice(&(body.len() as u64).to_be_bytes()[1..]);
+        data.extend_from_slice(body);
+        data
+    }
+
+    /// Writes a Matroska File whose Tags follow the Cluster, so they are
+    /// only found through the Seek Head, and probes it
+    fn probe_rated_matroska(name: &str, rating: &str) -> Item {
+        let simple_tag = [element(&[0x45, 0xA3], b"LAW_RATING"), element(&[0x44, 0x87], rating.as_bytes())].concat();
+        let tags = element(&[0x12, 0x54, 0xC3, 0x67], &element(&[0x73, 0x73], &element(&[0x67, 0xC8], &simple_tag)));
+        let video = [element(&[0xB0], &[0x02, 0x80]), element(&[0xBA], &[0x01, 0x68])].concat();
+        let entry = [element(&[0x83], &[1]), element(&[0x86], b"V_MPEG4/ISO/AVC"), element(&[0xE0], &video)].concat();
+        let tracks = element(&[0x16, 0x54, 0xAE, 0x6B], &element(&[0xAE], &entry));
+        let cluster = element(&[0x1F, 0x43, 0xB6, 0x75], &element(&[0xE7], &[0]));
+
+        // The Seek Head has a fixed Size, so the Position of the Tags is known up front
+        let seek_head_size = element(&[0x11, 0x4D, 0x9B, 0x74], &seek_entry(0)).len() as u64;
+        let tags_pos = seek_head_size + tracks.len() as u64 + cluster.len() as u64;
+        let seek_head = element(&[0x11, 0x4D, 0x9B, 0x74], &seek_entry(tags_pos));
+
+        let segment = [seek_head, tracks, cluster, tags].concat();
+        let file = [
+            element(&[0x1A, 0x45, 0xDF, 0xA3], &element(&[0x42, 0x82], b"matroska")),
+            element(&[0x18, 0x53, 0x80, 0x67], &segment),
+        ].concat();
+
+        let path = env::temp_dir().join(format!("slms-test-{}-{}.mkv", process::id(), name));
+        fs::write(&path, file).unwrap();
+
+        let mut item = Item::new();
+        let probed = MatroskaProber.probe(path.to_str().unwrap(), &mut item);
+        fs::remove_file(&path).unwrap();
+
+        assert!(probed);
+        item
+    }
+
+    /// Encodes a Seek Entry pointing to the Tags
+    fn seek_entry(position: u64) -> Vec<u8> {
+        let seek = [element(&[0x53, 0xAB], &[0x12, 0x54, 0xC3, 0x67]), element(&[0x53, 0xAC], &position.to_be_bytes())].concat();
+        element(&[0x4D, 0xBB], &seek)
+    }
+
+    #[test]
+    fn rating_of_matroska_file_hides_item() {
+        let mut renderer = RendererConfiguration::new();
+        renderer.max_rating = Some(12);
+
+        let rated = probe_rated_matroska("rated", "R");
+        let allowed = probe_rated_matroska("allowed", "PG");
+
+        assert_eq!(rated.meta_data.rating, "R");
+        assert!(!renderer.is_item_visible(&rated));
+        assert!(renderer.is_item_visible(&allowed));
+        assert!(!renderer.is_item_visible(&Item::new()));
+    }
+
+    #[test]
+    fn rating_is_taken_from_itunes_content_rating() {
+        let mut item = Item::new();
+        insert_meta_data("iTunEXTC", "mpaa|PG-13|300|", &mut item);
+
+        assert_eq!(item.meta_data.rating, "PG-13");
+    }
+
+    #[test]
+    fn broken_durations_are_zero() {
+        assert_eq!(format_duration(125.5), "00:02:05.50");
+        assert_eq!(format_duration(1e300), "00:00:00.00");
+        assert_eq!(format_duration(f64::INFINITY), "00:00:00.00");
+        assert_eq!(format_duration(f64::NAN), "00:00:00.00");
+        assert_eq!(convert_duration("-5.000"), "00:00:00.00");
+    }
+}
@@ -0,0 +1,420 @@
+use media::{Item, Stream, StreamType};
+use super::{MediaProber, format_duration, average_bitrate};
+use super::tags::{insert_meta_data, set_picture, get_genre_name, FRONT_COVER};
+use super::reader::{ProbeReader, read_be, read_text};
+
+/// Largest "moov" Atom that will be read into Memory
+const MAX_MOOV_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Top Level Atoms a MP4 / QuickTime File may start with
+const TOP_LEVEL_ATOMS: [&[u8; 4]; 7] = [b"ftyp", b"moov", b"mdat", b"free", b"skip", b"wide", b"pnot"];
+
+/// # Mp4Prober
+///
+/// Reads ISO Base Media (MP4, M4A, M4V, 3GP) and QuickTime
+/// Files. The Container and Stream Information is taken from
+/// the Atoms inside the "moov" Atom, Tags from the iTunes
+/// style "ilst" Atom.
+pub struct Mp4Prober;
+
+impl MediaProber for Mp4Prober {
+    fn can_probe(&self, header: &[u8], _extension: &str) -> bool {
+        match header.get(4..8) {
+            Some(value) => TOP_LEVEL_ATOMS.iter().any(|atom| &atom[..] == value),
+            None => false,
+        }
+    }
+
+    fn probe(&self, path: &str, target: &mut Item) -> bool {
+        let mut reader = match ProbeReader::open(path) {
+            Some(value) => value,
+            None => return false,
+        };
+
+        let (moov_pos, moov) = match find_moov(&mut reader) {
+            Some(value) => value,
+            None => return false,
+        };
+
+        let mut seconds: f64 = 0.0;
+        let mut index: u8 = 0;
+
+        for (atom_type, body) in get_atoms(&moov) {
+            match atom_type {
+                b"mvhd" => {
+                    // Version 1 uses 64 Bit Times and Duration
+                    let (scale_pos, duration_size) = if body.first() == Some(&1) { (20, 8) } else { (12, 4) };
+                    let timescale = read_be(body, scale_pos, 4).unwrap_or(0);
+                    let duration = read_be(body, scale_pos + 4, duration_size).unwrap_or(0);
+
+                    if timescale > 0 {
+                        seconds = duration as f64 / timescale as f64;
+                    }
+                }
+                b"trak" => {
+                    if let Some(mut stream) = read_track(body) {
+                        stream.index = index;
+                        index = index.saturating_add(1);
+                        target.media_tracks.push(stream);
+                    }
+                }
+                b"udta" => {
+                    if let Some(meta) = find_atom(body, b"meta") {
+                        read_meta(meta, moov_pos + get_offset(&moov, meta), target);
+                    }
+                }
+                b"meta" => read_meta(body, moov_pos + get_offset(&moov, body), target),
+                _ => (),
+            }
+        }
+
+        if target.media_tracks.is_empty() {
+            return false;
+        }
+
+        target.format_container.name = "mov,mp4,m4a,3gp,3g2,mj2".to_string();
+        target.duration = format_duration(seconds);
+        target.file_size = reader.get_size();
+
+        true
+    }
+}
+
+/// Walks the Top Level Atoms of the File and returns the
+/// Position and the Content of the "moov" Atom.
+///
+/// # Arguments
+///
+/// * `reader` - Reader of the opened File
+fn find_moov(reader: &mut ProbeReader) -> Option<(u64, Vec<u8>)> {
+    let mut pos: u64 = 0;
+
+    while let Some(header) = reader.read_at(pos, 8) {
+        let mut size = read_be(&header, 0, 4)?;
+        let mut header_size: u64 = 8;
+
+        if size == 1 {
+            size = read_be(&reader.read_at(pos + 8, 8)?, 0, 8)?;
+            header_size = 16;
+        } else if size == 0 {
+            size = reader.get_size() - pos;
+        }
+
+        if size < header_size {
+            return None;
+        }
+
+        if &header[4..8] == b"moov" {
+            if size > MAX_MOOV_SIZE {
+                return None;
+            }
+
+            let data = reader.read_at(pos + header_size, (size - header_size) as usize)?;
+            return Some((pos + header_size, data));
+        }
+
+        // Atom Sizes are read from the File and may point anywhere
+        pos = match pos.checked_add(size) {
+            Some(value) if value > pos && value <= reader.get_size() => value,
+            _ => return None,
+        };
+    }
+
+    None
+}
+
+/// Splits a Buffer into the Atoms it contains. Returns a List
+/// of the Atom Types and their Content.
+///
+/// # Arguments
+///
+/// * `data` - Content of the Parent Atom
+pub fn get_atoms(data: &[u8]) -> Vec<(&[u8], &[u8])> {
+    let mut atoms: Vec<(&[u8], &[u8])> = Vec::new();
+    let mut pos: usize = 0;
+
+    while pos + 8 <= data.len() {
+        let mut size = read_be(data, pos, 4).unwrap_or(0) as usize;
+        let mut header_size: usize = 8;
+
+        if size == 1 {
+            size = match read_be(data, pos + 8, 8) {
+                Some(value) => value as usize,
+                None => break,
+            };
+            header_size = 16;
+        } else if size == 0 {
+            size = data.len() - pos;
+        }
+
+        if size < header_size || size > data.len() - pos {
+            break;
+        }
+
+        atoms.push((&data[pos + 4..pos + 8], &data[pos + header_size..pos + size]));
+        pos += size;
+    }
+
+    atoms
+}
+
+/// Returns the Content of the first Child Atom of the given Type
+///
+/// # Arguments
+///
+/// * `data` - Content of the Parent Atom
+/// * `atom_type` - Type of the Atom to find
+pub fn find_atom<'a>(data: &'a [u8], atom_type: &[u8; 4]) -> Option<&'a [u8]> {
+    get_atoms(data)
+        .into_iter()
+        .find(|atom| atom.0 == &atom_type[..])
+        .map(|atom| atom.1)
+}
+
+/// Follows a Path of nested Atoms and returns the Content of the last one
+///
+/// # Arguments
+///
+/// * `data` - Content of the Atom to start at
+/// * `path` - Types of the nested Atoms
+fn find_path<'a>(data: &'a [u8], path: &[&[u8; 4]]) -> Option<&'a [u8]> {
+    let mut current = data;
+
+    for atom_type in path {
+        current = find_atom(current, atom_type)?;
+    }
+
+    Some(current)
+}
+
+/// Reads a "trak" Atom into a Stream. Returns None for Tracks
+/// that are no Audio, Video or Subtitle Tracks.
+///
+/// # Arguments
+///
+/// * `trak` - Content of the "trak" Atom
+fn read_track(trak: &[u8]) -> Option<Stream> {
+    let mdia = find_atom(trak, b"mdia")?;
+    let handler = find_atom(mdia, b"hdlr")?;
+
+    let mut stream: Stream = Stream::new();
+
+    stream.stream_type = match handler.get(8..12)? {
+        b"vide" => StreamType::VIDEO,
+        b"soun" => StreamType::AUDIO,
+        b"sbtl" | b"subt" | b"text" => StreamType::SUBTITLE,
+        _ => return None,
+    };
+
+    // Track Header: Enabled Flag and Dimensions (16.16 fixed point)
+    if let Some(tkhd) = find_atom(trak, b"tkhd") {
+        stream.is_default = read_be(tkhd, 1, 3).unwrap_or(0) & 0x01 != 0;
+
+        if tkhd.len() >= 8 {
+            stream.frame_width = (read_be(tkhd, tkhd.len() - 8, 4).unwrap_or(0) >> 16) as u16;
+            stream.frame_height = (read_be(tkhd, tkhd.len() - 4, 4).unwrap_or(0) >> 16) as u16;
+        }
+    }
+
+    // Media Header: Duration and packed ISO 639-2 Language
+    let mut seconds: f64 = 0.0;
+
+    if let Some(mdhd) = find_atom(mdia, b"mdhd") {
+        let (scale_pos, duration_size) = if mdhd.first() == Some(&1) { (20, 8) } else { (12, 4) };
+        let timescale = read_be(mdhd, scale_pos, 4).unwrap_or(0);
+        let duration = read_be(mdhd, scale_pos + 4, duration_size).unwrap_or(0);
+        let language = read_be(mdhd, scale_pos + 4 + duration_size, 2).unwrap_or(0);
+
+        if timescale > 0 {
+            seconds = duration as f64 / timescale as f64;
+        }
+
+        // Values below 0x400 are QuickTime Macintosh Language Codes
+        if language >= 0x400 && language != 0x7FFF {
+            let code: String = [10, 5, 0]
+                .iter()
+                .map(|shift| char::from((((language >> shift) & 0x1F) + 0x60) as u8))
+                .collect();
+
+            if code != "und" {
+                stream.language = code;
+            }
+        }
+    }
+
+    let stbl = find_path(mdia, &[b"minf", b"stbl"])?;
+
+    // First Sample Description
+    if let Some(stsd) = find_atom(stbl, b"stsd") {
+        let entry = stsd.get(8..).unwrap_or(&[]);
+        let format = entry.get(4..8).unwrap_or(&[]);
+        stream.codec_name = get_codec_name(format);
+
+        match stream.stream_type {
+            StreamType::VIDEO => {
+                let width = read_be(entry, 32, 2).unwrap_or(0) as u16;
+                let height = read_be(entry, 34, 2).unwrap_or(0) as u16;
+
+                if width > 0 && height > 0 {
+                    stream.frame_width = width;
+                    stream.frame_height = height;
+                }
+            }
+            StreamType::AUDIO => {
+                stream.audio_channels = read_be(entry, 24, 2).unwrap_or(0) as u8;
+                stream.sample_rate = (read_be(entry, 32, 4).unwrap_or(0) >> 16) as u32;
+
+                if stream.codec_name.starts_with("pcm") || stream.codec_name == "alac" {
+                    stream.bit_depth = read_be(entry, 26, 2).unwrap_or(0) as u8;
+                }
+            }
+            _ => (),
+        }
+    }
+
+    // Sample Sizes give the average Bitrate of the Track
+    if let Some(stsz) = find_atom(stbl, b"stsz") {
+        let sample_size = read_be(stsz, 4, 4).unwrap_or(0);
+        let count = read_be(stsz, 8, 4).unwrap_or(0);
+
+        let bytes = if sample_size > 0 {
+            sample_size.saturating_mul(count)
+        } else {
+            stsz.get(12..)
+                .unwrap_or(&[])
+                .chunks(4)
+                .take(count as usize)
+                .map(|entry| read_be(entry, 0, 4).unwrap_or(0))
+                .sum()
+        };
+
+        stream.bitrate = average_bitrate(bytes, seconds);
+    }
+
+    Some(stream)
+}
+
+/// Returns the Position of a Slice inside the Buffer it was taken from
+///
+/// # Arguments
+///
+/// * `data` - Whole Buffer
+/// * `part` - Slice of the Buffer
+fn get_offset(data: &[u8], part: &[u8]) -> u64 {
+    (part.as_ptr() as usize - data.as_ptr() as usize) as u64
+}
+
+/// Reads the iTunes style Tags from a "meta" Atom
+///
+/// # Arguments
+///
+/// * `meta` - Content of the "meta" Atom
+/// * `position` - Position of the Content inside the File
+/// * `target` - Item to store the Meta Data in
+fn read_meta(meta: &[u8], position: u64, target: &mut Item) {
+    // ISO "meta" Atoms are Full Atoms with Version and Flags, QuickTime ones are not
+    let children = if meta.get(4..8) == Some(&b"hdlr"[..]) { meta } else { meta.get(4..).unwrap_or(&[]) };
+
+    let ilst = match find_atom(children, b"ilst") {
+        Some(value) => value,
+        None => return,
+    };
+
+    for (atom_type, body) in get_atoms(ilst) {
+        // Well known Type (1 = UTF-8, 0 = binary), Locale and the Value
+        let data = match find_atom(body, b"data") {
+            Some(value) if value.len() >= 8 => value,
+            _ => continue,
+        };
+
+        let value = &data[8..];
+
+        match atom_type {
+            b"\xA9nam" => insert_meta_data("title", &read_text(value), target),
+            b"\xA9ART" => insert_meta_data("artist", &read_text(value), target),
+            b"aART" => insert_meta_data("album_artist", &read_text(value), target),
+            b"\xA9alb" => insert_meta_data("album", &read_text(value), target),
+            b"\xA9gen" => insert_meta_data("genre", &read_text(value), target),
+            b"\xA9day" => insert_meta_data("date", &read_text(value), target),
+            b"\xA9wrt" => insert_meta_data("composer", &read_text(value), target),
+            b"\xA9cmt" | b"desc" => insert_meta_data("comment", &read_text(value), target),
+            b"cprt" | b"\xA9cpy" => insert_meta_data("copyright", &read_text(value), target),
+            b"\xA9rat" => insert_meta_data("rating", &read_text(value), target),
+            b"----" => {
+                // Freeform Tags are named by a "name" Atom after Version and Flags
+                let name = find_atom(body, b"name").and_then(|name| name.get(4..)).map(read_text);
+
+                if name.as_deref() == Some("iTunEXTC") {
+                    insert_meta_data("rating", &read_text(value), target);
+                }
+            }
+            b"trkn" | b"disk" => {
+                let (name, total_name) = if atom_type == b"trkn" { ("track", "tracktotal") } else { ("disc", "disctotal") };
+
+                if let Some(number) = read_be(value, 2, 2) {
+                    if number > 0 {
+                        insert_meta_data(name, &number.to_string(), target);
+                    }
+                }
+
+                if let Some(total) = read_be(value, 4, 2) {
+                    if total > 0 {
+                        insert_meta_data(total_name, &total.to_string(), target);
+                    }
+                }
+            }
+            b"gnre" => {
+                // ID3v1 Genre Index starting at 1
+                if let Some(name) = read_be(value, 0, 2).and_then(|index| get_genre_name((index as usize).wrapping_sub(1))) {
+                    insert_meta_data("genre", name, target);
+                }
+            }
+            b"covr" => {
+                // Data Type 13 is JPEG, 14 is PNG
+                let mime = match read_be(data, 0, 4) {
+                    Some(13) => "image/jpeg",
+                    Some(14) => "image/png",
+                    _ => continue,
+                };
+
+                let offset = position + get_offset(meta, value);
+                set_picture(target, FRONT_COVER, mime, offset, value.len() as u64);
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Returns the ffmpeg Codec Name for a Sample Description Format
+///
+/// # Arguments
+///
+/// * `format` - Four Character Code of the Sample Description
+fn get_codec_name(format: &[u8]) -> String {
+    let name = match format {
+        b"avc1" | b"avc3" => "h264",
+        b"hvc1" | b"hev1" => "hevc",
+        b"mp4v" => "mpeg4",
+        b"av01" => "av1",
+        b"vp09" => "vp9",
+        b"vp08" => "vp8",
+        b"jpeg" | b"mjpa" => "mjpeg",
+        b"apcn" | b"apch" | b"apcs" | b"apco" | b"ap4h" => "prores",
+        b"mp4a" => "aac",
+        b"ac-3" => "ac3",
+        b"ec-3" => "eac3",
+        b"alac" => "alac",
+        b"Opus" => "opus",
+        b"fLaC" => "flac",
+        b".mp3" => "mp3",
+        b"sowt" => "pcm_s16le",
+        b"twos" => "pcm_s16be",
+        b"lpcm" | b"ipcm" => "pcm_s16le",
+        b"tx3g" => "mov_text",
+        b"wvtt" => "webvtt",
+        b"c608" => "eia_608",
+        _ => return String::from_utf8_lossy(format).trim().to_lowercase(),
+    };
+
+    name.to_string()
+}
@@ -0,0 +1,242 @@
+use media::{Item, Stream, StreamType};
+use super::{MediaProber, format_duration, average_bitrate};
+use super::tags::{self, id3, ape};
+use super::reader::{ProbeReader, read_be};
+
+/// Bitrates in kBit/s by Bitrate Index for MPEG-1 Layer I, II, III
+/// and MPEG-2 / 2.5 Layer I and Layer II / III.
+const BITRATES: [[u32; 15]; 5] = [
+    [0, 32, 64, 96, 128, 160, 192, 224, 256, 288, 320, 352, 384, 416, 448],
+    [0, 32, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 384],
+    [0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320],
+    [0, 32, 48, 56, 64, 80, 96, 112, 128, 144, 160, 176, 192, 224, 256],
+    [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160],
+];
+
+/// Sample Rates by Sample Rate Index for MPEG-1, MPEG-2 and MPEG-2.5
+const SAMPLE_RATES: [[u32; 3]; 3] = [[44100, 48000, 32000], [22050, 24000, 16000], [11025, 12000, 8000]];
+
+/// How far into the File a Frame Sync is searched for
+const SYNC_SEARCH_SIZE: usize = 64 * 1024;
+
+/// # FrameHeader
+///
+/// The decoded Values of a single MPEG Audio Frame Header
+struct FrameHeader {
+    version: usize, // 0 = MPEG-1, 1 = MPEG-2, 2 = MPEG-2.5
+    layer: u8, // 1, 2 or 3
+    bitrate: u32, // Bit/s
+    sample_rate: u32,
+    channels: u8,
+    samples_per_frame: u32,
+}
+
+impl FrameHeader {
+    /// Decodes the 4 Bytes of a Frame Header. Returns None if
+    /// the Bytes are no valid Header.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Buffer holding the Header
+    /// * `pos` - Position of the Header inside the Buffer
+    fn parse(data: &[u8], pos: usize) -> Option<FrameHeader> {
+        let header = read_be(data, pos, 4)? as u32;
+
+        if header >> 21 != 0x7FF {
+            return None;
+        }
+
+        let version = match (header >> 19) & 0x03 {
+            0 => 2,
+            2 => 1,
+            3 => 0,
+            _ => return None,
+        };
+
+        let layer: u8 = match (header >> 17) & 0x03 {
+            1 => 3,
+            2 => 2,
+            3 => 1,
+            _ => return None,
+        };
+
+        let bitrate_index = ((header >> 12) & 0x0F) as usize;
+        let sample_rate_index = ((header >> 10) & 0x03) as usize;
+
+        if bitrate_index == 0 || bitrate_index == 15 || sample_rate_index == 3 {
+            return None;
+        }
+
+        let table = match (version, layer) {
+            (0, _) => layer as usize - 1,
+            (_, 1) => 3,
+            _ => 4,
+        };
+
+        let samples_per_frame = match (version, layer) {
+            (_, 1) => 384,
+            (0, _) | (_, 2) => 1152,
+            _ => 576,
+        };
+
+        Some(FrameHeader {
+            version,
+            layer,
+            bitrate: BITRATES[table][bitrate_index] * 1000,
+            sample_rate: SAMPLE_RATES[version][sample_rate_index],
+            channels: if (header >> 6) & 0x03 == 3 { 1 } else { 2 },
+            samples_per_frame,
+        })
+    }
+
+    /// Returns the Offset of the Xing / Info Header from
+    /// the Start of the Frame
+    fn get_xing_offset(&self) -> usize {
+        match (self.version, self.channels) {
+            (0, 1) => 4 + 17,
+            (0, _) => 4 + 32,
+            (_, 1) => 4 + 9,
+            _ => 4 + 17,
+        }
+    }
+}
+
+/// # MpegAudioProber
+///
+/// Reads MPEG Audio Files (MP3, MP2). Skips a leading ID3v2 Tag,
+/// decodes the first Frame Header and uses a Xing / Info or VBRI
+/// Header to get the Duration of variable Bitrate Files.
+pub struct MpegAudioProber;
+
+impl MediaProber for MpegAudioProber {
+    fn can_probe(&self, header: &[u8], extension: &str) -> bool {
+        if id3::get_tag_size(header).is_some() {
+            return extension != "flac" && extension != "wav";
+        }
+
+        match extension {
+            "mp3" | "mp2" | "mpa" => FrameHeader::parse(header, 0).is_some(),
+            _ => false,
+        }
+    }
+
+    fn probe(&self, path: &str, target: &mut Item) -> bool {
+        let mut reader = match ProbeReader::open(path) {
+            Some(value) => value,
+            None => return false,
+        };
+
+        let file_size = reader.get_size();
+
+        // Read the ID3v2 Tags in front of the Audio Data. Multiple
+        // Tags are allowed, but rarely used.
+        let mut audio_start: u64 = 0;
+
+        loop {
+            let tag_header = reader.read_up_to(audio_start, id3::HEADER_SIZE);
+
+            let size = match id3::get_tag_size(&tag_header) {
+                Some(value) => value,
+                None => break,
+            };
+
+            let tag = reader.read_up_to(audio_start, size as usize);
+            id3::read_tags(&tag, audio_start, target);
+            audio_start += size;
+        }
+
+        // Find the first Frame
+        let data = reader.read_up_to(audio_start, SYNC_SEARCH_SIZE);
+        let mut pos: usize = 0;
+        let mut frame: Option<FrameHeader> = None;
+
+        while pos + 4 <= data.len() {
+            if data[pos] == 0xFF {
+                if let Some(value) = FrameHeader::parse(&data, pos) {
+                    frame = Some(value);
+                    break;
+                }
+            }
+
+            pos += 1;
+        }
+
+        let frame = match frame {
+            Some(value) => value,
+            None => return false,
+        };
+
+        audio_start += pos as u64;
+
+        // ID3v1 and APEv2 Tags at the End of the File are only
+        // used if there was no ID3v2 Tag
+        let mut audio_end = file_size;
+        let v1_tag = reader.read_up_to(file_size.saturating_sub(id3::V1_SIZE), id3::V1_SIZE as usize);
+        let has_v1 = v1_tag.starts_with(b"TAG");
+
+        if has_v1 {
+            audio_end -= id3::V1_SIZE;
+        }
+
+        if !tags::has_tags(target) && !ape::read_tags(&mut reader, audio_end, target) && has_v1 {
+            id3::read_v1_tags(&v1_tag, target);
+        }
+
+        let audio_size = audio_end.saturating_sub(audio_start);
+
+        // Variable Bitrate Headers hold the Number of Frames
+        let xing = frame.get_xing_offset();
+        let mut frames: Option<u64> = None;
+
+        if let Some(tag) = data.get(pos + xing..pos + xing + 4) {
+            if tag == b"Xing" || tag == b"Info" {
+                let flags = read_be(&data, pos + xing + 4, 4).unwrap_or(0);
+
+                if flags & 0x01 != 0 {
+                    frames = read_be(&data, pos + xing + 8, 4);
+                }
+            }
+        }
+
+        if let Some(tag) = data.get(pos + 36..pos + 40) {
+            if tag == b"VBRI" {
+                frames = read_be(&data, pos + 36 + 14, 4);
+            }
+        }
+
+        let (seconds, bitrate) = match frames {
+            Some(value) if value > 0 => {
+                let seconds = value as f64 * f64::from(frame.samples_per_frame) / f64::from(frame.sample_rate);
+                (seconds, average_bitrate(audio_size, seconds))
+            }
+            _ => (audio_size as f64 * 8.0 / f64::from(frame.bitrate), u64::from(frame.bitrate)),
+        };
+
+        let mut stream: Stream = Stream::new();
+        stream.stream_type = StreamType::AUDIO;
+        stream.codec_name = format!("mp{}", frame.layer);
+        stream.bitrate = bitrate;
+        stream.sample_rate = frame.sample_rate;
+        stream.audio_channels = frame.channels;
+        stream.is_default = true;
+
+        target.media_tracks.push(stream);
+        target.format_container.name = stream_format(frame.layer).to_string();
+        target.duration = format_duration(seconds);
+        target.file_size = file_size;
+
+        true
+    }
+}
+
+/// Returns the ffmpeg Format Name for the given Layer
+///
+/// # Arguments
+///
+/// * `layer` - MPEG Audio Layer
+fn stream_format(layer: u8) -> &'static str {
+    match layer {
+        3 => "mp3",
+        _ => "mpeg",
+    }
+}
@@ -0,0 +1,130 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+
+/// # ProbeReader
+///
+/// Small Helper around an opened Media File that allows the
+/// Probers to read Blocks of Bytes at any Position without
+/// loading the whole File into Memory.
+pub struct ProbeReader {
+    file: File,
+    size: u64,
+}
+
+impl ProbeReader {
+    /// Opens the File at the given Path for reading
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the Media File
+    pub fn open(path: &str) -> Option<ProbeReader> {
+        let file = match File::open(path) {
+            Ok(value) => value,
+            Err(_) => return None,
+        };
+
+        let size = match file.metadata() {
+            Ok(value) => value.len(),
+            Err(_) => return None,
+        };
+
+        Some(ProbeReader { file, size })
+    }
+
+    /// Returns the Size of the File in Bytes
+    pub fn get_size(&self) -> u64 {
+        self.size
+    }
+
+    /// Reads exactly `length` Bytes starting at `offset`.
+    /// Returns None if the File is shorter than requested.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - Position to start reading at
+    /// * `length` - Number of Bytes to read
+    pub fn read_at(&mut self, offset: u64, length: usize) -> Option<Vec<u8>> {
+        let data = self.read_up_to(offset, length);
+
+        if data.len() == length {
+            Some(data)
+        } else {
+            None
+        }
+    }
+
+    /// Reads up to `length` Bytes starting at `offset`. The
+    /// returned Buffer is shorter if the End of the File was
+    /// reached and empty if nothing could be read.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - Position to start reading at
+    /// * `length` - Maximum Number of Bytes to read
+    pub fn read_up_to(&mut self, offset: u64, length: usize) -> Vec<u8> {
+        let mut data: Vec<u8> = Vec::new();
+
+        if offset >= self.size || self.file.seek(SeekFrom::Start(offset)).is_err() {
+            return data;
+        }
+
+        let available = (self.size - offset).min(length as u64);
+
+        if (&mut self.file).take(available).read_to_end(&mut data).is_err() {
+            data.clear();
+        }
+
+        data
+    }
+}
+
+/// Reads a big endian unsigned Integer of `length` Bytes (1 to 8)
+/// at the given Position of a Buffer.
+///
+/// # Arguments
+///
+/// * `data` - Buffer to read from
+/// * `pos` - Position of the first Byte
+/// * `length` - Number of Bytes the Integer is made of
+pub fn read_be(data: &[u8], pos: usize, length: usize) -> Option<u64> {
+    let bytes = data.get(pos..pos.checked_add(length)?)?;
+    let mut value: u64 = 0;
+
+    for byte in bytes {
+        value = (value << 8) | u64::from(*byte);
+    }
+
+    Some(value)
+}
+
+/// Reads a little endian unsigned Integer of `length` Bytes (1 to 8)
+/// at the given Position of a Buffer.
+///
+/// # Arguments
+///
+/// * `data` - Buffer to read from
+/// * `pos` - Position of the first Byte
+/// * `length` - Number of Bytes the Integer is made of
+pub fn read_le(data: &[u8], pos: usize, length: usize) -> Option<u64> {
+    let bytes = data.get(pos..pos.checked_add(length)?)?;
+    let mut value: u64 = 0;
+
+    for byte in bytes.iter().rev() {
+        value = (value << 8) | u64::from(*byte);
+    }
+
+    Some(value)
+}
+
+/// Converts a Buffer of Text into a String. Invalid UTF-8 is
+/// replaced and trailing Zero Bytes and Whitespaces are removed.
+///
+/// # Arguments
+///
+/// * `data` - Buffer holding the Text
+pub fn read_text(data: &[u8]) -> String {
+    String::from_utf8_lossy(data)
+        .trim_end_matches('\0')
+        .trim()
+        .to_string()
+}
@@ -0,0 +1,137 @@
+use media::Item;
+use super::{insert_meta_data, set_picture, get_picture_mime, FRONT_COVER};
+use super::super::reader::{ProbeReader, read_le};
+
+/// Size of the APEv2 Header and Footer
+const FOOTER_SIZE: u64 = 32;
+
+/// Largest Tag that will be read into Memory
+const MAX_TAG_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Item Type of Binary Items (Bits 1 - 2 of the Item Flags)
+const ITEM_BINARY: u64 = 1;
+
+/// Reads an APEv2 Tag ending at the given Position of the File.
+/// Usually this is the End of the File or the Start of an ID3v1
+/// Tag. Returns false if there is no APEv2 Tag.
+///
+/// # Arguments
+///
+/// * `reader` - Reader of the opened File
+/// * `end` - Position the Tags Footer ends at
+/// * `target` - Item to store the Meta Data in
+pub fn read_tags(reader: &mut ProbeReader, end: u64, target: &mut Item) -> bool {
+    if end < FOOTER_SIZE {
+        return false;
+    }
+
+    let footer = match reader.read_at(end - FOOTER_SIZE, FOOTER_SIZE as usize) {
+        Some(value) => value,
+        None => return false,
+    };
+
+    if &footer[..8] != b"APETAGEX" {
+        return false;
+    }
+
+    // Size of all Items and the Footer
+    let size = read_le(&footer, 12, 4).unwrap_or(0);
+    let count = read_le(&footer, 16, 4).unwrap_or(0);
+
+    if size < FOOTER_SIZE || size > end || size > MAX_TAG_SIZE {
+        return false;
+    }
+
+    let start = end - size;
+
+    let data = match reader.read_at(start, (size - FOOTER_SIZE) as usize) {
+        Some(value) => value,
+        None => return false,
+    };
+
+    let mut pos: usize = 0;
+
+    for _ in 0..count {
+        let value_size = match read_le(&data, pos, 4) {
+            Some(value) => value as usize,
+            None => break,
+        };
+
+        let flags = read_le(&data, pos + 4, 4).unwrap_or(0);
+        pos += 8;
+
+        let key_end = match data[pos.min(data.len())..].iter().position(|byte| *byte == 0) {
+            Some(value) => pos + value,
+            None => break,
+        };
+
+        let key = String::from_utf8_lossy(&data[pos..key_end]).to_lowercase();
+        let value_start = key_end + 1;
+
+        let value = match data.get(value_start..value_start + value_size) {
+            Some(value) => value,
+            None => break,
+        };
+
+        pos = value_start + value_size;
+
+        if (flags >> 1) & 0x03 == ITEM_BINARY {
+            if key.starts_with("cover art") {
+                read_picture(&key, value, start + value_start as u64, target);
+            }
+
+            continue;
+        }
+
+        let name = match key.as_str() {
+            "title" => "title",
+            "artist" => "artist",
+            "album" => "album",
+            "album artist" | "albumartist" => "album_artist",
+            "year" => "date",
+            "track" => "track",
+            "disc" => "disc",
+            "genre" => "genre",
+            "composer" => "composer",
+            "comment" => "comment",
+            "copyright" => "copyright",
+            "publisher" | "label" => "publisher",
+            "language" => "language",
+            _ => continue,
+        };
+
+        // Multiple Values are separated by Zero
+        for part in value.split(|byte| *byte == 0) {
+            insert_meta_data(name, &String::from_utf8_lossy(part), target);
+        }
+    }
+
+    true
+}
+
+/// Reads a Cover Art Item (File Name, Zero, Picture Data) and
+/// uses it as Thumbnail.
+///
+/// # Arguments
+///
+/// * `key` - Lower Case Key of the Item
+/// * `value` - Content of the Item
+/// * `position` - Position of the Content inside the File
+/// * `target` - Item to store the Thumbnail in
+fn read_picture(key: &str, value: &[u8], position: u64, target: &mut Item) {
+    let name_end = match value.iter().position(|byte| *byte == 0) {
+        Some(value) => value + 1,
+        None => return,
+    };
+
+    let picture = &value[name_end..];
+    let picture_type = if key == "cover art (front)" { FRONT_COVER } else { 0 };
+
+    set_picture(
+        target,
+        picture_type,
+        get_picture_mime(picture),
+        position + name_end as u64,
+        picture.len() as u64,
+    );
+}
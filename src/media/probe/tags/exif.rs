@@ -0,0 +1,367 @@
+use media::Item;
+use super::xmp;
+use super::super::reader::{read_be, read_le, read_text};
+
+/// IFD0 Tags
+const TAG_IMAGE_WIDTH: u64 = 0x0100;
+const TAG_IMAGE_HEIGHT: u64 = 0x0101;
+const TAG_BITS_PER_SAMPLE: u64 = 0x0102;
+const TAG_MAKE: u64 = 0x010F;
+const TAG_MODEL: u64 = 0x0110;
+const TAG_ORIENTATION: u64 = 0x0112;
+const TAG_DATE_TIME: u64 = 0x0132;
+const TAG_XMP: u64 = 0x02BC;
+const TAG_EXIF_IFD: u64 = 0x8769;
+const TAG_GPS_IFD: u64 = 0x8825;
+
+/// Exif IFD Tags
+const TAG_DATE_TIME_ORIGINAL: u64 = 0x9003;
+
+/// GPS IFD Tags
+const TAG_GPS_LATITUDE_REF: u64 = 0x0001;
+const TAG_GPS_LATITUDE: u64 = 0x0002;
+const TAG_GPS_LONGITUDE_REF: u64 = 0x0003;
+const TAG_GPS_LONGITUDE: u64 = 0x0004;
+
+/// Size of a single IFD Entry
+const ENTRY_SIZE: usize = 12;
+
+/// # Tiff
+///
+/// Gives Access to the Image File Directories of a TIFF
+/// Structure as used by TIFF Files and EXIF Blocks. All
+/// Offsets are relative to the TIFF Header.
+pub struct Tiff<'a> {
+    data: &'a [u8],
+    little_endian: bool,
+}
+
+/// A single Entry of an Image File Directory
+pub struct Entry {
+    pub tag: u64,
+    pub kind: u64,
+    pub count: u64,
+    pub position: usize,
+}
+
+impl<'a> Tiff<'a> {
+    /// Checks the TIFF Header ("II*\0" or "MM\0*") and returns
+    /// the Structure if it is valid.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Buffer starting with the TIFF Header
+    pub fn new(data: &'a [u8]) -> Option<Tiff<'a>> {
+        let little_endian = match data.get(..4)? {
+            b"II*\0" => true,
+            b"MM\0*" => false,
+            _ => return None,
+        };
+
+        Some(Tiff {
+            data,
+            little_endian,
+        })
+    }
+
+    /// Reads an unsigned Integer in the Byte Order of the Structure
+    ///
+    /// # Arguments
+    ///
+    /// * `pos` - Position to read from
+    /// * `len` - Number of Bytes
+    fn read(&self, pos: usize, len: usize) -> Option<u64> {
+        if self.little_endian {
+            read_le(self.data, pos, len)
+        } else {
+            read_be(self.data, pos, len)
+        }
+    }
+
+    /// Returns the Entries of the first Image File Directory
+    pub fn get_first_entries(&self) -> Vec<Entry> {
+        match self.read(4, 4) {
+            Some(offset) => self.get_entries(offset as usize),
+            None => Vec::new(),
+        }
+    }
+
+    /// Returns the Entries of the Image File Directory a Pointer
+    /// Entry (e.g. Exif IFD or GPS IFD) points to.
+    ///
+    /// # Arguments
+    ///
+    /// * `entries` - Entries holding the Pointer
+    /// * `tag` - Tag of the Pointer
+    fn get_sub_entries(&self, entries: &[Entry], tag: u64) -> Vec<Entry> {
+        match find_entry(entries, tag).and_then(|entry| self.get_number(entry)) {
+            Some(offset) => self.get_entries(offset as usize),
+            None => Vec::new(),
+        }
+    }
+
+    /// Reads the Entries of the Image File Directory at the given Offset
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - Offset of the Directory
+    fn get_entries(&self, offset: usize) -> Vec<Entry> {
+        let mut entries: Vec<Entry> = Vec::new();
+        let count = self.read(offset, 2).unwrap_or(0) as usize;
+
+        for index in 0..count {
+            let pos = offset + 2 + index * ENTRY_SIZE;
+
+            let (tag, kind, count) = match (self.read(pos, 2), self.read(pos + 2, 2), self.read(pos + 4, 4)) {
+                (Some(tag), Some(kind), Some(count)) => (tag, kind, count),
+                _ => break,
+            };
+
+            // Values of up to four Bytes are stored inside the Entry itself
+            let size = get_type_size(kind).saturating_mul(count);
+            let position = if size <= 4 {
+                pos + 8
+            } else {
+                match self.read(pos + 8, 4) {
+                    Some(value) => value as usize,
+                    None => break,
+                }
+            };
+
+            entries.push(Entry {
+                tag,
+                kind,
+                count,
+                position,
+            });
+        }
+
+        entries
+    }
+
+    /// Returns the raw Bytes of an Entries Value
+    ///
+    /// # Arguments
+    ///
+    /// * `entry` - Entry to read
+    pub fn get_bytes(&self, entry: &Entry) -> Option<&'a [u8]> {
+        let size = get_type_size(entry.kind).saturating_mul(entry.count) as usize;
+        self.data.get(entry.position..entry.position.checked_add(size)?)
+    }
+
+    /// Returns the Value of an ASCII Entry
+    ///
+    /// # Arguments
+    ///
+    /// * `entry` - Entry to read
+    fn get_text(&self, entry: &Entry) -> Option<String> {
+        if entry.kind != 2 {
+            return None;
+        }
+
+        Some(read_text(self.get_bytes(entry)?))
+    }
+
+    /// Returns the first Value of a BYTE, SHORT or LONG Entry
+    ///
+    /// # Arguments
+    ///
+    /// * `entry` - Entry to read
+    pub fn get_number(&self, entry: &Entry) -> Option<u64> {
+        match entry.kind {
+            1 | 7 => self.read(entry.position, 1),
+            3 => self.read(entry.position, 2),
+            4 | 13 => self.read(entry.position, 4),
+            _ => None,
+        }
+    }
+
+    /// Returns a Value of an unsigned RATIONAL Entry
+    ///
+    /// # Arguments
+    ///
+    /// * `entry` - Entry to read
+    /// * `index` - Index of the Value
+    fn get_rational(&self, entry: &Entry, index: usize) -> Option<f64> {
+        if entry.kind != 5 || index as u64 >= entry.count {
+            return None;
+        }
+
+        let numerator = self.read(entry.position + index * 8, 4)?;
+        let denominator = self.read(entry.position + index * 8 + 4, 4)?;
+
+        if denominator == 0 {
+            return None;
+        }
+
+        Some(numerator as f64 / denominator as f64)
+    }
+
+    /// Returns a GPS Coordinate (Degrees, Minutes, Seconds) in
+    /// decimal Degrees. Southern and western Coordinates are negative.
+    ///
+    /// # Arguments
+    ///
+    /// * `entries` - Entries of the GPS IFD
+    /// * `tag` - Tag of the Coordinate
+    /// * `reference_tag` - Tag of the Coordinates Reference (N/S or E/W)
+    fn get_coordinate(&self, entries: &[Entry], tag: u64, reference_tag: u64) -> Option<f64> {
+        let entry = find_entry(entries, tag)?;
+
+        let mut value = self.get_rational(entry, 0)?;
+        value += self.get_rational(entry, 1).unwrap_or(0.0) / 60.0;
+        value += self.get_rational(entry, 2).unwrap_or(0.0) / 3600.0;
+
+        let reference = find_entry(entries, reference_tag)
+            .and_then(|entry| self.get_text(entry))
+            .unwrap_or_default();
+
+        if reference == "S" || reference == "W" {
+            value = -value;
+        }
+
+        Some(value)
+    }
+}
+
+/// Reads the EXIF Information of a TIFF Structure: Date taken,
+/// Orientation, Camera and GPS Position. XMP embedded into the
+/// Structure is read as well. Returns false if the Data is no
+/// valid TIFF Structure.
+///
+/// # Arguments
+///
+/// * `data` - Buffer starting with the TIFF Header
+/// * `target` - Item to store the Meta Data in
+pub fn read_exif(data: &[u8], target: &mut Item) -> bool {
+    let tiff = match Tiff::new(data) {
+        Some(value) => value,
+        None => return false,
+    };
+
+    let entries = tiff.get_first_entries();
+    let exif_entries = tiff.get_sub_entries(&entries, TAG_EXIF_IFD);
+    let gps_entries = tiff.get_sub_entries(&entries, TAG_GPS_IFD);
+
+    // The original Date is preferred over the last Modification
+    let date = find_entry(&exif_entries, TAG_DATE_TIME_ORIGINAL)
+        .or_else(|| find_entry(&entries, TAG_DATE_TIME))
+        .and_then(|entry| tiff.get_text(entry))
+        .and_then(|value| convert_date(&value));
+
+    if let Some(value) = date {
+        if target.meta_data.date.is_empty() {
+            target.meta_data.date = value;
+        }
+    }
+
+    if let Some(value) = find_entry(&entries, TAG_ORIENTATION).and_then(|entry| tiff.get_number(entry)) {
+        if (1..=8).contains(&value) {
+            target.meta_data.orientation = value as u8;
+        }
+    }
+
+    if let Some(value) = find_entry(&entries, TAG_MAKE).and_then(|entry| tiff.get_text(entry)) {
+        target.meta_data.camera_make = value;
+    }
+
+    if let Some(value) = find_entry(&entries, TAG_MODEL).and_then(|entry| tiff.get_text(entry)) {
+        target.meta_data.camera_model = value;
+    }
+
+    let latitude = tiff.get_coordinate(&gps_entries, TAG_GPS_LATITUDE, TAG_GPS_LATITUDE_REF);
+    let longitude = tiff.get_coordinate(&gps_entries, TAG_GPS_LONGITUDE, TAG_GPS_LONGITUDE_REF);
+
+    if let (Some(latitude), Some(longitude)) = (latitude, longitude) {
+        target.meta_data.latitude = format!("{:.6}", latitude);
+        target.meta_data.longitude = format!("{:.6}", longitude);
+    }
+
+    // TIFF Files may carry XMP inside the first IFD
+    if let Some(value) = find_entry(&entries, TAG_XMP).and_then(|entry| tiff.get_bytes(entry)) {
+        xmp::read_xmp(value, target);
+    }
+
+    true
+}
+
+/// Reads the Width, Height and Bits per Sample of the first
+/// Image of a TIFF Structure.
+///
+/// # Arguments
+///
+/// * `data` - Buffer starting with the TIFF Header
+pub fn read_dimensions(data: &[u8]) -> Option<(u64, u64, u8)> {
+    let tiff = Tiff::new(data)?;
+    let entries = tiff.get_first_entries();
+
+    let width = tiff.get_number(find_entry(&entries, TAG_IMAGE_WIDTH)?)?;
+    let height = tiff.get_number(find_entry(&entries, TAG_IMAGE_HEIGHT)?)?;
+    let bit_depth = find_entry(&entries, TAG_BITS_PER_SAMPLE)
+        .and_then(|entry| tiff.get_number(entry))
+        .unwrap_or(8);
+
+    Some((width, height, bit_depth as u8))
+}
+
+/// Converts an EXIF Date ("YYYY:MM:DD HH:MM:SS") into the ISO 8601
+/// Format used for dc:date. Returns None for empty or zeroed Dates.
+///
+/// # Arguments
+///
+/// * `value` - EXIF Date
+fn convert_date(value: &str) -> Option<String> {
+    let bytes = value.as_bytes();
+
+    // Checking every Byte keeps the Slices below on Char Boundaries
+    if !matches_pattern(bytes, b"dddd:dd:dd") || value.starts_with("0000") {
+        return None;
+    }
+
+    let mut date = format!("{}-{}-{}", &value[..4], &value[5..7], &value[8..10]);
+
+    if matches_pattern(bytes, b"dddd:dd:dd dd:dd:dd") {
+        date.push('T');
+        date.push_str(&value[11..19]);
+    }
+
+    Some(date)
+}
+
+/// Checks if the Value starts with the given Pattern, in which
+/// a "d" stands for any ASCII Digit
+///
+/// # Arguments
+///
+/// * `value` - Value to check
+/// * `pattern` - Pattern the Value has to start with
+fn matches_pattern(value: &[u8], pattern: &[u8]) -> bool {
+    value.len() >= pattern.len() &&
+        pattern.iter().zip(value).all(|(expected, byte)| {
+            if *expected == b'd' { byte.is_ascii_digit() } else { byte == expected }
+        })
+}
+
+/// Returns the Size in Bytes of a single Value of the given Type
+///
+/// # Arguments
+///
+/// * `kind` - TIFF Field Type
+fn get_type_size(kind: u64) -> u64 {
+    match kind {
+        3 | 8 => 2,
+        4 | 9 | 11 | 13 => 4,
+        5 | 10 | 12 => 8,
+        _ => 1,
+    }
+}
+
+/// Finds the Entry with the given Tag
+///
+/// # Arguments
+///
+/// * `entries` - Entries of an Image File Directory
+/// * `tag` - Tag to look for
+fn find_entry(entries: &[Entry], tag: u64) -> Option<&Entry> {
+    entries.iter().find(|entry| entry.tag == tag)
+}
@@ -0,0 +1,475 @@
+use media::Item;
+use super::{insert_meta_data, set_picture, get_genre_name};
+use super::super::reader::{read_be, read_text};
+
+/// Size of the ID3v2 Header (and Footer)
+pub const HEADER_SIZE: usize = 10;
+
+/// Size of an ID3v1 Tag at the End of a File
+pub const V1_SIZE: u64 = 128;
+
+/// Checks if the Buffer starts with an ID3v2 Tag and returns
+/// its complete Size including Header and Footer.
+///
+/// # Arguments
+///
+/// * `data` - First Bytes of the File or Chunk
+pub fn get_tag_size(data: &[u8]) -> Option<u64> {
+    if data.len() < HEADER_SIZE || &data[..3] != b"ID3" {
+        return None;
+    }
+
+    let size = read_synchsafe(data, 6)?;
+    let footer = if data[5] & 0x10 != 0 { HEADER_SIZE as u64 } else { 0 };
+
+    Some(HEADER_SIZE as u64 + size + footer)
+}
+
+/// Reads the Frames of an ID3v2.2, 2.3 or 2.4 Tag and stores
+/// them as Meta Data of the Item. Attached Pictures are used as
+/// Thumbnail as long as their Data is stored unmodified.
+///
+/// # Arguments
+///
+/// * `data` - The complete Tag starting with its Header
+/// * `base` - Position of the Tag inside the File
+/// * `target` - Item to store the Meta Data in
+pub fn read_tags(data: &[u8], base: u64, target: &mut Item) {
+    if get_tag_size(data).is_none() {
+        return;
+    }
+
+    let version = data[3];
+    let flags = data[5];
+    let end = data.len().min(HEADER_SIZE + read_synchsafe(data, 6).unwrap_or(0) as usize);
+
+    // Undo the Unsynchronisation of the whole Tag (ID3v2.2 / 2.3).
+    // Positions inside the File are unknown afterwards.
+    let mut body: Vec<u8> = data[HEADER_SIZE..end].to_vec();
+    let mut keeps_positions = true;
+
+    if flags & 0x80 != 0 && version < 4 {
+        body = remove_unsynchronisation(&body);
+        keeps_positions = false;
+    }
+
+    let mut pos: usize = 0;
+
+    // Skip the Extended Header
+    if flags & 0x40 != 0 && version >= 3 {
+        let size = match read_be(&body, 0, 4) {
+            Some(value) => value as usize,
+            None => return,
+        };
+
+        pos = if version == 3 { size + 4 } else { size };
+    }
+
+    let (id_size, header_size) = if version == 2 { (3, 6) } else { (4, 10) };
+
+    while pos + header_size <= body.len() {
+        // Reached the Padding
+        if body[pos] == 0 {
+            break;
+        }
+
+        let id = String::from_utf8_lossy(&body[pos..pos + id_size]).to_string();
+
+        let size = if version == 2 {
+            read_be(&body, pos + 3, 3)
+        } else if version == 4 {
+            read_synchsafe(&body, pos + 4)
+        } else {
+            read_be(&body, pos + 4, 4)
+        };
+
+        let size = match size {
+            Some(value) => value as usize,
+            None => break,
+        };
+
+        let mut start = pos + header_size;
+
+        if start + size > body.len() {
+            break;
+        }
+
+        let format_flags = if version >= 3 { body[pos + 9] } else { 0 };
+        let mut frame_start = start;
+        let mut unsynchronised = false;
+
+        // Compressed and encrypted Frames can not be read
+        let skip = match version {
+            3 => format_flags & 0xC0 != 0,
+            4 => format_flags & 0x0C != 0,
+            _ => false,
+        };
+
+        if version == 4 {
+            // Grouping Identity and Data Length Indicator come first
+            if format_flags & 0x40 != 0 {
+                frame_start += 1;
+            }
+
+            if format_flags & 0x01 != 0 {
+                frame_start += 4;
+            }
+
+            unsynchronised = format_flags & 0x02 != 0;
+        }
+
+        if !skip && frame_start <= start + size {
+            let mut frame: Vec<u8> = body[frame_start..start + size].to_vec();
+
+            if unsynchronised {
+                frame = remove_unsynchronisation(&frame);
+            }
+
+            let position = if keeps_positions && !unsynchronised {
+                Some(base + (HEADER_SIZE + frame_start) as u64)
+            } else {
+                None
+            };
+
+            read_frame(&id, &frame, position, target);
+        }
+
+        start += size;
+        pos = start;
+    }
+}
+
+/// Reads an ID3v1 Tag (128 Bytes at the End of a File).
+/// Returns false if the Buffer is no ID3v1 Tag.
+///
+/// # Arguments
+///
+/// * `data` - The 128 Bytes of the Tag
+/// * `target` - Item to store the Meta Data in
+pub fn read_v1_tags(data: &[u8], target: &mut Item) -> bool {
+    if data.len() < V1_SIZE as usize || &data[..3] != b"TAG" {
+        return false;
+    }
+
+    insert_meta_data("title", &read_latin1(&data[3..33]), target);
+    insert_meta_data("artist", &read_latin1(&data[33..63]), target);
+    insert_meta_data("album", &read_latin1(&data[63..93]), target);
+    insert_meta_data("date", &read_text(&data[93..97]), target);
+
+    // ID3v1.1 stores the Track inside the last Byte of the Comment
+    if data[125] == 0 && data[126] != 0 {
+        insert_meta_data("comment", &read_latin1(&data[97..125]), target);
+        insert_meta_data("track", &data[126].to_string(), target);
+    } else {
+        insert_meta_data("comment", &read_latin1(&data[97..127]), target);
+    }
+
+    if let Some(genre) = get_genre_name(data[127] as usize) {
+        insert_meta_data("genre", genre, target);
+    }
+
+    true
+}
+
+/// Maps a single Frame onto the Meta Data of the Item
+///
+/// # Arguments
+///
+/// * `id` - Frame ID (3 Characters for ID3v2.2, 4 otherwise)
+/// * `frame` - Content of the Frame
+/// * `position` - Position of the Frame Content inside the File if known
+/// * `target` - Item to store the Meta Data in
+fn read_frame(id: &str, frame: &[u8], position: Option<u64>, target: &mut Item) {
+    let name = match id {
+        "TIT2" | "TT2" => "title",
+        "TPE1" | "TP1" => "artist",
+        "TPE2" | "TP2" => "album_artist",
+        "TALB" | "TAL" => "album",
+        "TCON" | "TCO" => "genre",
+        "TRCK" | "TRK" => "track",
+        "TPOS" | "TPA" => "disc",
+        "TYER" | "TYE" | "TDRC" => "date",
+        "TCOM" | "TCM" => "composer",
+        "TCOP" | "TCR" => "copyright",
+        "TPUB" | "TPB" => "publisher",
+        "TLAN" | "TLA" => "language",
+        "COMM" | "COM" => "comment",
+        "TXXX" | "TXX" => "user",
+        "APIC" => {
+            read_picture(frame, false, position, target);
+            return;
+        }
+        "PIC" => {
+            read_picture(frame, true, position, target);
+            return;
+        }
+        _ => return,
+    };
+
+    match name {
+        "comment" => {
+            // Encoding, Language (3) and a Description before the Text
+            if frame.len() < 4 {
+                return;
+            }
+
+            let mut content: Vec<u8> = vec![frame[0]];
+            content.extend_from_slice(&frame[4..]);
+
+            if let Some(value) = decode_strings(&content).get(1) {
+                insert_meta_data(name, value, target);
+            }
+        }
+        "user" => {
+            // Description followed by the Value
+            let strings = decode_strings(frame);
+
+            let (description, value) = match (strings.first(), strings.get(1)) {
+                (Some(description), Some(value)) => (description.to_uppercase(), value),
+                _ => return,
+            };
+
+            let name = match description.as_str() {
+                "ALBUM ARTIST" | "ALBUMARTIST" => "album_artist",
+                "TOTALTRACKS" | "TRACKTOTAL" => "tracktotal",
+                "TOTALDISCS" | "DISCTOTAL" => "disctotal",
+                _ => return,
+            };
+
+            insert_meta_data(name, value, target);
+        }
+        "genre" => {
+            for value in decode_strings(frame) {
+                insert_meta_data(name, &resolve_genre(&value), target);
+            }
+        }
+        _ => {
+            // ID3v2.4 separates multiple Values by Zero
+            for value in decode_strings(frame) {
+                insert_meta_data(name, &value, target);
+
+                if name != "artist" {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Reads an Attached Picture Frame and uses it as Thumbnail.
+///
+/// # Arguments
+///
+/// * `frame` - Content of the Frame
+/// * `is_v22` - The Frame is a ID3v2.2 "PIC" Frame with a 3 Character Image Format
+/// * `position` - Position of the Frame Content inside the File if known
+/// * `target` - Item to store the Thumbnail in
+fn read_picture(frame: &[u8], is_v22: bool, position: Option<u64>, target: &mut Item) {
+    let position = match position {
+        Some(value) => value,
+        None => return,
+    };
+
+    if frame.len() < 5 {
+        return;
+    }
+
+    let encoding = frame[0];
+
+    // Mime Type (Latin-1, Zero terminated) or Image Format (3 Characters)
+    let (mime, mut pos) = if is_v22 {
+        (read_latin1(&frame[1..4]), 4)
+    } else {
+        match frame[1..].iter().position(|byte| *byte == 0) {
+            Some(value) => (read_latin1(&frame[1..1 + value]), 2 + value),
+            None => return,
+        }
+    };
+
+    let picture_type = match frame.get(pos) {
+        Some(value) => u32::from(*value),
+        None => return,
+    };
+
+    pos += 1;
+
+    // Skip the Description
+    pos += match find_terminator(&frame[pos.min(frame.len())..], encoding) {
+        Some(value) => value,
+        None => return,
+    };
+
+    if pos >= frame.len() {
+        return;
+    }
+
+    set_picture(
+        target,
+        picture_type,
+        &mime,
+        position + pos as u64,
+        (frame.len() - pos) as u64,
+    );
+}
+
+/// Resolves ID3v1 Genre References like "(17)", "17" or "(17)Rock"
+/// used inside ID3v2 Genre Frames.
+///
+/// # Arguments
+///
+/// * `value` - Content of the Genre Frame
+fn resolve_genre(value: &str) -> String {
+    let mut reference = value;
+    let mut text = "";
+
+    if value.starts_with('(') {
+        if let Some(position) = value.find(')') {
+            reference = &value[1..position];
+            text = &value[position + 1..];
+        }
+    }
+
+    // A refined Text wins over the Reference
+    if !text.trim().is_empty() {
+        return text.trim().to_string();
+    }
+
+    match reference.parse::<usize>() {
+        Ok(index) => get_genre_name(index).unwrap_or("").to_string(),
+        Err(_) => {
+            match reference {
+                "RX" => "Remix".to_string(),
+                "CR" => "Cover".to_string(),
+                _ => value.to_string(),
+            }
+        }
+    }
+}
+
+/// Decodes the Content of a Text Frame into its Strings. The
+/// first Byte selects the Encoding, the Strings are separated
+/// by the Encodings Terminator.
+///
+/// # Arguments
+///
+/// * `frame` - Content of the Frame starting with the Encoding Byte
+pub fn decode_strings(frame: &[u8]) -> Vec<String> {
+    let mut result: Vec<String> = Vec::new();
+
+    if frame.is_empty() {
+        return result;
+    }
+
+    let encoding = frame[0];
+    let data = &frame[1..];
+
+    if encoding == 1 || encoding == 2 {
+        // UTF-16 with BOM or UTF-16BE, terminated by two Zero Bytes
+        let mut big_endian = encoding == 2;
+        let mut units: Vec<u16> = Vec::new();
+        let mut pos: usize = 0;
+
+        while pos + 1 < data.len() {
+            let unit = if big_endian {
+                (u16::from(data[pos]) << 8) | u16::from(data[pos + 1])
+            } else {
+                (u16::from(data[pos + 1]) << 8) | u16::from(data[pos])
+            };
+            pos += 2;
+
+            match unit {
+                0xFEFF if units.is_empty() => (),
+                0xFFFE if units.is_empty() => big_endian = !big_endian,
+                0 => {
+                    result.push(String::from_utf16_lossy(&units));
+                    units.clear();
+                }
+                _ => units.push(unit),
+            }
+        }
+
+        if !units.is_empty() {
+            result.push(String::from_utf16_lossy(&units));
+        }
+    } else {
+        for part in data.split(|byte| *byte == 0) {
+            if encoding == 3 {
+                result.push(String::from_utf8_lossy(part).to_string());
+            } else {
+                result.push(read_latin1(part));
+            }
+        }
+    }
+
+    result.into_iter().map(|value| value.trim().to_string()).collect()
+}
+
+/// Returns the Position after the first String Terminator of the
+/// given Encoding (one Zero Byte or two for UTF-16).
+///
+/// # Arguments
+///
+/// * `data` - Buffer starting with the String
+/// * `encoding` - ID3v2 Text Encoding
+fn find_terminator(data: &[u8], encoding: u8) -> Option<usize> {
+    if encoding == 1 || encoding == 2 {
+        data.chunks(2)
+            .position(|unit| unit == [0, 0])
+            .map(|position| position * 2 + 2)
+    } else {
+        data.iter().position(|byte| *byte == 0).map(|position| position + 1)
+    }
+}
+
+/// Converts ISO-8859-1 Text into a String. The Characters map
+/// directly onto the first Unicode Code Points.
+///
+/// # Arguments
+///
+/// * `data` - Buffer holding the Text
+fn read_latin1(data: &[u8]) -> String {
+    let text: String = data.iter()
+        .take_while(|byte| **byte != 0)
+        .map(|byte| char::from(*byte))
+        .collect();
+
+    text.trim().to_string()
+}
+
+/// Reads a 28 Bit synchsafe Integer stored in 4 Bytes
+///
+/// # Arguments
+///
+/// * `data` - Buffer to read from
+/// * `pos` - Position of the first Byte
+fn read_synchsafe(data: &[u8], pos: usize) -> Option<u64> {
+    let bytes = data.get(pos..pos + 4)?;
+    let mut value: u64 = 0;
+
+    for byte in bytes {
+        value = (value << 7) | u64::from(*byte & 0x7F);
+    }
+
+    Some(value)
+}
+
+/// Removes the Zero Bytes that were inserted after every 0xFF
+/// in order to avoid false MPEG Frame Syncs.
+///
+/// # Arguments
+///
+/// * `data` - Unsynchronised Data
+fn remove_unsynchronisation(data: &[u8]) -> Vec<u8> {
+    let mut result: Vec<u8> = Vec::with_capacity(data.len());
+    let mut last: u8 = 0;
+
+    for byte in data {
+        if !(last == 0xFF && *byte == 0) {
+            result.push(*byte);
+        }
+
+        last = *byte;
+    }
+
+    result
+}
@@ -0,0 +1,201 @@
+use media::Item;
+
+pub mod id3;
+pub mod vorbis;
+pub mod ape;
+pub mod exif;
+pub mod xmp;
+
+/// Picture Type of a Front Cover as used by ID3v2, FLAC and APEv2
+pub const FRONT_COVER: u32 = 3;
+
+/// Genre Names of ID3v1 including the Winamp Extensions.
+/// ID3v2 and MP4 Files may still refer to them by their Index.
+const GENRES: [&str; 192] = [
+    "Blues", "Classic Rock", "Country", "Dance", "Disco", "Funk", "Grunge", "Hip-Hop", "Jazz",
+    "Metal", "New Age", "Oldies", "Other", "Pop", "R&B", "Rap", "Reggae", "Rock", "Techno",
+    "Industrial", "Alternative", "Ska", "Death Metal", "Pranks", "Soundtrack", "Euro-Techno",
+    "Ambient", "Trip-Hop", "Vocal", "Jazz+Funk", "Fusion", "Trance", "Classical", "Instrumental",
+    "Acid", "House", "Game", "Sound Clip", "Gospel", "Noise", "AlternRock", "Bass", "Soul", "Punk",
+    "Space", "Meditative", "Instrumental Pop", "Instrumental Rock", "Ethnic", "Gothic", "Darkwave",
+    "Techno-Industrial", "Electronic", "Pop-Folk", "Eurodance", "Dream", "Southern Rock", "Comedy",
+    "Cult", "Gangsta", "Top 40", "Christian Rap", "Pop/Funk", "Jungle", "Native American",
+    "Cabaret", "New Wave", "Psychadelic", "Rave", "Showtunes", "Trailer", "Lo-Fi", "Tribal",
+    "Acid Punk", "Acid Jazz", "Polka", "Retro", "Musical", "Rock & Roll", "Hard Rock", "Folk",
+    "Folk-Rock", "National Folk", "Swing", "Fast Fusion", "Bebob", "Latin", "Revival", "Celtic",
+    "Bluegrass", "Avantgarde", "Gothic Rock", "Progressive Rock", "Psychedelic Rock",
+    "Symphonic Rock", "Slow Rock", "Big Band", "Chorus", "Easy Listening", "Acoustic", "Humour",
+    "Speech", "Chanson", "Opera", "Chamber Music", "Sonata", "Symphony", "Booty Bass", "Primus",
+    "Porn Groove", "Satire", "Slow Jam", "Club", "Tango", "Samba", "Folklore", "Ballad",
+    "Power Ballad", "Rhythmic Soul", "Freestyle", "Duet", "Punk Rock", "Drum Solo", "A capella",
+    "Euro-House", "Dance Hall", "Goa", "Drum & Bass", "Club-House", "Hardcore", "Terror", "Indie",
+    "BritPop", "Negerpunk", "Polsk Punk", "Beat", "Christian Gangsta Rap", "Heavy Metal",
+    "Black Metal", "Crossover", "Contemporary Christian", "Christian Rock", "Merengue", "Salsa",
+    "Thrash Metal", "Anime", "JPop", "Synthpop", "Abstract", "Art Rock", "Baroque", "Bhangra",
+    "Big Beat", "Breakbeat", "Chillout", "Downtempo", "Dub", "EBM", "Eclectic", "Electro",
+    "Electroclash", "Emo", "Experimental", "Garage", "Global", "IDM", "Illbient", "Industro-Goth",
+    "Jam Band", "Krautrock", "Leftfield", "Lounge", "Math Rock", "New Romantic", "Nu-Breakz",
+    "Post-Punk", "Post-Rock", "Psytrance", "Shoegaze", "Space Rock", "Trop Rock", "World Music",
+    "Neoclassical", "Audiobook", "Audio Theatre", "Neue Deutsche Welle", "Podcast", "Indie Rock",
+    "G-Funk", "Dubstep", "Garage Rock", "Psybient",
+];
+
+/// Takes the Meta Tags read from a File and inserts them into the
+/// MetaData structure every Item holds. Tag Names follow the
+/// ffmpeg naming as all Tag Readers map their Formats Tags onto
+/// them. Track and Disc Numbers may contain the Total ("3/12"),
+/// Genres may hold multiple Values separated by ";". Ratings are
+/// also taken from the Matroska and iTunes Tags ffprobe passes on.
+///
+/// # Arguments
+///
+/// * `name` - Name of the Meta Tag
+/// * `value` - Value of the Meta Tag
+/// * `target` - Item to store the Value in
+pub fn insert_meta_data(name: &str, value: &str, target: &mut Item) {
+    let value = value.trim();
+
+    if value.is_empty() {
+        return;
+    }
+
+    match name {
+        "album" => target.meta_data.album = value.to_string(),
+        "album_artist" => target.meta_data.album_artist = value.to_string(),
+        "artist" => push_unique(&mut target.meta_data.artists, value),
+        "composer" => target.meta_data.composer = value.to_string(),
+        "copyright" => push_unique(&mut target.meta_data.copyrights, value),
+        "date" => target.meta_data.date = value.to_string(),
+        "comment" => target.meta_data.description = value.to_string(),
+        "genre" => {
+            for genre in value.split(';') {
+                push_unique(&mut target.meta_data.genres, genre.trim());
+            }
+        }
+        "language" => push_unique(&mut target.meta_data.languages, value),
+        "publisher" => target.meta_data.publisher = value.to_string(),
+        "track" => {
+            let (number, total) = split_number(value);
+            target.meta_data.track_number = number;
+
+            if !total.is_empty() {
+                target.meta_data.track_total = total;
+            }
+        }
+        "tracktotal" => target.meta_data.track_total = value.to_string(),
+        "disc" => {
+            let (number, total) = split_number(value);
+            target.meta_data.disc_number = number;
+
+            if !total.is_empty() {
+                target.meta_data.disc_total = total;
+            }
+        }
+        "disctotal" => target.meta_data.disc_total = value.to_string(),
+        "performer" => target.meta_data.actor = value.to_string(),
+        "title" => target.meta_data.title = value.to_string(),
+        "rating" | "LAW_RATING" | "iTunEXTC" => target.meta_data.rating = get_rating(value).to_string(),
+        _ => (),
+    }
+}
+
+/// Checks if any of the Tags that identify a Song were found
+///
+/// # Arguments
+///
+/// * `target` - Item to check
+pub fn has_tags(target: &Item) -> bool {
+    !target.meta_data.title.is_empty() || !target.meta_data.artists.is_empty() ||
+        !target.meta_data.album.is_empty()
+}
+
+/// Uses a Picture embedded into the Items File as its Thumbnail.
+/// Only JPEG and PNG Pictures are used. A Front Cover replaces
+/// any other Picture found before.
+///
+/// # Arguments
+///
+/// * `target` - Item the Picture belongs to
+/// * `picture_type` - Picture Type (3 = Front Cover)
+/// * `mime` - Mime Type of the Picture
+/// * `offset` - Position of the Picture inside the File
+/// * `size` - Size of the Picture in Bytes
+pub fn set_picture(target: &mut Item, picture_type: u32, mime: &str, offset: u64, size: u64) {
+    let mime = match mime.to_lowercase().as_str() {
+        "image/jpeg" | "image/jpg" | "jpg" => "image/jpeg",
+        "image/png" | "png" => "image/png",
+        _ => return,
+    };
+
+    if size == 0 || (!target.thumbnail.file_path.is_empty() && picture_type != FRONT_COVER) {
+        return;
+    }
+
+    target.thumbnail.file_path = target.file_path.clone();
+    target.thumbnail.mime_type = mime.to_string();
+    target.thumbnail.offset = offset;
+    target.thumbnail.file_size = size;
+}
+
+/// Detects the Mime Type of a Picture from its first Bytes
+///
+/// # Arguments
+///
+/// * `data` - First Bytes of the Picture
+pub fn get_picture_mime(data: &[u8]) -> &'static str {
+    if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        "image/jpeg"
+    } else if data.starts_with(&[0x89, b'P', b'N', b'G']) {
+        "image/png"
+    } else {
+        ""
+    }
+}
+
+/// Returns the Name of a Genre referenced by its ID3v1 Index
+///
+/// # Arguments
+///
+/// * `index` - Index of the Genre
+pub fn get_genre_name(index: usize) -> Option<&'static str> {
+    GENRES.get(index).cloned()
+}
+
+/// Splits a Track or Disc Number like "3/12" into Number and Total
+///
+/// # Arguments
+///
+/// * `value` - The Number with an optional Total
+fn split_number(value: &str) -> (String, String) {
+    match value.find('/') {
+        Some(position) => (value[..position].trim().to_string(), value[position + 1..].trim().to_string()),
+        None => (value.to_string(), String::new()),
+    }
+}
+
+/// Takes the Rating out of an iTunes Content Rating like
+/// "mpaa|PG-13|300|". Other Ratings are returned as they are.
+///
+/// # Arguments
+///
+/// * `value` - The Rating Tag
+fn get_rating(value: &str) -> &str {
+    let mut parts = value.split('|');
+
+    match (parts.next(), parts.next()) {
+        (Some(_), Some(rating)) => rating.trim(),
+        _ => value,
+    }
+}
+
+/// Adds a Value to a List of Values if not already inside
+///
+/// # Arguments
+///
+/// * `list` - List of Values
+/// * `value` - Value to add
+fn push_unique(list: &mut Vec<String>, value: &str) {
+    if !value.is_empty() && !list.iter().any(|entry| entry == value) {
+        list.push(value.to_string());
+    }
+}
@@ -0,0 +1,71 @@
+use media::Item;
+use super::insert_meta_data;
+use super::super::reader::read_le;
+
+/// Reads a Vorbis Comment Block (Vendor String followed by a
+/// List of NAME=value Strings) as used by FLAC, Ogg and Opus and
+/// stores the known Fields as Meta Data of the Item. Fields may
+/// appear more than once, e.g. for multiple Artists or Genres.
+///
+/// # Arguments
+///
+/// * `data` - Content of the Comment Block
+/// * `target` - Item to store the Meta Data in
+pub fn read_comments(data: &[u8], target: &mut Item) {
+    let vendor_size = match read_le(data, 0, 4) {
+        Some(value) => value as usize,
+        None => return,
+    };
+
+    let mut pos: usize = 4 + vendor_size;
+
+    let count = match read_le(data, pos, 4) {
+        Some(value) => value,
+        None => return,
+    };
+
+    pos += 4;
+
+    for _ in 0..count {
+        let size = match read_le(data, pos, 4) {
+            Some(value) => value as usize,
+            None => return,
+        };
+
+        pos += 4;
+
+        let comment = match data.get(pos..pos + size) {
+            Some(value) => String::from_utf8_lossy(value).to_string(),
+            None => return,
+        };
+
+        pos += size;
+
+        let separator = match comment.find('=') {
+            Some(value) => value,
+            None => continue,
+        };
+
+        let name = match comment[..separator].to_uppercase().as_str() {
+            "TITLE" => "title",
+            "ARTIST" => "artist",
+            "ALBUM" => "album",
+            "ALBUMARTIST" | "ALBUM ARTIST" | "ALBUM_ARTIST" => "album_artist",
+            "GENRE" => "genre",
+            "DATE" | "YEAR" => "date",
+            "TRACKNUMBER" => "track",
+            "TRACKTOTAL" | "TOTALTRACKS" => "tracktotal",
+            "DISCNUMBER" => "disc",
+            "DISCTOTAL" | "TOTALDISCS" => "disctotal",
+            "COMPOSER" => "composer",
+            "COPYRIGHT" => "copyright",
+            "ORGANIZATION" | "PUBLISHER" | "LABEL" => "publisher",
+            "DESCRIPTION" | "COMMENT" => "comment",
+            "LANGUAGE" => "language",
+            "PERFORMER" => "performer",
+            _ => continue,
+        };
+
+        insert_meta_data(name, &comment[separator + 1..], target);
+    }
+}
@@ -0,0 +1,103 @@
+use media::Item;
+
+/// Properties holding the Date a Photo was taken, best first
+const DATE_PROPERTIES: [&str; 3] = ["exif:DateTimeOriginal", "photoshop:DateCreated", "xmp:CreateDate"];
+
+/// Reads an XMP Packet and fills the Photo Information that was
+/// not already found inside the EXIF Block. Properties may be
+/// written as Attributes or as Elements, both are supported.
+///
+/// # Arguments
+///
+/// * `data` - XMP Packet
+/// * `target` - Item to store the Meta Data in
+pub fn read_xmp(data: &[u8], target: &mut Item) {
+    let xmp = String::from_utf8_lossy(data);
+
+    if target.meta_data.date.is_empty() {
+        if let Some(value) = DATE_PROPERTIES.iter().filter_map(|name| find_property(&xmp, name)).next() {
+            target.meta_data.date = value;
+        }
+    }
+
+    if target.meta_data.orientation == 0 {
+        let orientation = find_property(&xmp, "tiff:Orientation")
+            .and_then(|value| value.parse::<u8>().ok())
+            .unwrap_or(0);
+
+        if (1..=8).contains(&orientation) {
+            target.meta_data.orientation = orientation;
+        }
+    }
+
+    if target.meta_data.camera_make.is_empty() {
+        target.meta_data.camera_make = find_property(&xmp, "tiff:Make").unwrap_or_default();
+    }
+
+    if target.meta_data.camera_model.is_empty() {
+        target.meta_data.camera_model = find_property(&xmp, "tiff:Model").unwrap_or_default();
+    }
+
+    if target.meta_data.latitude.is_empty() {
+        let latitude = find_property(&xmp, "exif:GPSLatitude").and_then(|value| convert_coordinate(&value));
+        let longitude = find_property(&xmp, "exif:GPSLongitude").and_then(|value| convert_coordinate(&value));
+
+        if let (Some(latitude), Some(longitude)) = (latitude, longitude) {
+            target.meta_data.latitude = format!("{:.6}", latitude);
+            target.meta_data.longitude = format!("{:.6}", longitude);
+        }
+    }
+}
+
+/// Returns the Value of a simple Property given either as
+/// Attribute (name="value") or as Element (<name>value</name>).
+///
+/// # Arguments
+///
+/// * `xmp` - XMP Packet
+/// * `name` - Qualified Name of the Property
+fn find_property(xmp: &str, name: &str) -> Option<String> {
+    let attribute = format!("{}=\"", name);
+    let element = format!("<{}>", name);
+
+    let value = if let Some(start) = xmp.find(&attribute) {
+        let rest = &xmp[start + attribute.len()..];
+        &rest[..rest.find('"')?]
+    } else if let Some(start) = xmp.find(&element) {
+        let rest = &xmp[start + element.len()..];
+        &rest[..rest.find('<')?]
+    } else {
+        return None;
+    };
+
+    let value = value.trim();
+
+    if value.is_empty() {
+        None
+    } else {
+        Some(value.to_string())
+    }
+}
+
+/// Converts a XMP GPS Coordinate ("DDD,MM,SSk" or "DDD,MM.mmk"
+/// with k being N, S, E or W) into decimal Degrees.
+///
+/// # Arguments
+///
+/// * `value` - XMP Coordinate
+fn convert_coordinate(value: &str) -> Option<f64> {
+    let reference = value.chars().last()?;
+    let mut result: f64 = 0.0;
+    let mut divisor: f64 = 1.0;
+
+    for part in value[..value.len() - reference.len_utf8()].split(',') {
+        result += part.trim().parse::<f64>().ok()? / divisor;
+        divisor *= 60.0;
+    }
+
+    match reference {
+        'N' | 'E' => Some(result),
+        'S' | 'W' => Some(-result),
+        _ => None,
+    }
+}
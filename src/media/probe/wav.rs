@@ -0,0 +1,169 @@
+use media::{Item, Stream, StreamType};
+use super::{MediaProber, format_duration};
+use super::tags::{insert_meta_data, id3};
+use super::reader::{ProbeReader, read_le, read_text};
+
+/// Largest LIST / ID3 Chunk that will be read
+const MAX_TAG_SIZE: usize = 1024 * 1024;
+
+/// # WavProber
+///
+/// Reads RIFF WAVE Files. The Stream Information is taken from
+/// the "fmt " Chunk, the Duration from the Size of the "data"
+/// Chunk and Tags from a LIST INFO or "id3 " Chunk.
+pub struct WavProber;
+
+impl MediaProber for WavProber {
+    fn can_probe(&self, header: &[u8], _extension: &str) -> bool {
+        header.len() >= 12 && &header[..4] == b"RIFF" && &header[8..12] == b"WAVE"
+    }
+
+    fn probe(&self, path: &str, target: &mut Item) -> bool {
+        let mut reader = match ProbeReader::open(path) {
+            Some(value) => value,
+            None => return false,
+        };
+
+        let mut stream: Option<Stream> = None;
+        let mut byte_rate: u64 = 0;
+        let mut data_size: u64 = 0;
+        let mut pos: u64 = 12;
+
+        // Walk the Chunks of the RIFF File
+        while let Some(header) = reader.read_at(pos, 8) {
+            let id = &header[..4];
+            let size = read_le(&header, 4, 4).unwrap_or(0);
+            pos += 8;
+
+            match id {
+                b"fmt " => {
+                    let data = match reader.read_at(pos, size.min(64) as usize) {
+                        Some(value) => value,
+                        None => return false,
+                    };
+
+                    let mut format = match read_le(&data, 0, 2) {
+                        Some(value) => value,
+                        None => return false,
+                    };
+
+                    // WAVE_FORMAT_EXTENSIBLE holds the real Format in its Sub Format GUID
+                    if format == 0xFFFE {
+                        format = read_le(&data, 24, 2).unwrap_or(0);
+                    }
+
+                    let bit_depth = read_le(&data, 14, 2).unwrap_or(0) as u8;
+                    byte_rate = read_le(&data, 8, 4).unwrap_or(0);
+
+                    let mut value: Stream = Stream::new();
+                    value.stream_type = StreamType::AUDIO;
+                    value.codec_name = get_codec_name(format, bit_depth);
+                    value.audio_channels = read_le(&data, 2, 2).unwrap_or(0) as u8;
+                    value.sample_rate = read_le(&data, 4, 4).unwrap_or(0) as u32;
+                    value.bit_depth = bit_depth;
+                    value.bitrate = byte_rate * 8;
+                    value.is_default = true;
+                    stream = Some(value);
+                }
+                b"data" => data_size = size,
+                b"LIST" if size as usize <= MAX_TAG_SIZE => {
+                    if let Some(data) = reader.read_at(pos, size as usize) {
+                        read_info_list(&data, target);
+                    }
+                }
+                b"id3 " | b"ID3 " if size as usize <= MAX_TAG_SIZE => {
+                    if let Some(data) = reader.read_at(pos, size as usize) {
+                        id3::read_tags(&data, pos, target);
+                    }
+                }
+                _ => (),
+            }
+
+            // Chunks are padded to an even Size
+            pos += size + (size & 1);
+        }
+
+        let stream = match stream {
+            Some(value) => value,
+            None => return false,
+        };
+
+        // The data Chunk might be cut short or claim to be endless
+        data_size = data_size.min(reader.get_size());
+
+        if byte_rate > 0 {
+            target.duration = format_duration(data_size as f64 / byte_rate as f64);
+        }
+
+        target.media_tracks.push(stream);
+        target.format_container.name = "wav".to_string();
+        target.file_size = reader.get_size();
+
+        true
+    }
+}
+
+/// Reads the Sub Chunks of a LIST INFO Chunk
+///
+/// # Arguments
+///
+/// * `data` - Content of the LIST Chunk
+/// * `target` - Item to store the Meta Data in
+fn read_info_list(data: &[u8], target: &mut Item) {
+    if data.len() < 4 || &data[..4] != b"INFO" {
+        return;
+    }
+
+    let mut pos: usize = 4;
+
+    while pos + 8 <= data.len() {
+        let size = read_le(data, pos + 4, 4).unwrap_or(0) as usize;
+
+        let value = match data.get(pos + 8..pos + 8 + size) {
+            Some(value) => read_text(value),
+            None => return,
+        };
+
+        let name = match &data[pos..pos + 4] {
+            b"INAM" => "title",
+            b"IART" => "artist",
+            b"IPRD" => "album",
+            b"IGNR" => "genre",
+            b"ICRD" => "date",
+            b"ICMT" => "comment",
+            b"ICOP" => "copyright",
+            b"ITRK" | b"IPRT" => "track",
+            b"ILNG" => "language",
+            _ => "",
+        };
+
+        insert_meta_data(name, &value, target);
+
+        pos += 8 + size + (size & 1);
+    }
+}
+
+/// Returns the ffmpeg Codec Name for a WAVE Format Tag
+///
+/// # Arguments
+///
+/// * `format` - WAVE Format Tag
+/// * `bit_depth` - Bits per Sample
+fn get_codec_name(format: u64, bit_depth: u8) -> String {
+    let name = match (format, bit_depth) {
+        (1, 8) => "pcm_u8",
+        (1, 24) => "pcm_s24le",
+        (1, 32) => "pcm_s32le",
+        (1, _) => "pcm_s16le",
+        (3, 64) => "pcm_f64le",
+        (3, _) => "pcm_f32le",
+        (6, _) => "pcm_alaw",
+        (7, _) => "pcm_mulaw",
+        (0x55, _) => "mp3",
+        (0x2000, _) => "ac3",
+        (0x2001, _) => "dts",
+        _ => "unknown",
+    };
+
+    name.to_string()
+}
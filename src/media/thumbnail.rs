@@ -1,5 +1,7 @@
 use tools;
 use std::path::Path;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
 
 /// # Thumbnail
 ///
@@ -8,6 +10,10 @@ use std::path::Path;
 /// This structure is part of a Media Item and should not be
 /// used alone.
 ///
+/// Cover Art embedded into a Media File is not extracted. The
+/// Thumbnail then points to the Media File itself and the
+/// Offset of the Picture inside of it.
+///
 /// # To-Do
 /// Add the creation Function once FFMPEG can be directly used.
 #[derive(Clone)]
@@ -15,6 +21,7 @@ pub struct Thumbnail {
     pub item_id: u64,
     pub file_path: String,
     pub file_size: u64,
+    pub offset: u64,
     pub mime_type: String,
     pub width: u16,
     pub height: u16,
@@ -27,6 +34,7 @@ impl Thumbnail {
             item_id: 0,
             file_path: String::new(),
             file_size: 0,
+            offset: 0,
             mime_type: String::new(),
             width: 0,
             height: 0,
@@ -41,6 +49,7 @@ impl Thumbnail {
                 tools::NameValuePair::new("path", &self.file_path),
                 tools::NameValuePair::new("mimeType", &self.mime_type),
                 tools::NameValuePair::new("size", &self.file_size.to_string()),
+                tools::NameValuePair::new("offset", &self.offset.to_string()),
                 tools::NameValuePair::new("width", &self.width.to_string()),
                 tools::NameValuePair::new("height", &self.height.to_string()),
             ];
@@ -60,4 +69,24 @@ impl Thumbnail {
 
         false
     }
+
+    /// Reads the Pictures Data. Returns None if the File can
+    /// not be read or is shorter than expected.
+    pub fn read_data(&self) -> Option<Vec<u8>> {
+        let mut file = match File::open(&self.file_path) {
+            Ok(value) => value,
+            Err(_) => return None,
+        };
+
+        if file.seek(SeekFrom::Start(self.offset)).is_err() {
+            return None;
+        }
+
+        let mut data: Vec<u8> = vec![0; self.file_size as usize];
+
+        match file.read_exact(&mut data) {
+            Ok(_) => Some(data),
+            Err(_) => None,
+        }
+    }
 }
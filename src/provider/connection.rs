@@ -0,0 +1,89 @@
+use std::io::{self, Write};
+use std::net::TcpStream;
+use std::ops::Deref;
+
+/// # Connection
+///
+/// The TcpStream of a Client. Everything written is counted and
+/// the Status of the Response is taken from its Status Line, so
+/// every Request can be written to the Access Log no matter how
+/// the Response was sent.
+pub struct Connection {
+    stream: TcpStream,
+    status: u16,
+    bytes_sent: u64,
+}
+
+impl Connection {
+    /// Creates a new Connection
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - TcpStream of the Client
+    pub fn new(stream: TcpStream) -> Connection {
+        Connection {
+            stream,
+            status: 0,
+            bytes_sent: 0,
+        }
+    }
+
+    /// Forgets the Status and Bytes of the last Response. Called
+    /// before the next Request on the Connection is answered.
+    pub fn reset(&mut self) {
+        self.status = 0;
+        self.bytes_sent = 0;
+    }
+
+    /// Returns the Status of the Response sent since the last
+    /// reset. 0 if nothing was sent.
+    pub fn get_status(&self) -> u16 {
+        self.status
+    }
+
+    /// Returns the Number of Bytes sent since the last reset,
+    /// including the Header.
+    pub fn get_bytes_sent(&self) -> u64 {
+        self.bytes_sent
+    }
+
+    /// Counts Bytes sent without using the Connection as Writer,
+    /// e.g. by sendfile(2).
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - Number of Bytes sent
+    pub fn add_bytes_sent(&mut self, bytes: u64) {
+        self.bytes_sent += bytes;
+    }
+}
+
+impl Write for Connection {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.stream.write(buf)?;
+
+        // The first final Status Line is the one of the Response. "100 Continue" is skipped.
+        if self.status == 0 && written >= 12 && buf.starts_with(b"HTTP/1.") {
+            let status = String::from_utf8_lossy(&buf[9..12]).parse::<u16>().unwrap_or(0);
+
+            if status >= 200 {
+                self.status = status;
+            }
+        }
+
+        self.bytes_sent += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush()
+    }
+}
+
+impl Deref for Connection {
+    type Target = TcpStream;
+
+    fn deref(&self) -> &TcpStream {
+        &self.stream
+    }
+}
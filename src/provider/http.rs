@@ -1,21 +1,54 @@
 use std::io::prelude::*;
-use std::net::TcpStream;
+use provider::Connection;
+use std::io;
 use std::io::{SeekFrom, ErrorKind};
-use std::fs::{metadata, File};
-use chrono::{Local, Duration};
+use std::fs::File;
+use std::time::UNIX_EPOCH;
+use chrono::{DateTime, TimeZone, Utc};
+use uuid::Uuid;
+#[cfg(target_os = "linux")]
+use std::os::unix::io::AsRawFd;
+#[cfg(target_os = "linux")]
+use std::convert::TryFrom;
+#[cfg(target_os = "linux")]
+use libc;
 
 use configuration::ServerConfiguration;
+use super::Request;
+use super::timeseek::{TimeSeekRange, format_npt_time};
+
+/// Largest Number of Ranges served for a single Request
+const MAX_RANGES: usize = 32;
+
+/// Largest Number of Bytes handed to a single sendfile(2) Call
+#[cfg(target_os = "linux")]
+const MAX_SENDFILE_SIZE: u64 = 0x7FFF_F000;
+
+/// Seconds Clients may use Responses with Validators without asking again
+const CACHE_MAX_AGE: u64 = 300;
 
 /// # HTTP Status
 ///
 /// Enumeration that Provides the HTTP Status Codes
 pub enum Status {
     Ok200,
+    Accepted202,
+    PartialContent206,
+    NotModified304,
     BadRequest400,
+    Unauthorized401,
     Forbidden403,
     NotFound404,
+    MethodNotAllowed405,
+    Conflict409,
+    PayloadTooLarge413,
+    UriTooLong414,
     RangeNotSatisfiable416,
+    RequestHeaderFieldsTooLarge431,
     InternalServerError500,
+    NotImplemented501,
+    ServiceUnavailable503,
+    HttpVersionNotSupported505,
 }
 
 impl Status {
@@ -23,17 +56,33 @@ impl Status {
     pub fn get(&self) -> String {
         match self {
             &Status::Ok200 => String::from("200 OK"),
+            &Status::Accepted202 => String::from("202 Accepted"),
+            &Status::PartialContent206 => String::from("206 Partial Content"),
+            &Status::NotModified304 => String::from("304 Not Modified"),
             &Status::BadRequest400 => String::from("400 Bad Request"),
+            &Status::Unauthorized401 => String::from("401 Unauthorized"),
             &Status::Forbidden403 => String::from("403 Forbidden"),
             &Status::NotFound404 => String::from("404 Not Found"),
+            &Status::MethodNotAllowed405 => String::from("405 Method Not Allowed"),
+            &Status::Conflict409 => String::from("409 Conflict"),
+            &Status::PayloadTooLarge413 => String::from("413 Payload Too Large"),
+            &Status::UriTooLong414 => String::from("414 URI Too Long"),
             &Status::RangeNotSatisfiable416 => String::from("416 Range Not Satisfiable"),
+            &Status::RequestHeaderFieldsTooLarge431 => {
+                String::from("431 Request Header Fields Too Large")
+            }
             &Status::InternalServerError500 => String::from("500 Internal Server Error"),
+            &Status::NotImplemented501 => String::from("501 Not Implemented"),
+            &Status::ServiceUnavailable503 => String::from("503 Service Unavailable"),
+            &Status::HttpVersionNotSupported505 => String::from("505 HTTP Version Not Supported"),
         }
     }
 }
 
 /// Generates a HTTP Header with the given Values and
-/// returns it as String.
+/// returns it as String. Responses with Validators may be
+/// cached for a short Time, all others have to be requested
+/// again every Time.
 ///
 /// # Arguments
 ///
@@ -42,37 +91,38 @@ impl Status {
 /// * `keep_alive` - Kepp the Connection alive or close it
 /// * `server_cfg` - Reference to the Server Configuration
 /// * `status` - HTTP Status Line as enumeration
+/// * `validators` - Validators of the Content if it has any
 pub fn generate_header(
     content_size: usize,
     mime: &str,
     keep_alive: bool,
     server_cfg: &ServerConfiguration,
     status: Status,
+    validators: Option<&Validators>,
 ) -> String {
-    let now = Local::now();
+    let cache_fields = match validators {
+        Some(value) => format!("Cache-Control: max-age={}\r\n{}", CACHE_MAX_AGE, value.get_header_fields()),
+        None => String::from("Cache-Control: no-cache\r\n"),
+    };
 
-    return format!(
+    format!(
         "HTTP/1.1 {}\r\n\
 		 Content-Type: {}\r\n\
 		 Content-Length: {}\r\n\
 		 Connection: {}\r\n\
 		 SID: uuid: {} \r\n\
-		 Cache-Control: no-cache\r\n\
+		 {}\
 		 Date: {}\r\n\
-		 Expires: {}\r\n\
 		 Server: {}\r\n\r\n",
         status.get(),
         mime,
         content_size,
         if keep_alive { "Keep-Alive" } else { "Close" },
         server_cfg.server_uuid,
-        now.format("%a, %d %b %Y %H:%M:%S GMT%z"),
-        match now.checked_add_signed(Duration::seconds(180)) {
-            Some(result) => result.format("%a, %d %b %Y %H:%M:%S GMT%z"),
-            None => now.format("%a, %d %b %Y %H:%M:%S GMT%z"),
-        },
+        cache_fields,
+        format_date(Utc::now().timestamp() as u64),
         server_cfg.server_tag
-    );
+    )
 }
 
 /// Sends the given Error Code to the Stream.
@@ -82,234 +132,707 @@ pub fn generate_header(
 ///
 /// * `status` - HTTP Status Code as enumeration
 /// * `server_cfg` - Reference to the Server Configuration
-/// * `stream` - TcpStream so send the Header to
-pub fn send_error(status: Status, server_cfg: &ServerConfiguration, stream: &mut TcpStream) {
+/// * `stream` - Connection to send the Header to
+pub fn send_error(status: Status, server_cfg: &ServerConfiguration, stream: &mut Connection) {
     match stream.write(
-        generate_header(0, "text/html", false, server_cfg, status).as_bytes(),
+        generate_header(0, "text/html", false, server_cfg, status, None).as_bytes(),
     ) {
         Ok(_) => stream.flush().unwrap_or(()),
         Err(_) => {}
     }
 }
 
+/// Adds a Field to a Header generated by generate_header()
+///
+/// # Arguments
+///
+/// * `header` - The generated Header
+/// * `name` - Name of the Field
+/// * `value` - Value of the Field
+pub fn add_header_field(header: &mut String, name: &str, value: &str) {
+    // Insert in Front of the empty Line ending the Header
+    let position = header.len() - 2;
+    header.insert_str(position, &format!("{}: {}\r\n", name, value));
+}
+
+/// Answers a Request whose Method is not allowed for the Path.
+/// The allowed Methods are listed in the "Allow" Header.
+///
+/// # Arguments
+///
+/// * `allowed` - Methods allowed for the requested Path
+/// * `server_cfg` - Reference to the Server Configuration
+/// * `stream` - Connection to send the Header to
+pub fn send_method_not_allowed(allowed: &[&str], server_cfg: &ServerConfiguration, stream: &mut Connection) {
+    let mut header = generate_header(0, "text/html", false, server_cfg, Status::MethodNotAllowed405, None);
+    add_header_field(&mut header, "Allow", &allowed.join(", "));
+
+    if stream.write_all(header.as_bytes()).is_ok() {
+        stream.flush().unwrap_or(());
+    }
+}
+
+/// Answers a Request the Server is too busy for. The Client is told
+/// by the "Retry-After" Header when to try again.
+///
+/// # Arguments
+///
+/// * `retry_after` - Seconds the Client should wait before trying again
+/// * `server_cfg` - Reference to the Server Configuration
+/// * `stream` - Connection to send the Header to
+pub fn send_unavailable(retry_after: u64, server_cfg: &ServerConfiguration, stream: &mut Connection) {
+    let mut header = generate_header(0, "text/html", false, server_cfg, Status::ServiceUnavailable503, None);
+    add_header_field(&mut header, "Retry-After", &retry_after.to_string());
+
+    if stream.write_all(header.as_bytes()).is_ok() {
+        stream.flush().unwrap_or(());
+    }
+}
+
+/// # Validators
+///
+/// Last Modification Time and Entity Tag of a Resource, used
+/// by Clients to check if their Copy is still up to date.
+pub struct Validators {
+    pub last_modified: u64,
+    pub entity_tag: String,
+}
+
+impl Validators {
+    /// Creates the Validators of a Resource. The Entity Tag changes
+    /// whenever the Modification Time or the Size changes.
+    ///
+    /// # Arguments
+    ///
+    /// * `last_modified` - Modification Time as Unix Timestamp
+    /// * `size` - Size of the Resource in Bytes
+    pub fn new(last_modified: u64, size: u64) -> Validators {
+        Validators {
+            last_modified,
+            entity_tag: format!("\"{:x}-{:x}\"", last_modified, size),
+        }
+    }
+
+    /// Returns the Header Fields announcing the Validators
+    pub fn get_header_fields(&self) -> String {
+        format!(
+            "ETag: {}\r\nLast-Modified: {}\r\n",
+            self.entity_tag,
+            format_date(self.last_modified)
+        )
+    }
+}
+
+/// Formats a Unix Timestamp as HTTP Date (IMF-fixdate),
+/// e.g. "Sun, 06 Nov 1994 08:49:37 GMT".
+///
+/// # Arguments
+///
+/// * `timestamp` - Unix Timestamp to format
+pub fn format_date(timestamp: u64) -> String {
+    match Utc.timestamp_opt(timestamp as i64, 0).single() {
+        Some(value) => value.format("%a, %d %b %Y %H:%M:%S GMT").to_string(),
+        None => String::new(),
+    }
+}
+
+/// Parses a HTTP Date into a Unix Timestamp. Returns None if
+/// the Value is no valid Date.
+///
+/// # Arguments
+///
+/// * `value` - HTTP Date as sent by the Client
+pub fn parse_date(value: &str) -> Option<u64> {
+    let timestamp = DateTime::parse_from_rfc2822(value.trim()).ok()?.timestamp();
+
+    if timestamp < 0 {
+        return None;
+    }
+
+    Some(timestamp as u64)
+}
+
+/// Checks the Conditions of a Request against the current
+/// Validators. Returns true if the Client already has the
+/// current Version and "304 Not Modified" can be sent.
+/// "If-None-Match" takes Precedence over "If-Modified-Since".
+///
+/// # Arguments
+///
+/// * `request` - The incoming Request
+/// * `validators` - Validators of the requested Resource
+pub fn is_not_modified(request: &Request, validators: &Validators) -> bool {
+    if request.method != "GET" && request.method != "HEAD" {
+        return false;
+    }
+
+    let entity_tags = request.get_header_values("if-none-match");
+
+    if !entity_tags.is_empty() {
+        // Weak Comparison: "W/" Prefixes are ignored
+        let current = validators.entity_tag.trim_start_matches("W/");
+
+        return entity_tags
+            .iter()
+            .flat_map(|value| value.split(','))
+            .map(|value| value.trim())
+            .any(|value| value == "*" || value.trim_start_matches("W/") == current);
+    }
+
+    match request.get_header("if-modified-since").and_then(parse_date) {
+        Some(date) => validators.last_modified <= date,
+        None => false,
+    }
+}
+
+/// Answers a conditional Request with "304 Not Modified".
+/// Returns true if the Header was sent.
+///
+/// # Arguments
+///
+/// * `validators` - Validators of the requested Resource
+/// * `keep_alive` - Keep the Connection alive or close it
+/// * `server_cfg` - Reference to the Server Configuration
+/// * `stream` - Connection to send the Header to
+pub fn send_not_modified(
+    validators: &Validators,
+    keep_alive: bool,
+    server_cfg: &ServerConfiguration,
+    stream: &mut Connection,
+) -> bool {
+    // A 304 Response carries no Content, so there is no Content-Length
+    let header = format!(
+        "HTTP/1.1 {}\r\n\
+         {}\
+         Cache-Control: max-age={}\r\n\
+         Connection: {}\r\n\
+         Date: {}\r\n\
+         Server: {}\r\n\r\n",
+        Status::NotModified304.get(),
+        validators.get_header_fields(),
+        CACHE_MAX_AGE,
+        if keep_alive { "Keep-Alive" } else { "Close" },
+        format_date(Utc::now().timestamp() as u64),
+        server_cfg.server_tag
+    );
+
+    stream.write_all(header.as_bytes()).is_ok() && stream.flush().is_ok()
+}
+
 /// Sends the given File to the remote Host supporting Byte Ranges.
+/// Media Files with a known Play Time can be seeked by Time as well.
+/// Multiple Ranges are sent as "multipart/byteranges". Ranges are
+/// ignored if the Client sent "If-Range" and its Copy is outdated.
 /// If something goes wrong it will return immeditly.
+/// Returns true if the whole Response was sent and the Connection
+/// can be used for further Requests.
 ///
 /// # Arguments
 ///
-/// * `request` - The original incoming Request (Http Header)
+/// * `request` - The original incoming Request
 /// * `path` - Path to the File to serve
-/// * `stream` - TcpStream to write to
+/// * `stream` - Connection to write to
 /// * `server_cfg` - Reference to the Server Configuration to use
 /// * `mime` - Mime Type to use
+/// * `duration` - Play Time of Media Files in Seconds, enables Time based Seeking
+/// * `keep_alive` - Keep the Connection alive or close it
 pub fn send_file(
-    request: &str,
+    request: &Request,
     path: &str,
-    stream: &mut TcpStream,
+    stream: &mut Connection,
     server_cfg: &ServerConfiguration,
     mime: &str,
-) {
-    // Generate Header
-    let metadata = metadata(path);
-    let file_size: u64 =
-        match metadata {
-            Ok(some) => some.len(),
-            Err(err) => {
-                match err.kind() {
-                    ErrorKind::NotFound => send_error(Status::NotFound404, server_cfg, stream),
-                    ErrorKind::PermissionDenied => {
-                        send_error(Status::Forbidden403, server_cfg, stream)
-                    } 
-                    _ => send_error(Status::InternalServerError500, server_cfg, stream),
-                }
+    duration: Option<f64>,
+    keep_alive: bool,
+) -> bool {
+    let mut file = match File::open(path) {
+        Ok(value) => value,
+        Err(err) => {
+            send_error(get_error_status(&err), server_cfg, stream);
+            return false;
+        }
+    };
+
+    let metadata = match file.metadata() {
+        Ok(value) => value,
+        Err(err) => {
+            send_error(get_error_status(&err), server_cfg, stream);
+            return false;
+        }
+    };
+
+    let file_size: u64 = metadata.len();
+    let last_modified = metadata
+        .modified()
+        .ok()
+        .and_then(|value| value.duration_since(UNIX_EPOCH).ok())
+        .map(|value| value.as_secs())
+        .unwrap_or(0);
+    let validators = Validators::new(last_modified, file_size);
+
+    if is_not_modified(request, &validators) {
+        return send_not_modified(&validators, keep_alive, server_cfg, stream);
+    }
 
-                return;
+    // HEAD Requests get the same Header, but no Content
+    let send_content = request.method != "HEAD";
+    let features = get_dlna_features(duration.is_some());
+    let mut fields = validators.get_header_fields();
+
+    if let Some(duration) = duration.filter(|value| *value > 0.0 && file_size > 0) {
+        if request.get_header("getavailableseekrange.dlna.org") == Some("1") {
+            fields.push_str(&format!(
+                "availableSeekRange.dlna.org: 1 npt={}-{} bytes=0-{}\r\n",
+                format_npt_time(0.0),
+                format_npt_time(duration),
+                file_size - 1
+            ));
+        }
+
+        match TimeSeekRange::from_request(request, duration) {
+            Ok(Some(range)) => {
+                let (start, end) = range.get_byte_range(duration, file_size);
+                let seek_range = format!(
+                    "npt={}-{}/{} bytes={}-{}/{}",
+                    format_npt_time(range.start),
+                    format_npt_time(range.end),
+                    format_npt_time(duration),
+                    start,
+                    end,
+                    file_size
+                );
+
+                let header = generate_file_header(
+                    Status::Ok200,
+                    &format!(
+                        "Content-Type: {}\r\nTimeSeekRange.dlna.org: {}\r\nX-Seek-Range: {}\r\n{}",
+                        mime,
+                        seek_range,
+                        seek_range,
+                        fields
+                    ),
+                    &features,
+                    end - start + 1,
+                    keep_alive,
+                    server_cfg,
+                );
+
+                return stream.write_all(header.as_bytes()).is_ok() &&
+                    (!send_content || send_range(&mut file, start, end - start + 1, stream)) &&
+                    stream.flush().is_ok();
             }
-        };
-    let mut header: String = String::new();
-    let mut bytes_start: u64 = 0;
-    let mut bytes_end: u64 = file_size;
-
-    // Calculate the Byte offsets if requested
-    match request.to_lowercase().find("range: bytes=") {
-        Some(position) => {
-            // Make sure the Requests Line is complete -> Send Error if not
-            if position + 13 >= request.len() {
-
-                send_error(Status::BadRequest400, server_cfg, stream);
-                return;
+            Ok(None) => (),
+            Err(status) => {
+                send_error(status, server_cfg, stream);
+                return false;
             }
+        }
+    }
 
-            // Extract the bytes Request -> Send Error if something fails here
-            let mut bytes: String = request[(position + 13)..].to_string();
-            bytes = bytes[..(match bytes.find("\r\n") {
-                                 Some(position) => position,
-                                 None => {
-                                     send_error(Status::BadRequest400, server_cfg, stream);
-                                     return;
-                                 }
-                             })].to_string();
-
-            // Handle possibilities ("start-end" "-end" "start-" "start")
-            match bytes.find("-") {
-                Some(position) => {
-                    if position == 0 {
-                        bytes_start = 0;
-                        bytes_end = match bytes[1..].parse::<u64>() {
-                            Ok(value) => value,
-                            Err(_) => {
-                                send_error(Status::BadRequest400, server_cfg, stream);
-                                return;
-                            }
-                        }
-                    } else if position == bytes.len() - 1 {
-                        bytes_start = match bytes[..position].parse::<u64>() {
-                            Ok(value) => value,
-                            Err(_) => {
-                                send_error(Status::BadRequest400, server_cfg, stream);
-                                return;
-                            }
-                        };
-                        bytes_end = file_size;
-                    } else {
-                        bytes_start = match bytes[..position].parse::<u64>() {
-                            Ok(value) => value,
-                            Err(_) => {
-                                send_error(Status::BadRequest400, server_cfg, stream);
-                                return;
-                            }
-                        };
-
-                        bytes_end = match bytes[position..].parse::<u64>() {
-                            Ok(value) => value,
-                            Err(_) => {
-                                send_error(Status::BadRequest400, server_cfg, stream);
-                                return;
-                            }
-                        }
-                    }
-                }
-                None => {
-                    bytes_start = match bytes.parse::<u64>() {
-                        Ok(value) => value,
-                        Err(_) => {
-                            send_error(Status::BadRequest400, server_cfg, stream);
-                            return;
-                        }
-                    };
-                }
-            }
-            // Check Boundarys are in File
-            if bytes_start > bytes_end || bytes_end > file_size {
-                send_error(Status::RangeNotSatisfiable416, server_cfg, stream);
-                return;
+    let ranges = if is_range_valid(request, &validators) {
+        match get_ranges(request, file_size) {
+            Some(value) => value,
+            None => {
+                // None of the Ranges is inside the File
+                let header = generate_file_header(
+                    Status::RangeNotSatisfiable416,
+                    &format!("Content-Range: bytes */{}\r\n", file_size),
+                    &features,
+                    0,
+                    keep_alive,
+                    server_cfg,
+                );
+
+                return stream.write_all(header.as_bytes()).is_ok() && stream.flush().is_ok();
             }
-            // Create Partial Content Header
-            header.push_str(&format!(
-                "HTTP/1.1 206 Partial Content\r\n\
-            	 Content-Type: {}\r\n\
-            	 Content-Range: bytes {}-{}/{}\r\n\
-            	 Accept-Ranges: bytes\r\n\
-            	 Connection: Close\r\n\
-            	 ContentFeatures.DLNA.ORG: DLNA.ORG_OP=11;DLNA.ORG_CI=0\r\n\
-            	 TransferMode.DLNA.ORG: Streaming\r\n\
-            	 Server: {}\r\n\
-            	 Content-Length: {}\r\n\r\n",
-                mime,
-                bytes_start,
-                bytes_end,
-                file_size,
-                server_cfg.server_tag,
-                bytes_end - bytes_start
-            ));
         }
-        None => {
-            // Create default Header
-            header.push_str(&format!(
-                "HTTP/1.1 200 OK\r\n\
-	             Content-Type: {}\r\n\
-	             File-Size: {}\r\n\
-	             Accept-Ranges: bytes\r\n\
-            	 Connection: Close\r\n\
-            	 ContentFeatures.DLNA.ORG: DLNA.ORG_OP=11;DLNA.ORG_CI=0\r\n\
-            	 TransferMode.DLNA.ORG: Streaming\r\n\
-            	 Server: {}\r\n\
-            	 Content-Length: {}\r\n\r\n",
-                mime,
+    } else {
+        Vec::new()
+    };
+
+    match ranges.len() {
+        0 => {
+            let header = generate_file_header(
+                Status::Ok200,
+                &format!("Content-Type: {}\r\nFile-Size: {}\r\n{}", mime, file_size, fields),
+                &features,
                 file_size,
-                server_cfg.server_tag,
-                file_size
-            ));
+                keep_alive,
+                server_cfg,
+            );
+
+            stream.write_all(header.as_bytes()).is_ok() &&
+                (!send_content || send_range(&mut file, 0, file_size, stream)) && stream.flush().is_ok()
         }
-    }
-    // Send Header
-    match stream.write_all(header.as_bytes()) {
-        Ok(_) => {}
-        Err(_) => {
-            return;
+        1 => {
+            let (start, end) = ranges[0];
+
+            let header = generate_file_header(
+                Status::PartialContent206,
+                &format!(
+                    "Content-Type: {}\r\nContent-Range: bytes {}-{}/{}\r\n{}",
+                    mime,
+                    start,
+                    end,
+                    file_size,
+                    fields
+                ),
+                &features,
+                end - start + 1,
+                keep_alive,
+                server_cfg,
+            );
+
+            stream.write_all(header.as_bytes()).is_ok() &&
+                (!send_content || send_range(&mut file, start, end - start + 1, stream)) && stream.flush().is_ok()
         }
+        _ => {
+            let boundary = Uuid::new_v4().simple().to_string();
+
+            // Every Part gets its own Header, the Body ends with the closing Boundary
+            let part_headers: Vec<String> = ranges
+                .iter()
+                .map(|&(start, end)| {
+                    format!(
+                        "\r\n--{}\r\nContent-Type: {}\r\nContent-Range: bytes {}-{}/{}\r\n\r\n",
+                        boundary,
+                        mime,
+                        start,
+                        end,
+                        file_size
+                    )
+                })
+                .collect();
+            let closing = format!("\r\n--{}--\r\n", boundary);
+
+            let content_length = part_headers.iter().map(|part| part.len() as u64).sum::<u64>() +
+                ranges.iter().map(|&(start, end)| end - start + 1).sum::<u64>() +
+                closing.len() as u64;
+
+            let header = generate_file_header(
+                Status::PartialContent206,
+                &format!(
+                    "Content-Type: multipart/byteranges; boundary={}\r\n{}",
+                    boundary,
+                    fields
+                ),
+                &features,
+                content_length,
+                keep_alive,
+                server_cfg,
+            );
+
+            if stream.write_all(header.as_bytes()).is_err() {
+                return false;
+            }
+
+            if !send_content {
+                return stream.flush().is_ok();
+            }
+
+            for (part_header, &(start, end)) in part_headers.iter().zip(ranges.iter()) {
+                if stream.write_all(part_header.as_bytes()).is_err() ||
+                    !send_range(&mut file, start, end - start + 1, stream)
+                {
+                    return false;
+                }
+            }
+
+            stream.write_all(closing.as_bytes()).is_ok() && stream.flush().is_ok()
+        }
+    }
+}
+
+/// Returns the DLNA Content Features announcing the supported
+/// Seek Operations, e.g. "DLNA.ORG_OP=11;DLNA.ORG_CI=0". Files are
+/// always sent with Byte Range Support, so Seeking by Bytes is
+/// announced in any Case.
+///
+/// # Arguments
+///
+/// * `time_seek` - Seeking by Play Time (TimeSeekRange.dlna.org) is supported
+pub fn get_dlna_features(time_seek: bool) -> String {
+    format!("DLNA.ORG_OP={}1;DLNA.ORG_CI=0", time_seek as u8)
+}
+
+/// Returns the Status answering a failed File Access
+///
+/// # Arguments
+///
+/// * `err` - Error of the File Access
+fn get_error_status(err: &io::Error) -> Status {
+    match err.kind() {
+        ErrorKind::NotFound => Status::NotFound404,
+        ErrorKind::PermissionDenied => Status::Forbidden403,
+        _ => Status::InternalServerError500,
+    }
+}
+
+/// Generates the Header of a File Response
+///
+/// # Arguments
+///
+/// * `status` - HTTP Status Line as enumeration
+/// * `fields` - Header Fields depending on the Response, each ending with a Line Break
+/// * `features` - DLNA Content Features of the File
+/// * `content_length` - Size of the Content in Bytes
+/// * `keep_alive` - Keep the Connection alive or close it
+/// * `server_cfg` - Reference to the Server Configuration
+fn generate_file_header(
+    status: Status,
+    fields: &str,
+    features: &str,
+    content_length: u64,
+    keep_alive: bool,
+    server_cfg: &ServerConfiguration,
+) -> String {
+    format!(
+        "HTTP/1.1 {}\r\n\
+         {}\
+         Accept-Ranges: bytes\r\n\
+         Cache-Control: max-age={}\r\n\
+         Connection: {}\r\n\
+         ContentFeatures.DLNA.ORG: {}\r\n\
+         TransferMode.DLNA.ORG: Streaming\r\n\
+         Date: {}\r\n\
+         Server: {}\r\n\
+         Content-Length: {}\r\n\r\n",
+        status.get(),
+        fields,
+        CACHE_MAX_AGE,
+        if keep_alive { "Keep-Alive" } else { "Close" },
+        features,
+        format_date(Utc::now().timestamp() as u64),
+        server_cfg.server_tag,
+        content_length
+    )
+}
+
+/// Checks if the Ranges of a Request may be served. This is the
+/// Case unless the Client sent "If-Range" with an Entity Tag or
+/// Date that does not match the current File.
+///
+/// # Arguments
+///
+/// * `request` - The incoming Request
+/// * `validators` - Validators of the File
+fn is_range_valid(request: &Request, validators: &Validators) -> bool {
+    let value = match request.get_header("if-range") {
+        Some(value) => value.trim(),
+        None => return true,
+    };
+
+    // Weak Entity Tags never match, Dates have to match exactly
+    if value.starts_with('"') || value.starts_with("W/") {
+        value == validators.entity_tag
+    } else {
+        parse_date(value) == Some(validators.last_modified)
     }
-    // Open File and create Buffer
-    let mut file =
-        match File::open(path) {
+}
+
+/// Parses the "Range" Header of a Request (RFC 7233). Returns the
+/// requested, inclusive Byte Ranges sorted and merged, or an empty
+/// List if the whole File is requested. Invalid Headers are ignored.
+/// Returns None if no Range is inside the File.
+///
+/// # Arguments
+///
+/// * `request` - The incoming Request
+/// * `file_size` - Size of the File in Bytes
+fn get_ranges(request: &Request, file_size: u64) -> Option<Vec<(u64, u64)>> {
+    let value = match request.get_header("range") {
+        Some(value) => value.trim(),
+        None => return Some(Vec::new()),
+    };
+
+    let specs = match value.get(..6) {
+        Some(unit) if unit.eq_ignore_ascii_case("bytes=") => &value[6..],
+        _ => return Some(Vec::new()),
+    };
+
+    let mut ranges: Vec<(u64, u64)> = Vec::new();
+    let mut count: usize = 0;
+
+    for spec in specs.split(',').map(|spec| spec.trim()).filter(|spec| !spec.is_empty()) {
+        let position = match spec.find('-') {
+            Some(value) => value,
+            None => return Some(Vec::new()),
+        };
+
+        let first = &spec[..position];
+        let last = &spec[position + 1..];
+        count += 1;
+
+        if count > MAX_RANGES {
+            return Some(Vec::new());
+        }
+
+        if first.is_empty() {
+            // Suffix Range: the last N Bytes
+            let length = match last.parse::<u64>() {
+                Ok(value) => value,
+                Err(_) => return Some(Vec::new()),
+            };
+
+            if length > 0 && file_size > 0 {
+                ranges.push((file_size.saturating_sub(length), file_size - 1));
+            }
+
+            continue;
+        }
+
+        let start = match first.parse::<u64>() {
             Ok(value) => value,
-            Err(err) => {
-                match err.kind() {
-                    ErrorKind::NotFound => send_error(Status::NotFound404, server_cfg, stream),
-                    ErrorKind::PermissionDenied => {
-                        send_error(Status::Forbidden403, server_cfg, stream)
-                    } 
-                    _ => send_error(Status::InternalServerError500, server_cfg, stream),
-                }
+            Err(_) => return Some(Vec::new()),
+        };
 
-                return;
+        // Open ended Ranges reach to the End of the File
+        let end = if last.is_empty() {
+            u64::MAX
+        } else {
+            match last.parse::<u64>() {
+                Ok(value) if value >= start => value,
+                _ => return Some(Vec::new()),
             }
         };
-    let mut buffer = [0; 65515];
-    let mut transferred: u64 = 0;
-
-    // Seek to requested Position
-    match file.seek(SeekFrom::Start(bytes_start)) {
-        Ok(_) => {}
-        Err(_) => {
-            send_error(Status::InternalServerError500, server_cfg, stream);
-            return;
+
+        if start < file_size {
+            ranges.push((start, end.min(file_size - 1)));
         }
     }
-    // Send the File Contents
-    loop {
-        // Read from File
-        let readed: usize = match file.read(&mut buffer) {
-            Ok(read) => read,
-            Err(_) => {
-                break;
-            }
-        };
 
-        if readed == 0 {
-            break;
+    if count == 0 {
+        return Some(Vec::new());
+    }
+
+    if ranges.is_empty() {
+        return None;
+    }
+
+    // Overlapping and adjacent Ranges are sent only once
+    ranges.sort();
+
+    let mut merged: Vec<(u64, u64)> = Vec::new();
+
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1.saturating_add(1) => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
         }
+    }
 
-        // Write to Stream
-        match stream.write_all(&buffer[..readed]) {
-            Ok(_) => {}
-            Err(_) => {
-                break;
-            }
+    Some(merged)
+}
+
+/// Sends a Part of the File to the Stream. The Kernel copies the
+/// Data directly from the File to the Socket where possible, else
+/// it is copied through a Buffer. Returns false if the Part could
+/// not be sent completely.
+///
+/// # Arguments
+///
+/// * `file` - The opened File
+/// * `start` - Offset of the first Byte to send
+/// * `length` - Number of Bytes to send
+/// * `stream` - Connection to write to
+fn send_range(file: &mut File, start: u64, length: u64, stream: &mut Connection) -> bool {
+    match send_range_zero_copy(file, start, length, stream) {
+        Some(result) => result,
+        None => send_range_buffered(file, start, length, stream),
+    }
+}
+
+/// Sends a Part of the File using sendfile(2), so the Data never
+/// passes User Space. Returns None without sending anything if
+/// sendfile can not be used for the File, e.g. because the File
+/// System does not support it.
+///
+/// # Arguments
+///
+/// * `file` - The opened File
+/// * `start` - Offset of the first Byte to send
+/// * `length` - Number of Bytes to send
+/// * `stream` - Connection to write to
+#[cfg(target_os = "linux")]
+fn send_range_zero_copy(file: &File, start: u64, length: u64, stream: &mut Connection) -> Option<bool> {
+    // 32 Bit Systems can not address large Files with off_t
+    let mut offset = libc::off_t::try_from(start).ok()?;
+    libc::off_t::try_from(start.checked_add(length)?).ok()?;
+
+    let mut remaining: u64 = length;
+    let mut started = false;
+
+    while remaining > 0 {
+        let count = remaining.min(MAX_SENDFILE_SIZE) as usize;
+        let sent = unsafe { libc::sendfile(stream.as_raw_fd(), file.as_raw_fd(), &mut offset, count) };
+
+        if sent > 0 {
+            remaining -= sent as u64;
+            stream.add_bytes_sent(sent as u64);
+            started = true;
+            continue;
         }
 
-        // Abort if everything was sent
-        transferred += readed as u64;
+        // The File got shorter than expected
+        if sent == 0 {
+            return Some(false);
+        }
+
+        let err = io::Error::last_os_error();
+
+        if err.kind() == ErrorKind::Interrupted {
+            continue;
+        }
 
-        if transferred >= bytes_end {
-            break;
+        // Nothing was sent yet, so the Buffer can take over
+        if !started && (err.raw_os_error() == Some(libc::EINVAL) || err.raw_os_error() == Some(libc::ENOSYS)) {
+            return None;
         }
 
+        return Some(false);
     }
 
-    // Make sure everything is transferred
-    match stream.flush() {
-        Ok(_) => {}
-        Err(_) => {}
+    Some(true)
+}
+
+/// sendfile(2) is only used on Linux, everything else uses the Buffer
+#[cfg(not(target_os = "linux"))]
+fn send_range_zero_copy(_file: &File, _start: u64, _length: u64, _stream: &mut Connection) -> Option<bool> {
+    None
+}
+
+/// Sends a Part of the File to the Stream through a Buffer.
+/// Returns false if the Part could not be sent completely.
+///
+/// # Arguments
+///
+/// * `file` - The opened File
+/// * `start` - Offset of the first Byte to send
+/// * `length` - Number of Bytes to send
+/// * `stream` - Connection to write to
+fn send_range_buffered(file: &mut File, start: u64, length: u64, stream: &mut Connection) -> bool {
+    if file.seek(SeekFrom::Start(start)).is_err() {
+        return false;
     }
+
+    let mut buffer = [0; 65515];
+    let mut remaining: u64 = length;
+
+    while remaining > 0 {
+        // Never read more than requested
+        let size = remaining.min(buffer.len() as u64) as usize;
+
+        let readed: usize = match file.read(&mut buffer[..size]) {
+            Ok(0) | Err(_) => return false,
+            Ok(read) => read,
+        };
+
+        if stream.write_all(&buffer[..readed]).is_err() {
+            return false;
+        }
+
+        remaining -= readed as u64;
+    }
+
+    true
 }
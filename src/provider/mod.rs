@@ -1 +1,14 @@
 pub mod http;
+
+mod timeseek;
+
+mod request;
+pub use self::request::Request;
+pub use self::request::RequestError;
+
+mod router;
+pub use self::router::Router;
+pub use self::router::RouteError;
+
+mod connection;
+pub use self::connection::Connection;
@@ -0,0 +1,468 @@
+use std::io::prelude::*;
+
+use tools::NameValuePair;
+use super::http::Status;
+
+/// Longest Request Line (Method, Target and Version) accepted
+const MAX_REQUEST_LINE_SIZE: usize = 8 * 1024;
+
+/// Largest Size of all Header Lines together
+const MAX_HEADER_SIZE: usize = 64 * 1024;
+
+/// Largest Number of Header Fields accepted
+const MAX_HEADER_COUNT: usize = 100;
+
+/// Largest Request Body accepted. Requests from Control
+/// Points are small SOAP Envelopes.
+const MAX_BODY_SIZE: usize = 1024 * 1024;
+
+/// Longest Line introducing a Chunk of a chunked Body
+const MAX_CHUNK_LINE_SIZE: usize = 1024;
+
+/// # RequestError
+///
+/// Reasons a Request could not be read
+pub enum RequestError {
+    /// The Connection was closed or broke down. Nothing can be answered.
+    Closed,
+    /// The Request is invalid and has to be answered with the Status
+    Failed(Status),
+}
+
+/// # Request
+///
+/// A single HTTP/1.1 Request. The Request Target is split into
+/// the decoded Path and Query, Header Names are compared case
+/// insensitive. The Body is read completely, no matter if it
+/// was sent with a Content-Length or chunked.
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub query: Vec<NameValuePair>,
+    pub version: String,
+    pub headers: Vec<NameValuePair>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    /// Reads the next Request from the Reader. If the Client expects
+    /// it, "100 Continue" is sent to the Writer before the Body is read.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - Buffered Reader of the Connection
+    /// * `writer` - Writer of the same Connection
+    pub fn read<R: BufRead, W: Write>(reader: &mut R, writer: &mut W) -> Result<Request, RequestError> {
+        // Empty Lines in front of the Request Line have to be ignored
+        let mut request_line = String::new();
+
+        for _ in 0..4 {
+            request_line = read_line(reader, MAX_REQUEST_LINE_SIZE, Status::UriTooLong414)?;
+
+            if !request_line.is_empty() {
+                break;
+            }
+        }
+
+        let mut request = Request::parse_request_line(&request_line)?;
+        let mut header_size: usize = 0;
+
+        loop {
+            let line = read_line(reader, MAX_HEADER_SIZE.saturating_sub(header_size), Status::RequestHeaderFieldsTooLarge431)?;
+
+            if line.is_empty() {
+                break;
+            }
+
+            header_size += line.len() + 2;
+
+            if request.headers.len() >= MAX_HEADER_COUNT {
+                return Err(RequestError::Failed(Status::RequestHeaderFieldsTooLarge431));
+            }
+
+            request.headers.push(parse_header(&line)?);
+        }
+
+        request.read_body(reader, writer)?;
+
+        Ok(request)
+    }
+
+    /// Parses the Request Line ("METHOD TARGET HTTP/1.1") into a
+    /// Request without Headers and Body.
+    ///
+    /// # Arguments
+    ///
+    /// * `line` - The Request Line without Line Break
+    fn parse_request_line(line: &str) -> Result<Request, RequestError> {
+        let parts: Vec<&str> = line.split(' ').collect();
+
+        if parts.len() != 3 || parts[0].is_empty() || !parts[0].bytes().all(|byte| byte.is_ascii_uppercase()) {
+            return Err(RequestError::Failed(Status::BadRequest400));
+        }
+
+        match parts[2].strip_prefix("HTTP/") {
+            Some("1.0") | Some("1.1") => (),
+            Some(_) => return Err(RequestError::Failed(Status::HttpVersionNotSupported505)),
+            None => return Err(RequestError::Failed(Status::BadRequest400)),
+        }
+
+        // Proxies send the absolute Form "http://host/path"
+        let mut target = parts[1];
+
+        if let Some(value) = target.strip_prefix("http://") {
+            target = match value.find('/') {
+                Some(position) => &value[position..],
+                None => "/",
+            };
+        }
+
+        if !target.starts_with('/') && target != "*" {
+            return Err(RequestError::Failed(Status::BadRequest400));
+        }
+
+        let (path, query) = match target.find('?') {
+            Some(position) => (&target[..position], &target[position + 1..]),
+            None => (target, ""),
+        };
+
+        let mut request = Request {
+            method: parts[0].to_string(),
+            path: decode(path, false),
+            query: Vec::new(),
+            version: parts[2].to_string(),
+            headers: Vec::new(),
+            body: Vec::new(),
+        };
+
+        for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+            let (name, value) = match pair.find('=') {
+                Some(position) => (&pair[..position], &pair[position + 1..]),
+                None => (pair, ""),
+            };
+
+            request.query.push(NameValuePair::new(&decode(name, true), &decode(value, true)));
+        }
+
+        Ok(request)
+    }
+
+    /// Reads the Body following the Headers. Chunked Transfer Coding
+    /// takes Precedence over the Content-Length.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - Buffered Reader of the Connection
+    /// * `writer` - Writer of the same Connection
+    fn read_body<R: BufRead, W: Write>(&mut self, reader: &mut R, writer: &mut W) -> Result<(), RequestError> {
+        let transfer_encoding = self.get_header_values("transfer-encoding");
+
+        if !transfer_encoding.is_empty() {
+            let codings = transfer_encoding.join(",").to_lowercase();
+
+            // Only "chunked" is supported and it has to be the final Coding
+            if codings.split(',').map(|coding| coding.trim()).rfind(|coding| !coding.is_empty()) != Some("chunked") {
+                return Err(RequestError::Failed(Status::NotImplemented501));
+            }
+
+            self.send_continue(writer)?;
+
+            return self.read_chunked_body(reader);
+        }
+
+        // Repeated Content-Length Fields are only fine if they agree
+        let mut content_length: Option<usize> = None;
+
+        for value in self.get_header_values("content-length") {
+            for part in value.split(',') {
+                let length = match part.trim().parse::<usize>() {
+                    Ok(length) => length,
+                    Err(_) => return Err(RequestError::Failed(Status::BadRequest400)),
+                };
+
+                if content_length.is_some_and(|previous| previous != length) {
+                    return Err(RequestError::Failed(Status::BadRequest400));
+                }
+
+                content_length = Some(length);
+            }
+        }
+
+        let length = match content_length {
+            Some(0) | None => return Ok(()),
+            Some(value) => value,
+        };
+
+        if length > MAX_BODY_SIZE {
+            return Err(RequestError::Failed(Status::PayloadTooLarge413));
+        }
+
+        self.send_continue(writer)?;
+
+        self.body = vec![0; length];
+        reader.read_exact(&mut self.body).map_err(|_| RequestError::Closed)
+    }
+
+    /// Reads a Body sent with the chunked Transfer Coding. Trailer
+    /// Fields following the last Chunk are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - Buffered Reader of the Connection
+    fn read_chunked_body<R: BufRead>(&mut self, reader: &mut R) -> Result<(), RequestError> {
+        loop {
+            let line = read_line(reader, MAX_CHUNK_LINE_SIZE, Status::BadRequest400)?;
+
+            // Chunk Extensions follow the Size after a Semicolon
+            let size_field = line.split(';').next().unwrap_or("").trim();
+
+            let size = match usize::from_str_radix(size_field, 16) {
+                Ok(value) => value,
+                Err(_) => return Err(RequestError::Failed(Status::BadRequest400)),
+            };
+
+            if size == 0 {
+                break;
+            }
+
+            if size > MAX_BODY_SIZE - self.body.len() {
+                return Err(RequestError::Failed(Status::PayloadTooLarge413));
+            }
+
+            let start = self.body.len();
+            self.body.resize(start + size, 0);
+
+            if reader.read_exact(&mut self.body[start..]).is_err() {
+                return Err(RequestError::Closed);
+            }
+
+            if !read_line(reader, MAX_CHUNK_LINE_SIZE, Status::BadRequest400)?.is_empty() {
+                return Err(RequestError::Failed(Status::BadRequest400));
+            }
+        }
+
+        let mut trailer_size: usize = 0;
+
+        loop {
+            let line = read_line(reader, MAX_HEADER_SIZE.saturating_sub(trailer_size), Status::RequestHeaderFieldsTooLarge431)?;
+
+            if line.is_empty() {
+                return Ok(());
+            }
+
+            trailer_size += line.len() + 2;
+        }
+    }
+
+    /// Sends the interim "100 Continue" Response if the Client waits
+    /// for it before sending the Body.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - Writer of the Connection
+    fn send_continue<W: Write>(&self, writer: &mut W) -> Result<(), RequestError> {
+        let expects_continue = self
+            .get_header("expect")
+            .is_some_and(|value| value.eq_ignore_ascii_case("100-continue"));
+
+        if !expects_continue || self.version != "HTTP/1.1" {
+            return Ok(());
+        }
+
+        writer
+            .write_all(b"HTTP/1.1 100 Continue\r\n\r\n")
+            .and_then(|_| writer.flush())
+            .map_err(|_| RequestError::Closed)
+    }
+
+    /// Returns the decoded Value of the first Query Parameter with
+    /// the given Name.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name of the Query Parameter
+    pub fn get_query(&self, name: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|parameter| parameter.name == name)
+            .map(|parameter| parameter.value.as_str())
+    }
+
+    /// Returns the Value of the first Header Field with the given Name.
+    /// Names are compared case insensitive.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name of the Header Field
+    pub fn get_header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|header| header.name.eq_ignore_ascii_case(name))
+            .map(|header| header.value.as_str())
+    }
+
+    /// Returns the Values of all Header Fields with the given Name
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name of the Header Fields
+    pub fn get_header_values(&self, name: &str) -> Vec<&str> {
+        self.headers
+            .iter()
+            .filter(|header| header.name.eq_ignore_ascii_case(name))
+            .map(|header| header.value.as_str())
+            .collect()
+    }
+
+    /// Checks if the Client wants to keep the Connection open after
+    /// the Response. HTTP/1.1 Connections are persistent unless the
+    /// Client asks to close them, HTTP/1.0 ones only on Request.
+    pub fn is_keep_alive(&self) -> bool {
+        let has_option = |option: &str| {
+            self.get_header_values("connection")
+                .iter()
+                .flat_map(|value| value.split(','))
+                .any(|value| value.trim().eq_ignore_ascii_case(option))
+        };
+
+        if self.version == "HTTP/1.1" {
+            !has_option("close")
+        } else {
+            has_option("keep-alive")
+        }
+    }
+
+    /// Returns the Body as Text. Invalid UTF-8 Sequences are replaced.
+    pub fn get_body_text(&self) -> String {
+        String::from_utf8_lossy(&self.body).to_string()
+    }
+
+    /// Returns the Name of the invoked UPnP Action. It is taken from
+    /// the SOAPACTION Header ("urn:...:ContentDirectory:1#Browse") or,
+    /// if that is missing, from the first Element inside the SOAP Body.
+    /// Returns an empty String if there is none.
+    pub fn get_soap_action(&self) -> String {
+        if let Some(value) = self.get_header("soapaction") {
+            if let Some(position) = value.rfind('#') {
+                return value[position + 1..].trim_matches(|c: char| c == '"' || c.is_whitespace()).to_string();
+            }
+        }
+
+        let body = self.get_body_text();
+
+        let content = match body.find("Body>") {
+            Some(position) => &body[position + 5..],
+            None => return String::new(),
+        };
+
+        let element = match content.find('<') {
+            Some(position) => &content[position + 1..],
+            None => return String::new(),
+        };
+
+        let name = element.split(|c: char| c == '>' || c == '/' || c.is_whitespace()).next().unwrap_or("");
+
+        match name.find(':') {
+            Some(position) => name[position + 1..].to_string(),
+            None => name.to_string(),
+        }
+    }
+}
+
+/// Reads a single Line terminated by CRLF (or a bare LF) and returns
+/// it without the Line Break.
+///
+/// # Arguments
+///
+/// * `reader` - Buffered Reader of the Connection
+/// * `limit` - Largest Number of Bytes the Line may have
+/// * `status` - Status to answer with if the Line is too long
+fn read_line<R: BufRead>(reader: &mut R, limit: usize, status: Status) -> Result<String, RequestError> {
+    let mut line: Vec<u8> = Vec::new();
+
+    match reader.by_ref().take(limit as u64 + 2).read_until(b'\n', &mut line) {
+        Ok(0) => return Err(RequestError::Closed),
+        Ok(_) => (),
+        Err(_) => return Err(RequestError::Closed),
+    }
+
+    if line.last() != Some(&b'\n') {
+        if line.len() > limit {
+            return Err(RequestError::Failed(status));
+        }
+
+        return Err(RequestError::Closed);
+    }
+
+    line.pop();
+
+    if line.last() == Some(&b'\r') {
+        line.pop();
+    }
+
+    if line.len() > limit {
+        return Err(RequestError::Failed(status));
+    }
+
+    match String::from_utf8(line) {
+        Ok(value) => Ok(value),
+        Err(_) => Err(RequestError::Failed(Status::BadRequest400)),
+    }
+}
+
+/// Parses a single Header Line ("Name: Value")
+///
+/// # Arguments
+///
+/// * `line` - The Header Line without Line Break
+fn parse_header(line: &str) -> Result<NameValuePair, RequestError> {
+    let position = match line.find(':') {
+        Some(value) => value,
+        None => return Err(RequestError::Failed(Status::BadRequest400)),
+    };
+
+    let name = &line[..position];
+
+    // Folded Lines and Whitespace in front of the Colon are not allowed
+    if name.is_empty() || name.contains(|c: char| c.is_whitespace()) {
+        return Err(RequestError::Failed(Status::BadRequest400));
+    }
+
+    Ok(NameValuePair::new(name, line[position + 1..].trim()))
+}
+
+/// Decodes Percent Encoded Characters. Invalid Sequences are kept
+/// as they are.
+///
+/// # Arguments
+///
+/// * `value` - The encoded Value
+/// * `plus_as_space` - Decode "+" to a Space as used in Queries
+pub fn decode(value: &str, plus_as_space: bool) -> String {
+    let bytes = value.as_bytes();
+    let mut result: Vec<u8> = Vec::with_capacity(bytes.len());
+    let mut index: usize = 0;
+
+    while index < bytes.len() {
+        match bytes[index] {
+            b'%' => {
+                let high = bytes.get(index + 1).and_then(|byte| (*byte as char).to_digit(16));
+                let low = bytes.get(index + 2).and_then(|byte| (*byte as char).to_digit(16));
+
+                if let (Some(high), Some(low)) = (high, low) {
+                    result.push((high * 16 + low) as u8);
+                    index += 3;
+                    continue;
+                }
+
+                result.push(b'%');
+            }
+            b'+' if plus_as_space => result.push(b' '),
+            byte => result.push(byte),
+        }
+
+        index += 1;
+    }
+
+    String::from_utf8_lossy(&result).to_string()
+}
@@ -0,0 +1,105 @@
+/// # RouteError
+///
+/// Reasons a Request could not be routed
+pub enum RouteError {
+    /// No Route matches the Path
+    NotFound,
+    /// Routes match the Path, but none of them the Method. Holds
+    /// the Methods allowed for the Path.
+    MethodNotAllowed(Vec<&'static str>),
+}
+
+/// A single Route of the Router
+struct Route<T> {
+    methods: Vec<&'static str>,
+    segments: Vec<&'static str>,
+    target: T,
+}
+
+/// # Router
+///
+/// Resolves the Method and Path of a Request to the Target
+/// serving it. Patterns are Paths like "/stream/{}", where a
+/// "{}" Segment matches any single Segment of the Path. The
+/// matching Segments are handed out as Parameters. Every
+/// Route accepting GET accepts HEAD as well.
+pub struct Router<T: Copy> {
+    routes: Vec<Route<T>>,
+}
+
+impl<T: Copy> Router<T> {
+    /// Creates a new Router without any Route
+    pub fn new() -> Router<T> {
+        Router { routes: Vec::new() }
+    }
+
+    /// Adds a Route. Routes are checked in the Order they were added.
+    ///
+    /// # Arguments
+    ///
+    /// * `methods` - Methods the Route accepts
+    /// * `pattern` - Path Pattern of the Route
+    /// * `target` - Target serving the Route
+    pub fn add(&mut self, methods: &[&'static str], pattern: &'static str, target: T) {
+        self.routes.push(Route {
+            methods: methods.to_vec(),
+            segments: pattern.split('/').skip(1).collect(),
+            target,
+        });
+    }
+
+    /// Returns the Target of the Route matching the Method and Path
+    /// together with the Parameters taken from the Path.
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - Method of the Request
+    /// * `path` - Decoded Path of the Request
+    pub fn find(&self, method: &str, path: &str) -> Result<(T, Vec<String>), RouteError> {
+        let segments: Vec<&str> = path.split('/').skip(1).collect();
+        let mut allowed: Vec<&'static str> = Vec::new();
+
+        for route in &self.routes {
+            if route.segments.len() != segments.len() {
+                continue;
+            }
+
+            let mut parameters: Vec<String> = Vec::new();
+
+            let matches = route.segments.iter().zip(segments.iter()).all(|(pattern, segment)| {
+                if *pattern == "{}" {
+                    parameters.push(segment.to_string());
+                    !segment.is_empty()
+                } else {
+                    pattern == segment
+                }
+            });
+
+            if !matches {
+                continue;
+            }
+
+            let accepts_get = route.methods.contains(&"GET");
+
+            if route.methods.contains(&method) || (method == "HEAD" && accepts_get) {
+                return Ok((route.target, parameters));
+            }
+
+            for value in &route.methods {
+                if !allowed.contains(value) {
+                    allowed.push(value);
+                }
+            }
+
+            if accepts_get && !allowed.contains(&"HEAD") {
+                allowed.push("HEAD");
+            }
+        }
+
+        if allowed.is_empty() {
+            Err(RouteError::NotFound)
+        } else {
+            Err(RouteError::MethodNotAllowed(allowed))
+        }
+    }
+}
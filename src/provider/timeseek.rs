@@ -0,0 +1,135 @@
+use super::http::Status;
+use super::Request;
+
+/// # TimeSeekRange
+///
+/// Play Time Range requested by a Renderer through the
+/// "TimeSeekRange.dlna.org: npt=start-end" Header. Both
+/// Values are in Seconds, an open End reaches to the End
+/// of the Media.
+pub struct TimeSeekRange {
+    pub start: f64,
+    pub end: f64,
+}
+
+impl TimeSeekRange {
+    /// Reads the Time Seek Range of a Request. Returns None if the
+    /// Request has none, the Status to answer with if it is invalid
+    /// or starts behind the End of the Media.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The incoming Request
+    /// * `duration` - Play Time of the Media in Seconds
+    pub fn from_request(request: &Request, duration: f64) -> Result<Option<TimeSeekRange>, Status> {
+        let value = match request.get_header("timeseekrange.dlna.org") {
+            Some(value) => value.trim(),
+            None => return Ok(None),
+        };
+
+        let range = match value.get(..4) {
+            Some(unit) if unit.eq_ignore_ascii_case("npt=") => &value[4..],
+            _ => return Err(Status::BadRequest400),
+        };
+
+        let position = match range.find('-') {
+            Some(value) => value,
+            None => return Err(Status::BadRequest400),
+        };
+
+        let start = match parse_npt_time(&range[..position]) {
+            Some(value) => value,
+            None => return Err(Status::BadRequest400),
+        };
+
+        // The End may be followed by the Duration ("/300.0") or be missing
+        let end_field = range[position + 1..].split('/').next().unwrap_or("").trim();
+
+        let end = if end_field.is_empty() {
+            duration
+        } else {
+            match parse_npt_time(end_field) {
+                Some(value) if value >= start => value.min(duration),
+                _ => return Err(Status::BadRequest400),
+            }
+        };
+
+        if start >= duration {
+            return Err(Status::RangeNotSatisfiable416);
+        }
+
+        Ok(Some(TimeSeekRange { start, end }))
+    }
+
+    /// Returns the inclusive Byte Range matching the Time Range. As
+    /// the Files are not indexed, the Offsets are estimated from the
+    /// average Bitrate of the File.
+    ///
+    /// # Arguments
+    ///
+    /// * `duration` - Play Time of the Media in Seconds
+    /// * `file_size` - Size of the File in Bytes
+    pub fn get_byte_range(&self, duration: f64, file_size: u64) -> (u64, u64) {
+        let bytes_per_second = file_size as f64 / duration;
+        let last = file_size.saturating_sub(1);
+
+        let start = ((self.start * bytes_per_second) as u64).min(last);
+        let end = if self.end >= duration {
+            last
+        } else {
+            ((self.end * bytes_per_second).ceil() as u64).clamp(start, last)
+        };
+
+        (start, end)
+    }
+}
+
+/// Parses a Normal Play Time ("123.45" or "1:02:03.45") into Seconds
+///
+/// # Arguments
+///
+/// * `value` - The Normal Play Time
+pub fn parse_npt_time(value: &str) -> Option<f64> {
+    let value = value.trim();
+
+    if value.is_empty() || !value.chars().all(|c| c.is_ascii_digit() || c == ':' || c == '.') {
+        return None;
+    }
+
+    let parts: Vec<&str> = value.split(':').collect();
+
+    let seconds = match parts.len() {
+        1 => parts[0].parse::<f64>().ok()?,
+        3 => {
+            let hours = parts[0].parse::<u64>().ok()?;
+            let minutes = parts[1].parse::<u64>().ok()?;
+            let seconds = parts[2].parse::<f64>().ok()?;
+
+            if minutes >= 60 || seconds >= 60.0 {
+                return None;
+            }
+
+            (hours * 3600 + minutes * 60) as f64 + seconds
+        }
+        _ => return None,
+    };
+
+    Some(seconds)
+}
+
+/// Formats Seconds as Normal Play Time ("1:02:03.450")
+///
+/// # Arguments
+///
+/// * `seconds` - Time in Seconds
+pub fn format_npt_time(seconds: f64) -> String {
+    let milliseconds = (seconds.max(0.0) * 1000.0).round() as u64;
+
+    format!(
+        "{}:{:02}:{:02}.{:03}",
+        milliseconds / 3_600_000,
+        milliseconds / 60_000 % 60,
+        milliseconds / 1000 % 60,
+        milliseconds % 1000
+    )
+}
@@ -0,0 +1,422 @@
+use chrono::Local;
+use configuration::ConfigurationHandler;
+use database::{DatabaseManager, Folder, MediaDatabase, Scanner, ScanProgress, Viewer};
+use media::{Item, MediaType};
+use provider::Request;
+use provider::http::Status;
+use tools::{JSONValue, Logger};
+use super::streamlimits::StreamLimits;
+
+/// # ApiEndpoint
+///
+/// The Endpoints of the Admin API below /api/
+#[derive(Clone, Copy)]
+pub enum ApiEndpoint {
+    Library, // GET /api/library
+    Item, // GET /api/items/{id} or /api/items?path=
+    Folder, // GET /api/folders/{id} or /api/folders?path=
+    Renderers, // GET /api/renderers
+    Streams, // GET /api/streams
+    Scan, // POST /api/scan, /api/scan?share= or /api/scan?path=
+}
+
+/// # AdminApi
+///
+/// Answers the Requests of the JSON Admin API. The API lets
+/// Scripts query the Library, the loaded Renderer Profiles and
+/// the running Streams, and trigger new Scans. Every Request
+/// has to carry the configured Token as Bearer Token.
+pub struct AdminApi<'a> {
+    cfg_handler: &'a ConfigurationHandler,
+    db: &'static MediaDatabase,
+    progress: &'static ScanProgress,
+    streams: &'static StreamLimits,
+    logger: Logger,
+}
+
+impl<'a> AdminApi<'a> {
+    /// Creates a new AdminApi working on the given Structures
+    ///
+    /// # Arguments
+    ///
+    /// * `cfg_handler` - Reference to the Configuration Handler
+    /// * `db` - The Media Database
+    /// * `progress` - Progress of the Library Scan
+    /// * `streams` - The running Streams
+    /// * `logger` - Logger to use for triggered Scans
+    pub fn new(
+        cfg_handler: &'a ConfigurationHandler,
+        db: &'static MediaDatabase,
+        progress: &'static ScanProgress,
+        streams: &'static StreamLimits,
+        logger: Logger,
+    ) -> AdminApi<'a> {
+        AdminApi {
+            cfg_handler,
+            db,
+            progress,
+            streams,
+            logger,
+        }
+    }
+
+    /// Checks if the API is enabled by a configured Token
+    pub fn is_enabled(&self) -> bool {
+        !self.cfg_handler.server_configuration.api_token.is_empty()
+    }
+
+    /// Checks if the Request carries the configured Token in its
+    /// "Authorization: Bearer" Header.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The incoming Request
+    pub fn is_authorized(&self, request: &Request) -> bool {
+        let token = &self.cfg_handler.server_configuration.api_token;
+
+        let value = match request.get_header("authorization") {
+            Some(value) => value.trim(),
+            None => return false,
+        };
+
+        let given = match value.get(..7) {
+            Some(scheme) if scheme.eq_ignore_ascii_case("bearer ") => value[7..].trim(),
+            _ => return false,
+        };
+
+        // Compare every Byte, so the Time taken does not tell how much was right
+        given.len() == token.len() &&
+            given
+                .bytes()
+                .zip(token.bytes())
+                .fold(0, |difference, (a, b)| difference | (a ^ b)) == 0
+    }
+
+    /// Answers a Request to the given Endpoint. Returns the Status
+    /// and the JSON Document to respond with.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The requested Endpoint
+    /// * `request` - The incoming Request
+    /// * `parameters` - Parameters taken from the Path
+    pub fn handle_request(
+        &self,
+        endpoint: ApiEndpoint,
+        request: &Request,
+        parameters: &[String],
+    ) -> (Status, JSONValue) {
+        match endpoint {
+            ApiEndpoint::Library => (Status::Ok200, self.get_library()),
+            ApiEndpoint::Item => self.get_item(request, parameters),
+            ApiEndpoint::Folder => self.get_folder(request, parameters),
+            ApiEndpoint::Renderers => (Status::Ok200, self.get_renderers()),
+            ApiEndpoint::Streams => (Status::Ok200, self.get_streams()),
+            ApiEndpoint::Scan => self.start_scan(request),
+        }
+    }
+
+    /// Library Statistics and the Progress of the last Scan
+    fn get_library(&self) -> JSONValue {
+        let db = self.db.read();
+        let mut media_types: Vec<(&str, JSONValue)> = Vec::new();
+        let mut total_size: u64 = 0;
+
+        for media_type in &[MediaType::AUDIO, MediaType::PICTURE, MediaType::VIDEO, MediaType::UNKNOWN] {
+            let (count, size) = db.get_media_statistics(media_type);
+            total_size += size;
+
+            media_types.push((
+                media_type.get_name(),
+                JSONValue::object(vec![
+                    ("items", JSONValue::unsigned(count as u64)),
+                    ("size", JSONValue::unsigned(size)),
+                ]),
+            ));
+        }
+
+        JSONValue::object(vec![
+            ("folders", JSONValue::unsigned(db.get_folder_count() as u64)),
+            ("items", JSONValue::unsigned(db.get_item_count() as u64)),
+            ("size", JSONValue::unsigned(total_size)),
+            ("mediaTypes", JSONValue::object(media_types)),
+            ("scan", self.get_scan_progress()),
+        ])
+    }
+
+    /// Progress of the running or last Scan
+    fn get_scan_progress(&self) -> JSONValue {
+        JSONValue::object(vec![
+            ("running", JSONValue::Bool(self.progress.is_running())),
+            ("found", JSONValue::unsigned(self.progress.get_found() as u64)),
+            ("unchanged", JSONValue::unsigned(self.progress.get_unchanged() as u64)),
+            ("parsed", JSONValue::unsigned(self.progress.get_parsed() as u64)),
+            ("failed", JSONValue::unsigned(self.progress.get_failed() as u64)),
+            ("started", JSONValue::unsigned(self.progress.get_started())),
+            ("finished", JSONValue::unsigned(self.progress.get_finished())),
+        ])
+    }
+
+    /// A single Item looked up by Id or Path
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The incoming Request
+    /// * `parameters` - Parameters taken from the Path
+    fn get_item(&self, request: &Request, parameters: &[String]) -> (Status, JSONValue) {
+        let db = self.db.read();
+
+        let item = match AdminApi::get_lookup(request, parameters) {
+            Some(Ok(id)) => db.get_item_direct(id),
+            Some(Err(path)) => db.get_item_by_path(&path),
+            None => return AdminApi::get_error(Status::BadRequest400, "Id or Path required"),
+        };
+
+        match item {
+            Ok(value) => (Status::Ok200, AdminApi::get_item_json(&value)),
+            Err(_) => AdminApi::get_error(Status::NotFound404, "Item not found"),
+        }
+    }
+
+    /// A single Folder looked up by Id or Path, including its Children
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The incoming Request
+    /// * `parameters` - Parameters taken from the Path
+    fn get_folder(&self, request: &Request, parameters: &[String]) -> (Status, JSONValue) {
+        let db = self.db.read();
+
+        let folder = match AdminApi::get_lookup(request, parameters) {
+            Some(Ok(id)) => db.get_folder_direct(id, &Viewer::anyone()),
+            Some(Err(path)) => db.get_folder_by_path(&path),
+            None => return AdminApi::get_error(Status::BadRequest400, "Id or Path required"),
+        };
+
+        match folder {
+            Ok(value) => (Status::Ok200, AdminApi::get_folder_json(&value, Some(&db))),
+            Err(_) => AdminApi::get_error(Status::NotFound404, "Folder not found"),
+        }
+    }
+
+    /// The loaded Renderer Profiles
+    fn get_renderers(&self) -> JSONValue {
+        let renderers = self.cfg_handler
+            .renderer_configurations
+            .iter()
+            .enumerate()
+            .map(|(index, renderer)| {
+                JSONValue::object(vec![
+                    ("name", JSONValue::string(&renderer.display_name)),
+                    ("default", JSONValue::Bool(index == self.cfg_handler.default_index)),
+                    ("userAgents", JSONValue::strings(&renderer.user_agent_search)),
+                    ("remoteIp", JSONValue::string(&renderer.remote_ip)),
+                    ("fileExtensions", JSONValue::strings(&renderer.file_extensions)),
+                    ("transcoding", JSONValue::Bool(renderer.transcode_enabled)),
+                    ("audioLanguages", JSONValue::strings(&renderer.audio_languages)),
+                    ("allowedPaths", JSONValue::strings(&renderer.allowed_paths)),
+                    ("deniedPaths", JSONValue::strings(&renderer.denied_paths)),
+                    (
+                        "mediaTypes",
+                        JSONValue::Array(
+                            renderer.media_types.iter().map(|media_type| JSONValue::string(media_type.get_name())).collect(),
+                        ),
+                    ),
+                    ("withinSchedule", JSONValue::Bool(renderer.is_within_schedule(&Local::now()))),
+                    (
+                        "maxRating",
+                        match renderer.max_rating {
+                            Some(age) => JSONValue::unsigned(u64::from(age)),
+                            None => JSONValue::Null,
+                        },
+                    ),
+                ])
+            })
+            .collect();
+
+        JSONValue::Array(renderers)
+    }
+
+    /// The running Streams
+    fn get_streams(&self) -> JSONValue {
+        let streams = self.streams
+            .get_streams()
+            .iter()
+            .map(|stream| {
+                JSONValue::object(vec![
+                    ("id", JSONValue::unsigned(stream.id)),
+                    ("client", JSONValue::string(&stream.address.to_string())),
+                    ("itemId", JSONValue::unsigned(stream.item_id)),
+                    ("path", JSONValue::string(&stream.file_path)),
+                    ("started", JSONValue::unsigned(stream.started)),
+                ])
+            })
+            .collect();
+
+        JSONValue::Array(streams)
+    }
+
+    /// Starts a Scan of all Shares, a single Share ("share" Parameter,
+    /// Name or Path) or a single File or Folder ("path" Parameter).
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The incoming Request
+    fn start_scan(&self, request: &Request) -> (Status, JSONValue) {
+        let shares = &self.cfg_handler.server_configuration.shares;
+        let workers = self.cfg_handler.server_configuration.get_scan_threads();
+
+        let (started, target) = if let Some(path) = request.get_query("path") {
+            let share = match self.cfg_handler.server_configuration.get_share(path) {
+                Some(value) => value.clone(),
+                None => return AdminApi::get_error(Status::NotFound404, "Path is not inside a Share"),
+            };
+
+            (
+                Scanner::start_path(self.db, self.progress, share, path, workers, self.logger.clone()),
+                path.to_string(),
+            )
+        } else if let Some(name) = request.get_query("share") {
+            let share = match shares.iter().find(|share| share.get_title() == name || share.path == name) {
+                Some(value) => value.clone(),
+                None => return AdminApi::get_error(Status::NotFound404, "Share not found"),
+            };
+            let target = share.path.clone();
+
+            (
+                Scanner::start(self.db, self.progress, vec![share], workers, self.logger.clone()),
+                target,
+            )
+        } else {
+            (
+                Scanner::start(self.db, self.progress, shares.clone(), workers, self.logger.clone()),
+                String::from("*"),
+            )
+        };
+
+        if !started {
+            return AdminApi::get_error(Status::Conflict409, "A Scan is already running");
+        }
+
+        (
+            Status::Accepted202,
+            JSONValue::object(vec![
+                ("started", JSONValue::Bool(true)),
+                ("target", JSONValue::string(&target)),
+            ]),
+        )
+    }
+
+    /// Returns what to look up: the Id given in the Path, or else the
+    /// "path" Parameter of the Query. None if neither is usable.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The incoming Request
+    /// * `parameters` - Parameters taken from the Path
+    fn get_lookup(request: &Request, parameters: &[String]) -> Option<Result<u64, String>> {
+        match parameters.first() {
+            Some(id) => id.parse::<u64>().ok().map(Ok),
+            None => request.get_query("path").map(|path| Err(path.to_string())),
+        }
+    }
+
+    /// Returns the JSON Representation of an Item
+    ///
+    /// # Arguments
+    ///
+    /// * `item` - The Item
+    fn get_item_json(item: &Item) -> JSONValue {
+        JSONValue::object(vec![
+            ("id", JSONValue::unsigned(item.id)),
+            ("parentId", JSONValue::unsigned(item.parent_id)),
+            ("title", JSONValue::string(&item.meta_data.title)),
+            ("fileName", JSONValue::string(&item.meta_data.file_name)),
+            ("path", JSONValue::string(&item.file_path)),
+            ("mediaType", JSONValue::string(item.media_type.get_name())),
+            ("mimeType", JSONValue::string(&item.get_mime_type())),
+            ("size", JSONValue::unsigned(item.file_size)),
+            (
+                "duration",
+                match item.get_duration_seconds() {
+                    Some(value) => JSONValue::Number(value),
+                    None => JSONValue::Null,
+                },
+            ),
+            ("lastModified", JSONValue::unsigned(item.last_modified)),
+            ("playCount", JSONValue::unsigned(u64::from(item.play_count))),
+            (
+                "lastPlayed",
+                match item.last_played {
+                    0 => JSONValue::Null,
+                    time => JSONValue::unsigned(time),
+                },
+            ),
+            (
+                "bookmarks",
+                JSONValue::Array(
+                    item.bookmarks
+                        .iter()
+                        .map(|bookmark| {
+                            JSONValue::object(vec![
+                                (
+                                    "renderer",
+                                    match bookmark.renderer.as_str() {
+                                        "" => JSONValue::Null,
+                                        renderer => JSONValue::string(renderer),
+                                    },
+                                ),
+                                ("position", JSONValue::unsigned(bookmark.position)),
+                                ("updated", JSONValue::unsigned(bookmark.updated)),
+                            ])
+                        })
+                        .collect(),
+                ),
+            ),
+        ])
+    }
+
+    /// Returns the JSON Representation of a Folder. If the Database is
+    /// given, the Sub Folders and Items of the Folder are included.
+    ///
+    /// # Arguments
+    ///
+    /// * `folder` - The Folder
+    /// * `db` - The Database to look up the Children in
+    fn get_folder_json(folder: &Folder, db: Option<&DatabaseManager>) -> JSONValue {
+        let mut members = vec![
+            ("id", JSONValue::unsigned(folder.id)),
+            ("parentId", JSONValue::unsigned(folder.parent_id)),
+            ("title", JSONValue::string(&folder.title)),
+            ("path", JSONValue::string(&folder.path)),
+            ("childCount", JSONValue::unsigned(u64::from(folder.element_count))),
+            ("lastModified", JSONValue::unsigned(folder.last_modified)),
+        ];
+
+        if let Some(db) = db {
+            let folders = db.get_folder_from_parent(folder.id, &Viewer::anyone())
+                .iter()
+                .map(|value| AdminApi::get_folder_json(value, None))
+                .collect();
+            let items = db.get_items_from_parent(folder.id, &Viewer::anyone())
+                .iter()
+                .map(AdminApi::get_item_json)
+                .collect();
+
+            members.push(("folders", JSONValue::Array(folders)));
+            members.push(("items", JSONValue::Array(items)));
+        }
+
+        JSONValue::object(members)
+    }
+
+    /// Returns the Status and JSON Document of an Error
+    ///
+    /// # Arguments
+    ///
+    /// * `status` - HTTP Status of the Error
+    /// * `message` - Description of the Error
+    pub fn get_error(status: Status, message: &str) -> (Status, JSONValue) {
+        (status, JSONValue::object(vec![("error", JSONValue::string(message))]))
+    }
+}
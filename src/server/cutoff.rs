@@ -0,0 +1,42 @@
+use std::net::{Shutdown, TcpStream};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::Duration;
+
+use tools::{Logger, LogLevel};
+
+/// # CutOff
+///
+/// Closes the Connection of a Stream once a Deadline has passed,
+/// e.g. because the Schedule of the Renderer ended. Dropping the
+/// CutOff before the Deadline cancels it, so a Connection kept
+/// alive for further Requests is left untouched.
+pub struct CutOff {
+    _cancel: Sender<()>,
+}
+
+impl CutOff {
+    /// Starts watching the Connection. Returns None if the Connection
+    /// can not be watched.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - Connection the Stream is sent through
+    /// * `deadline` - Time left until the Connection is closed
+    /// * `reason` - Log Message written when the Connection is closed
+    /// * `logger` - Logger to write the Message to
+    pub fn new(stream: &TcpStream, deadline: Duration, reason: String, logger: Logger) -> Option<CutOff> {
+        let stream = stream.try_clone().ok()?;
+        let (cancel, cancelled) = mpsc::channel::<()>();
+
+        thread::spawn(move || {
+            // Nothing is ever sent, the Sender is only dropped
+            if cancelled.recv_timeout(deadline) == Err(RecvTimeoutError::Timeout) {
+                logger.write_log(&reason, LogLevel::INFORMATION);
+                let _ = stream.shutdown(Shutdown::Both);
+            }
+        });
+
+        Some(CutOff { _cancel: cancel })
+    }
+}
@@ -1,18 +1,80 @@
-use std::net::TcpListener;
+use std::net::{IpAddr, TcpListener};
 use std::thread;
-use std::sync::Mutex;
 use daemonize::Daemonize;
-use std::net::TcpStream;
+use std::io::{BufReader, Write};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use chrono::Local;
 
-use configuration::{ConfigurationHandler, ServerConfiguration};
-use tools::{Logger, LogLevel};
-use database::DatabaseManager;
-use server::SSDPServer;
+use configuration::{ConfigurationHandler, RendererConfiguration, ServerConfiguration};
+use tools::{AccessEntry, AccessLog, Logger, LogLevel, LogModule, XMLParser, NameValuePair};
+use database::{MediaDatabase, Scanner, ScanProgress};
+use server::{AdminApi, ApiEndpoint, CutOff, Metrics, PlaybackTracker, SSDPServer, StreamLimits, WorkerPool};
 use upnp::{ConnectionManager, ContentDirectory};
-use provider::http;
+use provider::{http, Connection, Request, RequestError, Router, RouteError};
+use media::{imagescaler, ImageProfile, Item, MediaType};
+use web::WebInterface;
 
-lazy_static! { static ref LOGGER: Mutex<Logger> = Mutex::new(Logger::new()); }
-lazy_static! { static ref DB_MANAGER: Mutex<DatabaseManager> = Mutex::new(DatabaseManager::new()); }
+lazy_static! { static ref LOGGER: Logger = Logger::new(); }
+lazy_static! { static ref HTTP_LOGGER: Logger = LOGGER.for_module(LogModule::Http); }
+lazy_static! { static ref UPNP_LOGGER: Logger = LOGGER.for_module(LogModule::Upnp); }
+lazy_static! { static ref ACCESS_LOG: AccessLog = AccessLog::new(); }
+lazy_static! { static ref DB_MANAGER: MediaDatabase = MediaDatabase::new(); }
+lazy_static! { static ref SCAN_PROGRESS: ScanProgress = ScanProgress::new(); }
+lazy_static! { static ref ROUTER: Router<Handler> = MediaServer::get_router(); }
+lazy_static! { static ref STREAM_LIMITS: StreamLimits = StreamLimits::new(); }
+lazy_static! { static ref METRICS: Metrics = Metrics::new(); }
+lazy_static! { static ref PLAYBACK_TRACKER: PlaybackTracker = PlaybackTracker::new(); }
+
+/// Number of accepted Connections waiting for a free Worker
+const MAX_QUEUED_CONNECTIONS: usize = 16;
+
+/// Seconds a Client is asked to wait if the Server is too busy
+const RETRY_AFTER: u64 = 10;
+
+/// Milliseconds to wait after a failed Accept before trying again
+const ACCEPT_ERROR_DELAY: u64 = 100;
+
+/// Targets of the Paths the Server answers
+#[derive(Clone, Copy)]
+enum Handler {
+    ConnectionManager,
+    ContentDirectory,
+    Stream,
+    Thumbnail,
+    Image,
+    Status,
+    Metrics,
+    Icon,
+    Api(ApiEndpoint),
+    WebFolder,
+    WebItem,
+}
+
+impl Handler {
+    /// Returns the Name the Handler is counted by in the Metrics
+    fn get_name(self) -> &'static str {
+        match self {
+            Handler::ConnectionManager => "connection_manager",
+            Handler::ContentDirectory => "content_directory",
+            Handler::Stream => "stream",
+            Handler::Thumbnail => "thumbnail",
+            Handler::Image => "image",
+            Handler::Status => "status",
+            Handler::Metrics => "metrics",
+            Handler::Icon => "icon",
+            Handler::Api(_) => "api",
+            Handler::WebFolder => "web_folder",
+            Handler::WebItem => "web_item",
+        }
+    }
+}
+
+/// The Client a Request came from and how it is answered
+struct RequestContext<'a> {
+    client: IpAddr, // Address of the Client
+    renderer: Option<&'a RendererConfiguration>, // Renderer Profile matched for the Request
+    keep_alive: bool, // Keep the Connection alive or close it after the Response
+}
 
 pub struct MediaServer {}
 
@@ -30,7 +92,13 @@ impl MediaServer {
         // Try to parse the Configurations
         let mut cfg_handler: ConfigurationHandler = ConfigurationHandler::new();
 
-        if !cfg_handler.parse(cfg_path) {
+        let cfg_success = cfg_handler.parse(cfg_path);
+
+        for error in &cfg_handler.errors {
+            println!("Attention: {}", error);
+        }
+
+        if !cfg_success {
             println!(
                 "Unable to load the Configuration File: {} !! Make sure to make it readable !! - Shutdown",
                 cfg_path
@@ -40,11 +108,26 @@ impl MediaServer {
         }
 
         // Prepare Logging
-        LOGGER.lock().unwrap().set(
+        LOGGER.set(
             &cfg_handler.server_configuration.log_path,
             cfg_handler.server_configuration.log_level,
             daemonize,
         );
+        LOGGER.set_output(cfg_handler.server_configuration.log_output);
+        LOGGER.set_rotation(
+            cfg_handler.server_configuration.log_rotate_size * 1024 * 1024,
+            cfg_handler.server_configuration.log_rotate_daily,
+            cfg_handler.server_configuration.log_retention,
+        );
+
+        for &(module, level) in &cfg_handler.server_configuration.log_levels {
+            LOGGER.set_module_level(module, level);
+        }
+
+        ACCESS_LOG.set(
+            &cfg_handler.server_configuration.access_log_path,
+            cfg_handler.server_configuration.access_log_format,
+        );
 
         // Bring to Background if requested
         if daemonize {
@@ -61,7 +144,7 @@ impl MediaServer {
         }
 
         // Ouput to Log File
-        LOGGER.lock().unwrap().write_log(
+        LOGGER.write_log(
             &format!(
                 "Simple Linux Media Server Version: {}",
                 option_env!("CARGO_PKG_VERSION").unwrap_or("")
@@ -69,41 +152,24 @@ impl MediaServer {
             LogLevel::INFORMATION,
         );
 
-        LOGGER.lock().unwrap().write_log(
+        LOGGER.write_log(
             "Loading Database...",
             LogLevel::INFORMATION,
         );
 
         // Bring up the Media Database
-        match DB_MANAGER.lock() {
-            Ok(mut value) => {
-                value.load(
-                    &cfg_handler.server_configuration.media_db_path,
-                    cfg_handler.server_configuration.share_dirs.clone(),
-                    LOGGER.lock().unwrap().clone(),
-                )
-            }
-            Err(_) => {
-                LOGGER.lock().unwrap().write_log(
-                    "Unable to get Database Mutex - db.load()!",
-                    LogLevel::ERROR,
-                );
-                return;
-            }
-        }
-
-        match DB_MANAGER.lock() {
-            Ok(mut value) => value.boot_up(),
-            Err(_) => {
-                LOGGER.lock().unwrap().write_log(
-                    "Unable to get Database Mutex - db.boot_up()!",
-                    LogLevel::ERROR,
-                );
-                return;
-            }
-        }
+        DB_MANAGER.update(|db| {
+            db.load(
+                &cfg_handler.server_configuration.media_db_path,
+                cfg_handler.server_configuration.shares.clone(),
+                LOGGER.for_module(LogModule::Database),
+            );
+            db.boot_up();
+        });
+        DB_MANAGER.publish();
+        DB_MANAGER.start_saver();
 
-        LOGGER.lock().unwrap().write_log(
+        LOGGER.write_log(
             "Database ready.",
             LogLevel::INFORMATION,
         );
@@ -115,7 +181,7 @@ impl MediaServer {
         )) {
             Ok(value) => value,
             Err(_) => {
-                LOGGER.lock().unwrap().write_log(
+                LOGGER.write_log(
                     &format!(
                         "Unable to bind TCP Socket to {}:{}!",
                         cfg_handler.server_configuration.server_ip,
@@ -127,7 +193,7 @@ impl MediaServer {
             }
         };
 
-        LOGGER.lock().unwrap().write_log(
+        LOGGER.write_log(
             "Running SSDP Server...",
             LogLevel::INFORMATION,
         );
@@ -135,11 +201,12 @@ impl MediaServer {
         // Bring up the SSDP Server
         let ssdp_server: SSDPServer = match SSDPServer::new(
             &cfg_handler.server_configuration,
-            LOGGER.lock().unwrap().clone(),
+            LOGGER.for_module(LogModule::Ssdp),
+            &METRICS,
         ) {
             Ok(value) => value,
             Err(_) => {
-                LOGGER.lock().unwrap().write_log(
+                LOGGER.write_log(
                     "Unable to create SSDP Server!",
                     LogLevel::ERROR,
                 );
@@ -150,7 +217,7 @@ impl MediaServer {
         match ssdp_server.discover() {
             Ok(_) => {}
             Err(_) => {
-                LOGGER.lock().unwrap().write_log(
+                LOGGER.write_log(
                     "Unable to announce Server!",
                     LogLevel::ERROR,
                 );
@@ -158,43 +225,74 @@ impl MediaServer {
             }
         }
 
-        LOGGER.lock().unwrap().write_log(
+        // Bring the Database up to date in the Background
+        Scanner::start(
+            &DB_MANAGER,
+            &SCAN_PROGRESS,
+            cfg_handler.server_configuration.shares.clone(),
+            cfg_handler.server_configuration.get_scan_threads(),
+            LOGGER.for_module(LogModule::Database),
+        );
+
+        LOGGER.write_log(
             "Waiting for incoming Connections...",
             LogLevel::INFORMATION,
         );
 
-        // Process Incoming Connections in new Threads
+        // Process Incoming Connections by a bounded Number of Workers
+        let workers = WorkerPool::new(cfg_handler.server_configuration.http_threads, MAX_QUEUED_CONNECTIONS);
+
         for stream in listener.incoming() {
             let mut stream = match stream {
-                Ok(value) => value,
-                Err(_) => {
-                    LOGGER.lock().unwrap().write_log(
-                        "Unable to establish Connection!",
+                Ok(value) => Connection::new(value),
+                Err(e) => {
+                    // Running out of File Descriptors or an aborted Handshake is no Reason to stop
+                    HTTP_LOGGER.write_log(
+                        &format!("Unable to establish Connection: {}", e),
                         LogLevel::ERROR,
                     );
-                    break;
+                    thread::sleep(Duration::from_millis(ACCEPT_ERROR_DELAY));
+                    continue;
                 }
             };
             let tcfg_handler = cfg_handler.clone();
             let svr_cfg = cfg_handler.server_configuration.clone();
 
-            LOGGER.lock().unwrap().write_log(
-                &format!(
-                    "New Connection from: {}",
-                    match stream.peer_addr() {
-                        Ok(value) => value,
-                        Err(_) => continue,
-                    }
-                ),
+            HTTP_LOGGER.write_fields(
+                "New Connection",
                 LogLevel::VERBOSE,
+                &[
+                    (
+                        "client",
+                        &match stream.peer_addr() {
+                            Ok(value) => value.to_string(),
+                            Err(_) => continue,
+                        },
+                    ),
+                ],
             );
 
-            thread::spawn(move || {
+            let timeout = Some(Duration::from_secs(svr_cfg.socket_timeout));
+
+            if stream.set_write_timeout(timeout).is_err() {
+                continue;
+            }
+
+            if workers.is_saturated() {
+                HTTP_LOGGER.write_log(
+                    "All Workers are busy. Rejecting Connection..",
+                    LogLevel::INFORMATION,
+                );
+                http::send_unavailable(RETRY_AFTER, &svr_cfg, &mut stream);
+                continue;
+            }
+
+            workers.execute(move || {
                 MediaServer::process_incoming(&mut stream, &svr_cfg, &tcfg_handler);
             });
         }
 
-        LOGGER.lock().unwrap().write_log(
+        LOGGER.write_log(
             "Something went wrong. Shutting down!",
             LogLevel::ERROR,
         );
@@ -203,89 +301,345 @@ impl MediaServer {
         ssdp_server.byebye();
     }
 
+    /// Parses and checks the Configuration at the given Path without
+    /// starting the Server. Every Problem found is printed.
+    /// Returns true only if no Problem was found at all.
+    ///
+    /// # Arguments
+    ///
+    /// * `cfg_path` - Path to the Server Configuration File
+    pub fn check_configuration(cfg_path: &str) -> bool {
+        let mut cfg_handler: ConfigurationHandler = ConfigurationHandler::new();
+        let success = cfg_handler.parse(cfg_path);
+
+        for error in &cfg_handler.errors {
+            println!("{}", error);
+        }
+
+        if success && cfg_handler.errors.is_empty() {
+            println!("Configuration OK: {}", cfg_path);
+            true
+        } else {
+            println!(
+                "Configuration invalid: {} Problem(s) found in {}",
+                cfg_handler.errors.len(),
+                cfg_path
+            );
+            false
+        }
+    }
+
+    /// Builds the Router holding every Path the Server answers
+    fn get_router() -> Router<Handler> {
+        let mut router: Router<Handler> = Router::new();
+
+        router.add(&["GET"], "/connection/description.xml", Handler::ConnectionManager);
+        router.add(&["GET"], "/connection/connection_manager.xml", Handler::ConnectionManager);
+        router.add(&["GET"], "/connection/content_directory.xml", Handler::ConnectionManager);
+        router.add(&["POST", "SUBSCRIBE"], "/connection/connection_manager", Handler::ConnectionManager);
+        router.add(&["POST", "SUBSCRIBE"], "/content/content_directory", Handler::ContentDirectory);
+        router.add(&["GET"], "/stream/{}", Handler::Stream);
+        router.add(&["GET"], "/thumbnail/{}", Handler::Thumbnail);
+        router.add(&["GET"], "/image/{}/{}", Handler::Image);
+        router.add(&["GET"], "/status", Handler::Status);
+        router.add(&["GET"], "/metrics", Handler::Metrics);
+        router.add(&["GET"], "/files/images/icon.png", Handler::Icon);
+        router.add(&["GET"], "/api/library", Handler::Api(ApiEndpoint::Library));
+        router.add(&["GET"], "/api/items", Handler::Api(ApiEndpoint::Item));
+        router.add(&["GET"], "/api/items/{}", Handler::Api(ApiEndpoint::Item));
+        router.add(&["GET"], "/api/folders", Handler::Api(ApiEndpoint::Folder));
+        router.add(&["GET"], "/api/folders/{}", Handler::Api(ApiEndpoint::Folder));
+        router.add(&["GET"], "/api/renderers", Handler::Api(ApiEndpoint::Renderers));
+        router.add(&["GET"], "/api/streams", Handler::Api(ApiEndpoint::Streams));
+        router.add(&["POST"], "/api/scan", Handler::Api(ApiEndpoint::Scan));
+        router.add(&["GET"], "/", Handler::WebFolder);
+        router.add(&["GET"], "/web", Handler::WebFolder);
+        router.add(&["GET"], "/web/", Handler::WebFolder);
+        router.add(&["GET"], "/web/folder/{}", Handler::WebFolder);
+        router.add(&["GET"], "/web/item/{}", Handler::WebItem);
+
+        router
+    }
+
+    /// Serves the Requests of a Connection until the Client closes it,
+    /// it stays idle for too long or the Request Limit is reached.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - The Connection
+    /// * `svr_cfg` - Reference to the Server Configuration
+    /// * `tcfg_handler` - Reference to the Configuration Handler
     fn process_incoming(
-        stream: &mut TcpStream,
+        stream: &mut Connection,
         svr_cfg: &ServerConfiguration,
         tcfg_handler: &ConfigurationHandler,
     ) {
-        let con_manager: ConnectionManager = ConnectionManager::new(&svr_cfg);
-        let content: String = con_manager.handle_connection(stream);
-        let mut xml: String = String::new();
+        let mut reader = match stream.try_clone() {
+            Ok(value) => BufReader::new(value),
+            Err(_) => return,
+        };
 
-        if content.find("/connection/").is_some() {
-            LOGGER.lock().unwrap().write_log(
-                "Got Connection Manager Request...",
-                LogLevel::VERBOSE,
-            );
-            xml = con_manager.handle_request(&content);
-        } else if content.find("/content/").is_some() {
-            LOGGER.lock().unwrap().write_log(
-                "Got Content Directory Request...",
-                LogLevel::VERBOSE,
+        // Idle Connections are closed once the Timeout is reached
+        let timeout = match svr_cfg.keep_alive_timeout {
+            0 => Duration::from_secs(svr_cfg.socket_timeout),
+            value => Duration::from_secs(value.min(svr_cfg.socket_timeout)),
+        };
+
+        if stream.set_read_timeout(Some(timeout)).is_err() {
+            return;
+        }
+
+        let client = match stream.peer_addr() {
+            Ok(value) => value.ip(),
+            Err(_) => return,
+        };
+
+        // Denied Clients get nothing but a 403, whatever they ask for
+        if !svr_cfg.access_rules.is_allowed(&client) {
+            HTTP_LOGGER.write_fields(
+                "Denied Access",
+                LogLevel::INFORMATION,
+                &[("client", &client.to_string())],
             );
-            let db = match DB_MANAGER.lock() {
+
+            // Read the Request first, so the Client gets to see the Response
+            if let Ok(request) = Request::read(&mut reader, stream) {
+                let started = Instant::now();
+                stream.reset();
+                http::send_error(http::Status::Forbidden403, svr_cfg, stream);
+                METRICS.add_request("denied", stream.get_status(), stream.get_bytes_sent());
+                MediaServer::write_access_log(Some(&request), &client, None, stream, started);
+            }
+            return;
+        }
+
+        for count in 1..=svr_cfg.max_keep_alive_requests {
+            let request = match Request::read(&mut reader, stream) {
                 Ok(value) => value,
-                Err(_) => {
-                    LOGGER.lock().unwrap().write_log(
-                        "Unable to mutex Database!",
-                        LogLevel::ERROR,
+                Err(RequestError::Closed) => return,
+                Err(RequestError::Failed(status)) => {
+                    HTTP_LOGGER.write_log(
+                        &format!("Got Invalid Request: {}", status.get()),
+                        LogLevel::VERBOSE,
                     );
-                    http::send_error(http::Status::InternalServerError500, &svr_cfg, stream);
-                    return;
-                }
-            };
-            let mut con_dir: ContentDirectory = ContentDirectory::new(&tcfg_handler, &db);
-            xml = con_dir.handle_request(&content);
-        } else if content.find("/stream/").is_some() {
-            // Streaming
-            let id_field: &str = &content[(content.find("/stream/").unwrap() + 8)..];
-            let id: u64 = id_field[..(id_field.find(" ").unwrap())]
-                .parse::<u64>()
-                .unwrap();
-
-            let item = match DB_MANAGER.lock().unwrap().get_item_direct(id) {
-                Ok(value) => value,
-                Err(_) => {
-                    http::send_error(http::Status::NotFound404, &svr_cfg, stream);
+                    let started = Instant::now();
+                    stream.reset();
+                    http::send_error(status, svr_cfg, stream);
+                    METRICS.add_request("invalid", stream.get_status(), stream.get_bytes_sent());
+                    MediaServer::write_access_log(None, &client, None, stream, started);
                     return;
                 }
             };
 
-            http::send_file(
-                &content,
-                &item.file_path,
-                stream,
-                &svr_cfg,
-                &item.get_mime_type(),
-            );
+            let started = Instant::now();
+            stream.reset();
 
-            return;
-        } else if content.find("/files/images/icon.png").is_some() {
-            LOGGER.lock().unwrap().write_log(
-                "Got Icon / PNG Request...",
-                LogLevel::VERBOSE,
-            );
-            http::send_file(
-                &content,
-                "/var/lib/slms/icon.png",
+            let keep_alive = svr_cfg.keep_alive_timeout > 0 &&
+                count < svr_cfg.max_keep_alive_requests &&
+                request.is_keep_alive();
+
+            let renderer = tcfg_handler.get_renderer(request.get_header("User-Agent").unwrap_or(""), &client);
+            let completed = MediaServer::process_request(
+                &request,
+                &client,
+                renderer,
+                keep_alive,
                 stream,
-                &svr_cfg,
-                "image/png",
+                svr_cfg,
+                tcfg_handler,
             );
 
-            return;
+            let route = match ROUTER.find(&request.method, &request.path) {
+                Ok((handler, _)) => handler.get_name(),
+                Err(_) => "unknown",
+            };
+
+            METRICS.add_request(route, stream.get_status(), stream.get_bytes_sent());
+            MediaServer::write_access_log(Some(&request), &client, renderer, stream, started);
+
+            if !completed || !keep_alive {
+                return;
+            }
         }
+    }
+
+    /// Writes a Request answered on the Connection to the Access Log
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The Request. None if it could not be read
+    /// * `client` - Address of the Client
+    /// * `renderer` - Renderer Profile matched for the Request
+    /// * `stream` - Connection the Response was sent through
+    /// * `started` - Time the Request was read at
+    fn write_access_log(
+        request: Option<&Request>,
+        client: &IpAddr,
+        renderer: Option<&RendererConfiguration>,
+        stream: &Connection,
+        started: Instant,
+    ) {
+        let path = match request {
+            Some(request) if !request.query.is_empty() => {
+                let query: Vec<String> = request
+                    .query
+                    .iter()
+                    .map(|pair| format!("{}={}", pair.name, pair.value))
+                    .collect();
+                format!("{}?{}", request.path, query.join("&"))
+            }
+            Some(request) => request.path.clone(),
+            None => String::new(),
+        };
+        let header = |name: &str| match request {
+            Some(request) => request.get_header(name).unwrap_or(""),
+            None => "",
+        };
+
+        ACCESS_LOG.write(&AccessEntry {
+            client: *client,
+            renderer: match renderer {
+                Some(renderer) => &renderer.display_name,
+                None => "",
+            },
+            user_agent: header("User-Agent"),
+            referer: header("Referer"),
+            method: request.map_or("", |request| &request.method),
+            path: &path,
+            version: request.map_or("", |request| &request.version),
+            range: header("Range"),
+            status: stream.get_status(),
+            bytes: stream.get_bytes_sent(),
+            duration: started.elapsed(),
+        });
+    }
+
+    /// Answers a single Request. Returns true if the Response was sent
+    /// completely and the Connection can be used for the next Request.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The incoming Request
+    /// * `client` - Address of the Client
+    /// * `renderer` - Renderer Profile matched for the Request
+    /// * `keep_alive` - Keep the Connection alive or close it after the Response
+    /// * `stream` - Connection to write to
+    /// * `svr_cfg` - Reference to the Server Configuration
+    /// * `tcfg_handler` - Reference to the Configuration Handler
+    fn process_request(
+        request: &Request,
+        client: &IpAddr,
+        renderer: Option<&RendererConfiguration>,
+        keep_alive: bool,
+        stream: &mut Connection,
+        svr_cfg: &ServerConfiguration,
+        tcfg_handler: &ConfigurationHandler,
+    ) -> bool {
+        let (handler, parameters) = match ROUTER.find(&request.method, &request.path) {
+            Ok(value) => value,
+            Err(RouteError::NotFound) => {
+                HTTP_LOGGER.write_log(
+                    &format!("Got Request for unknown Path: {}", request.path),
+                    LogLevel::VERBOSE,
+                );
+                http::send_error(http::Status::NotFound404, svr_cfg, stream);
+                return false;
+            }
+            Err(RouteError::MethodNotAllowed(allowed)) => {
+                http::send_method_not_allowed(&allowed, svr_cfg, stream);
+                return false;
+            }
+        };
+
+        let con_manager: ConnectionManager = ConnectionManager::new(svr_cfg);
+
+        let xml: String = match handler {
+            Handler::ConnectionManager => {
+                UPNP_LOGGER.write_log(
+                    "Got Connection Manager Request...",
+                    LogLevel::VERBOSE,
+                );
+                con_manager.handle_request(request)
+            }
+            Handler::ContentDirectory => {
+                UPNP_LOGGER.write_log(
+                    "Got Content Directory Request...",
+                    LogLevel::VERBOSE,
+                );
+                let mut con_dir: ContentDirectory = ContentDirectory::new(tcfg_handler, &DB_MANAGER, *client, renderer);
+                con_dir.handle_request(request)
+            }
+            Handler::Stream => {
+                return MediaServer::send_stream(request, &parameters, client, renderer, keep_alive, stream, svr_cfg);
+            }
+            Handler::Thumbnail => {
+                return MediaServer::send_thumbnail(request, &parameters, client, renderer, keep_alive, stream, svr_cfg);
+            }
+            Handler::Image => {
+                return MediaServer::send_image(request, &parameters, client, renderer, keep_alive, stream, svr_cfg);
+            }
+            Handler::WebFolder | Handler::WebItem => {
+                let context = RequestContext {
+                    client: *client,
+                    renderer,
+                    keep_alive,
+                };
+                return MediaServer::send_web_page(request, handler, &parameters, &context, stream, tcfg_handler);
+            }
+            Handler::Api(endpoint) => {
+                return MediaServer::send_api(request, endpoint, &parameters, keep_alive, stream, tcfg_handler);
+            }
+            Handler::Status => {
+                HTTP_LOGGER.write_log(
+                    "Got Status Request...",
+                    LogLevel::VERBOSE,
+                );
+                MediaServer::get_status()
+            }
+            Handler::Metrics => {
+                HTTP_LOGGER.write_log(
+                    "Got Metrics Request...",
+                    LogLevel::VERBOSE,
+                );
+                return MediaServer::send_metrics(request, keep_alive, stream, svr_cfg);
+            }
+            Handler::Icon => {
+                HTTP_LOGGER.write_log(
+                    "Got Icon / PNG Request...",
+                    LogLevel::VERBOSE,
+                );
+                return http::send_file(
+                    request,
+                    "/var/lib/slms/icon.png",
+                    stream,
+                    svr_cfg,
+                    "image/png",
+                    None,
+                    keep_alive,
+                );
+            }
+        };
+
+        if !xml.is_empty() {
+            let mut response = http::generate_header(
+                xml.len(),
+                "text/xml",
+                keep_alive,
+                svr_cfg,
+                http::Status::Ok200,
+                None,
+            );
 
-        if xml.len() > 0 {
-            let mut response =
-                http::generate_header(xml.len(), "text/xml", false, &svr_cfg, http::Status::Ok200);
+            // HEAD Requests get the Header only
+            if request.method != "HEAD" {
+                response.push_str(&xml);
+            }
 
-            response.push_str(&xml);
-            con_manager.send_data(&response, stream);
+            con_manager.send_data(&response, stream)
         } else {
-            LOGGER.lock().unwrap().write_log(
+            UPNP_LOGGER.write_log(
                 "Got Invalid Request. Terminating Connection..",
-                LogLevel::ERROR,
+                LogLevel::INFORMATION,
             );
 
             con_manager.send_data(
@@ -293,12 +647,550 @@ impl MediaServer {
                     0,
                     "text/html",
                     false,
-                    &svr_cfg,
+                    svr_cfg,
                     http::Status::InternalServerError500,
+                    None,
                 ),
                 stream,
             );
+
+            false
+        }
+    }
+
+    /// Looks up the Item with the given Id. Fails with 404 if there is
+    /// no such Item and with 403 if the Client may not access its Share
+    /// or the Item is hidden from the Renderer.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Id of the Item
+    /// * `client` - Address of the Client
+    /// * `renderer` - Configuration of the Renderer
+    /// * `svr_cfg` - Reference to the Server Configuration
+    fn get_item(
+        id: u64,
+        client: &IpAddr,
+        renderer: Option<&RendererConfiguration>,
+        svr_cfg: &ServerConfiguration,
+    ) -> Result<Item, http::Status> {
+        let item = match DB_MANAGER.read().get_item_direct(id) {
+            Ok(value) => value,
+            Err(_) => return Err(http::Status::NotFound404),
+        };
+
+        if !svr_cfg.is_path_allowed(&item.file_path, client) {
+            HTTP_LOGGER.write_fields(
+                "Denied Access to Share",
+                LogLevel::INFORMATION,
+                &[("client", &client.to_string()), ("item", &item.id.to_string()), ("path", &item.file_path)],
+            );
+            return Err(http::Status::Forbidden403);
         }
+
+        if let Some(renderer) = renderer {
+            if !renderer.is_item_visible(&item) || renderer.is_hidden_by_schedule(&Local::now()) {
+                HTTP_LOGGER.write_fields(
+                    "Item is hidden from Renderer",
+                    LogLevel::INFORMATION,
+                    &[
+                        ("client", &client.to_string()),
+                        ("renderer", &renderer.display_name),
+                        ("item", &item.id.to_string()),
+                    ],
+                );
+                return Err(http::Status::Forbidden403);
+            }
+        }
+
+        Ok(item)
+    }
+
+    /// Streams a Media File: /stream/{id}
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The incoming Request
+    /// * `parameters` - Parameters taken from the Path
+    /// * `client` - Address of the Client
+    /// * `renderer` - Configuration of the Renderer
+    /// * `keep_alive` - Keep the Connection alive or close it
+    /// * `stream` - Connection to write to
+    /// * `svr_cfg` - Reference to the Server Configuration
+    fn send_stream(
+        request: &Request,
+        parameters: &[String],
+        client: &IpAddr,
+        renderer: Option<&RendererConfiguration>,
+        keep_alive: bool,
+        stream: &mut Connection,
+        svr_cfg: &ServerConfiguration,
+    ) -> bool {
+        let id: u64 = match parameters[0].parse::<u64>() {
+            Ok(value) => value,
+            Err(_) => {
+                http::send_error(http::Status::BadRequest400, svr_cfg, stream);
+                return false;
+            }
+        };
+
+        let item = match MediaServer::get_item(id, client, renderer, svr_cfg) {
+            Ok(value) => value,
+            Err(status) => {
+                http::send_error(status, svr_cfg, stream);
+                return false;
+            }
+        };
+
+        let now = Local::now();
+        let mut _cut_off: Option<CutOff> = None;
+
+        if let Some(renderer) = renderer {
+            let within_schedule = renderer.is_within_schedule(&now);
+
+            // Follow-up Requests of a Stream started inside the Schedule may finish it
+            let allowed = if renderer.may_finish_streams() {
+                PLAYBACK_TRACKER.begin_request(*client, item.id, within_schedule)
+            } else {
+                within_schedule
+            };
+
+            if !allowed {
+                HTTP_LOGGER.write_fields(
+                    "Renderer is outside of its Schedule. Rejecting Stream",
+                    LogLevel::INFORMATION,
+                    &[
+                        ("client", &client.to_string()),
+                        ("renderer", &renderer.display_name),
+                        ("item", &item.id.to_string()),
+                    ],
+                );
+                http::send_error(http::Status::Forbidden403, svr_cfg, stream);
+                return false;
+            }
+
+            // Closes the Connection when the Schedule ends while streaming
+            if let Some(deadline) = renderer.get_stream_deadline(&now) {
+                _cut_off = CutOff::new(
+                    stream,
+                    deadline,
+                    format!("Schedule of Renderer: {} ended. Cutting off Stream to: {}", renderer.display_name, client),
+                    HTTP_LOGGER.clone(),
+                );
+            }
+        }
+
+        // The Slot is held until the File was sent
+        let _guard = match STREAM_LIMITS.try_acquire(
+            *client,
+            item.id,
+            &item.file_path,
+            svr_cfg.max_streams,
+            svr_cfg.max_streams_per_client,
+        ) {
+            Some(value) => value,
+            None => {
+                HTTP_LOGGER.write_fields(
+                    "Too many Streams. Rejecting Stream",
+                    LogLevel::INFORMATION,
+                    &[("client", &client.to_string()), ("item", &item.id.to_string())],
+                );
+                http::send_unavailable(RETRY_AFTER, svr_cfg, stream);
+                return false;
+            }
+        };
+
+        HTTP_LOGGER.write_fields(
+            "Streaming Item",
+            LogLevel::DEBUG,
+            &[
+                ("client", &client.to_string()),
+                ("renderer", renderer.map(|value| value.display_name.as_str()).unwrap_or("")),
+                ("item", &item.id.to_string()),
+                ("range", request.get_header("Range").unwrap_or("")),
+            ],
+        );
+
+        let sent = http::send_file(
+            request,
+            &item.file_path,
+            stream,
+            svr_cfg,
+            &item.get_mime_type(),
+            item.get_duration_seconds(),
+            keep_alive,
+        );
+
+        MediaServer::track_playback(&item, client, renderer, stream, svr_cfg);
+
+        sent
+    }
+
+    /// Records a Playback of the streamed Item once the Client received
+    /// enough of it. The Play Count and the Time it was played are
+    /// written to the Database right away.
+    ///
+    /// # Arguments
+    ///
+    /// * `item` - The streamed Item
+    /// * `client` - Address of the Client
+    /// * `renderer` - Configuration of the Renderer
+    /// * `stream` - Connection the Item was sent through
+    /// * `svr_cfg` - Reference to the Server Configuration
+    fn track_playback(
+        item: &Item,
+        client: &IpAddr,
+        renderer: Option<&RendererConfiguration>,
+        stream: &Connection,
+        svr_cfg: &ServerConfiguration,
+    ) {
+        // Viewing a Picture is no Playback
+        if item.media_type != MediaType::AUDIO && item.media_type != MediaType::VIDEO {
+            return;
+        }
+
+        if stream.get_status() != 200 && stream.get_status() != 206 {
+            return;
+        }
+
+        let threshold = item.file_size / 100 * svr_cfg.playback_threshold;
+
+        if !PLAYBACK_TRACKER.add_progress(*client, item.id, stream.get_bytes_sent(), threshold) {
+            return;
+        }
+
+        let now = match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(value) => value.as_secs(),
+            Err(_) => return,
+        };
+
+        if !DB_MANAGER.update(|db| db.add_playback(item.id, now)) {
+            return;
+        }
+
+        DB_MANAGER.publish();
+        DB_MANAGER.save_later();
+
+        UPNP_LOGGER.write_fields(
+            "Item played",
+            LogLevel::INFORMATION,
+            &[
+                ("client", &client.to_string()),
+                ("renderer", renderer.map(|value| value.display_name.as_str()).unwrap_or("")),
+                ("item", &item.id.to_string()),
+            ],
+        );
+    }
+
+    /// Sends the Cover Art embedded into a Media File: /thumbnail/{id}
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The incoming Request
+    /// * `parameters` - Parameters taken from the Path
+    /// * `client` - Address of the Client
+    /// * `renderer` - Configuration of the Renderer
+    /// * `keep_alive` - Keep the Connection alive or close it
+    /// * `stream` - Connection to write to
+    /// * `svr_cfg` - Reference to the Server Configuration
+    fn send_thumbnail(
+        request: &Request,
+        parameters: &[String],
+        client: &IpAddr,
+        renderer: Option<&RendererConfiguration>,
+        keep_alive: bool,
+        stream: &mut Connection,
+        svr_cfg: &ServerConfiguration,
+    ) -> bool {
+        let id: u64 = match parameters[0].parse::<u64>() {
+            Ok(value) => value,
+            Err(_) => {
+                http::send_error(http::Status::BadRequest400, svr_cfg, stream);
+                return false;
+            }
+        };
+
+        let item = match MediaServer::get_item(id, client, renderer, svr_cfg) {
+            Ok(value) => value,
+            Err(status) => {
+                http::send_error(status, svr_cfg, stream);
+                return false;
+            }
+        };
+
+        let data = match item.thumbnail.read_data() {
+            Some(value) => value,
+            None => {
+                http::send_error(http::Status::NotFound404, svr_cfg, stream);
+                return false;
+            }
+        };
+
+        let validators = http::Validators::new(item.last_modified, data.len() as u64);
+
+        if http::is_not_modified(request, &validators) {
+            return http::send_not_modified(&validators, keep_alive, svr_cfg, stream);
+        }
+
+        let header = http::generate_header(
+            data.len(),
+            &item.thumbnail.mime_type,
+            keep_alive,
+            svr_cfg,
+            http::Status::Ok200,
+            Some(&validators),
+        );
+
+        // HEAD Requests get the Header only
+        stream.write_all(header.as_bytes()).is_ok() &&
+            (request.method == "HEAD" || stream.write_all(&data).is_ok()) && stream.flush().is_ok()
+    }
+
+    /// Sends a Picture scaled to a DLNA Profile: /image/{id}/{profile}
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The incoming Request
+    /// * `parameters` - Parameters taken from the Path
+    /// * `client` - Address of the Client
+    /// * `renderer` - Configuration of the Renderer
+    /// * `keep_alive` - Keep the Connection alive or close it
+    /// * `stream` - Connection to write to
+    /// * `svr_cfg` - Reference to the Server Configuration
+    fn send_image(
+        request: &Request,
+        parameters: &[String],
+        client: &IpAddr,
+        renderer: Option<&RendererConfiguration>,
+        keep_alive: bool,
+        stream: &mut Connection,
+        svr_cfg: &ServerConfiguration,
+    ) -> bool {
+        let id = parameters[0].parse::<u64>().ok();
+        let profile = ImageProfile::from_name(&parameters[1]);
+
+        let (id, profile) = match (id, profile) {
+            (Some(id), Some(profile)) => (id, profile),
+            _ => {
+                http::send_error(http::Status::BadRequest400, svr_cfg, stream);
+                return false;
+            }
+        };
+
+        let item = match MediaServer::get_item(id, client, renderer, svr_cfg) {
+            Ok(ref value) if value.media_type == MediaType::PICTURE => value.clone(),
+            Ok(_) => {
+                http::send_error(http::Status::NotFound404, svr_cfg, stream);
+                return false;
+            }
+            Err(status) => {
+                http::send_error(status, svr_cfg, stream);
+                return false;
+            }
+        };
+
+        match imagescaler::get_scaled_image(&item, profile, &svr_cfg.thumbnail_dir) {
+            Some(path) => http::send_file(request, &path, stream, svr_cfg, "image/jpeg", None, keep_alive),
+            None => {
+                HTTP_LOGGER.write_log(
+                    &format!("Unable to scale Picture: {} to {}", item.file_path, profile.get_name()),
+                    LogLevel::ERROR,
+                );
+                http::send_error(http::Status::InternalServerError500, svr_cfg, stream);
+                false
+            }
+        }
+    }
+
+    /// Sends a Page of the Web Interface: /web/folder/{id} or /web/item/{id}.
+    /// The Root Folder is shown for /web/ and /.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The incoming Request
+    /// * `handler` - Kind of Page requested
+    /// * `parameters` - Parameters taken from the Path
+    /// * `context` - Client and Renderer of the Request
+    /// * `stream` - Connection to write to
+    /// * `tcfg_handler` - Reference to the Configuration Handler
+    fn send_web_page(
+        request: &Request,
+        handler: Handler,
+        parameters: &[String],
+        context: &RequestContext,
+        stream: &mut Connection,
+        tcfg_handler: &ConfigurationHandler,
+    ) -> bool {
+        let svr_cfg = &tcfg_handler.server_configuration;
+
+        let id: u64 = match parameters.first() {
+            Some(value) => match value.parse::<u64>() {
+                Ok(id) => id,
+                Err(_) => {
+                    http::send_error(http::Status::BadRequest400, svr_cfg, stream);
+                    return false;
+                }
+            },
+            None => 0,
+        };
+
+        let db = DB_MANAGER.read();
+        let web_interface = WebInterface::new(tcfg_handler, &db, context.client, context.renderer);
+
+        let page = match handler {
+            Handler::WebItem => web_interface.get_item_page(id),
+            _ => web_interface.get_folder_page(id),
+        };
+
+        let page = match page {
+            Some(value) => value,
+            None => {
+                http::send_error(http::Status::NotFound404, svr_cfg, stream);
+                return false;
+            }
+        };
+
+        let mut response = http::generate_header(
+            page.len(),
+            "text/html; charset=utf-8",
+            context.keep_alive,
+            svr_cfg,
+            http::Status::Ok200,
+            None,
+        );
+
+        // HEAD Requests get the Header only
+        if request.method != "HEAD" {
+            response.push_str(&page);
+        }
+
+        stream.write_all(response.as_bytes()).is_ok() && stream.flush().is_ok()
+    }
+
+    /// Answers a Request to the JSON Admin API: /api/...
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The incoming Request
+    /// * `endpoint` - The requested Endpoint
+    /// * `parameters` - Parameters taken from the Path
+    /// * `keep_alive` - Keep the Connection alive or close it
+    /// * `stream` - Connection to write to
+    /// * `tcfg_handler` - Reference to the Configuration Handler
+    fn send_api(
+        request: &Request,
+        endpoint: ApiEndpoint,
+        parameters: &[String],
+        keep_alive: bool,
+        stream: &mut Connection,
+        tcfg_handler: &ConfigurationHandler,
+    ) -> bool {
+        let svr_cfg = &tcfg_handler.server_configuration;
+        let api = AdminApi::new(
+            tcfg_handler,
+            &DB_MANAGER,
+            &SCAN_PROGRESS,
+            &STREAM_LIMITS,
+            LOGGER.for_module(LogModule::Database),
+        );
+
+        // Without a Token the API does not exist at all
+        if !api.is_enabled() {
+            http::send_error(http::Status::NotFound404, svr_cfg, stream);
+            return false;
+        }
+
+        let (status, json) = if api.is_authorized(request) {
+            HTTP_LOGGER.write_log(
+                &format!("Got Admin API Request: {} {}", request.method, request.path),
+                LogLevel::VERBOSE,
+            );
+            api.handle_request(endpoint, request, parameters)
+        } else {
+            HTTP_LOGGER.write_log(
+                &format!("Got unauthorized Admin API Request: {} {}", request.method, request.path),
+                LogLevel::INFORMATION,
+            );
+            AdminApi::get_error(http::Status::Unauthorized401, "Missing or invalid Token")
+        };
+
+        let is_unauthorized = matches!(status, http::Status::Unauthorized401);
+
+        let body = json.to_json();
+        let mut response = http::generate_header(
+            body.len(),
+            "application/json; charset=utf-8",
+            keep_alive,
+            svr_cfg,
+            status,
+            None,
+        );
+
+        if is_unauthorized {
+            http::add_header_field(&mut response, "WWW-Authenticate", "Bearer realm=\"slms\"");
+        }
+
+        // HEAD Requests get the Header only
+        if request.method != "HEAD" {
+            response.push_str(&body);
+        }
+
+        stream.write_all(response.as_bytes()).is_ok() && stream.flush().is_ok()
+    }
+
+    /// Sends the Metrics of the Server in the Prometheus Text Format: /metrics
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The incoming Request
+    /// * `keep_alive` - Keep the Connection alive or close it after the Response
+    /// * `stream` - Connection to write to
+    /// * `svr_cfg` - Reference to the Server Configuration
+    fn send_metrics(
+        request: &Request,
+        keep_alive: bool,
+        stream: &mut Connection,
+        svr_cfg: &ServerConfiguration,
+    ) -> bool {
+        let body = METRICS.get_text(&DB_MANAGER, &SCAN_PROGRESS, &STREAM_LIMITS);
+        let mut response = http::generate_header(
+            body.len(),
+            "text/plain; version=0.0.4; charset=utf-8",
+            keep_alive,
+            svr_cfg,
+            http::Status::Ok200,
+            None,
+        );
+
+        // HEAD Requests get the Header only
+        if request.method != "HEAD" {
+            response.push_str(&body);
+        }
+
+        stream.write_all(response.as_bytes()).is_ok() && stream.flush().is_ok()
+    }
+
+    /// Generates the Status Response containing the Progress of the
+    /// Library Scan and the Size of the Media Database.
+    fn get_status() -> String {
+        let db = DB_MANAGER.read();
+        let (folders, items) = (db.get_folder_count(), db.get_item_count());
+        let mut xml_parser: XMLParser = XMLParser::new();
+
+        xml_parser.start_xml();
+        xml_parser.open_tag("status", &Vec::new(), true);
+        xml_parser.open_tag("scan", &SCAN_PROGRESS.get_name_value_pairs(), false);
+        xml_parser.open_tag(
+            "database",
+            &vec![
+                NameValuePair::new("folders", &folders.to_string()),
+                NameValuePair::new("items", &items.to_string()),
+            ],
+            false,
+        );
+        xml_parser.close_tag("status");
+
+        xml_parser.xml_content
     }
 
     fn print_welcome() {
@@ -310,7 +1202,7 @@ impl MediaServer {
 
     fn print_helpscreen() {
         println!(
-            "Commandline Options:\n\t-h\t--help\t\t\t\tDisplay Help Screen\n\t-c\t--configuration\t[PATH]\t\tUse the given Configuration File\n\t-d\t--dont-daemonize\t\tDo not run in Background\n",
+            "Commandline Options:\n\t-h\t--help\t\t\t\tDisplay Help Screen\n\t-c\t--configuration\t[PATH]\t\tUse the given Configuration File\n\t-d\t--dont-daemonize\t\tDo not run in Background\n\t\t--check-config\t\t\tCheck the Configuration and exit\n",
         );
     }
 }
@@ -0,0 +1,263 @@
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time;
+
+use database::{MediaDatabase, ScanProgress};
+use media;
+use server::StreamLimits;
+
+/// Requests answered on a Route with the same Status
+struct RequestCount {
+    route: &'static str,
+    status: u16,
+    requests: u64,
+    bytes: u64,
+}
+
+/// # Metrics
+///
+/// Counters of the Server exposed in the Prometheus Text Format, so
+/// the Server can be monitored and alerted on. Values kept by other
+/// Parts of the Server, e.g. the Database or the Scan Progress, are
+/// read from them when the Metrics are requested.
+pub struct Metrics {
+    requests: Mutex<Vec<RequestCount>>,
+    ssdp_responses: AtomicU64,
+    started: u64,
+}
+
+impl Metrics {
+    /// Creates new Metrics with all Counters set to Zero
+    pub fn new() -> Metrics {
+        Metrics {
+            requests: Mutex::new(Vec::new()),
+            ssdp_responses: AtomicU64::new(0),
+            started: match time::SystemTime::now().duration_since(time::UNIX_EPOCH) {
+                Ok(value) => value.as_secs(),
+                Err(_) => 0,
+            },
+        }
+    }
+
+    /// Counts an answered Request
+    ///
+    /// # Arguments
+    ///
+    /// * `route` - Name of the Route the Request was answered by
+    /// * `status` - Status of the Response. 0 if nothing was sent
+    /// * `bytes` - Bytes sent including the Header
+    pub fn add_request(&self, route: &'static str, status: u16, bytes: u64) {
+        let mut requests = self.lock();
+
+        match requests.iter_mut().find(|count| count.route == route && count.status == status) {
+            Some(count) => {
+                count.requests += 1;
+                count.bytes += bytes;
+            }
+            None => {
+                requests.push(RequestCount {
+                    route,
+                    status,
+                    requests: 1,
+                    bytes,
+                });
+            }
+        }
+    }
+
+    /// Counts a sent SSDP Search Response
+    pub fn add_ssdp_response(&self) {
+        self.ssdp_responses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns all Metrics in the Prometheus Text Format
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The Media Database
+    /// * `progress` - Progress of the Library Scans
+    /// * `limits` - The running Streams
+    pub fn get_text(&self, db: &MediaDatabase, progress: &ScanProgress, limits: &StreamLimits) -> String {
+        let mut text = String::new();
+        let mut requests: Vec<(String, f64)> = Vec::new();
+        let mut bytes: Vec<(&'static str, u64)> = Vec::new();
+
+        for count in self.lock().iter() {
+            let status = match count.status {
+                0 => String::from("none"),
+                status => status.to_string(),
+            };
+
+            requests.push((
+                format!("route=\"{}\",status=\"{}\"", count.route, status),
+                count.requests as f64,
+            ));
+
+            match bytes.iter_mut().find(|value| value.0 == count.route) {
+                Some(value) => value.1 += count.bytes,
+                None => bytes.push((count.route, count.bytes)),
+            }
+        }
+
+        let bytes: Vec<(String, f64)> = bytes
+            .into_iter()
+            .map(|(route, value)| (format!("route=\"{}\"", route), value as f64))
+            .collect();
+
+        add_value(
+            &mut text,
+            "slms_start_time_seconds",
+            "gauge",
+            "UNIX Timestamp the Server was started at",
+            self.started as f64,
+        );
+        add_metric(
+            &mut text,
+            "slms_http_requests_total",
+            "counter",
+            "HTTP Requests answered by Route and Status",
+            &requests,
+        );
+        add_metric(
+            &mut text,
+            "slms_http_sent_bytes_total",
+            "counter",
+            "Bytes sent including Headers by Route",
+            &bytes,
+        );
+        add_value(
+            &mut text,
+            "slms_streams_active",
+            "gauge",
+            "Media Streams currently sent",
+            limits.get_streams().len() as f64,
+        );
+        add_value(
+            &mut text,
+            "slms_ssdp_search_responses_total",
+            "counter",
+            "SSDP Search Responses sent",
+            self.ssdp_responses.load(Ordering::Relaxed) as f64,
+        );
+
+        let snapshot = db.read();
+        add_value(
+            &mut text,
+            "slms_db_items",
+            "gauge",
+            "Items inside the Media Database",
+            snapshot.get_item_count() as f64,
+        );
+        add_value(
+            &mut text,
+            "slms_db_folders",
+            "gauge",
+            "Folders inside the Media Database",
+            snapshot.get_folder_count() as f64,
+        );
+        add_value(
+            &mut text,
+            "slms_db_locks_total",
+            "counter",
+            "Times the Database was locked for a Change",
+            db.get_locks() as f64,
+        );
+        add_value(
+            &mut text,
+            "slms_db_lock_wait_seconds_total",
+            "counter",
+            "Time spent waiting for the Database Lock",
+            db.get_lock_wait().as_secs_f64(),
+        );
+
+        add_value(
+            &mut text,
+            "slms_scan_running",
+            "gauge",
+            "1 while a Library Scan is running",
+            if progress.is_running() { 1.0 } else { 0.0 },
+        );
+        add_value(
+            &mut text,
+            "slms_scans_total",
+            "counter",
+            "Library Scans finished",
+            progress.get_scans() as f64,
+        );
+        add_value(
+            &mut text,
+            "slms_scan_duration_seconds_total",
+            "counter",
+            "Time all finished Library Scans took",
+            progress.get_scan_time().as_secs_f64(),
+        );
+        add_value(
+            &mut text,
+            "slms_scan_last_duration_seconds",
+            "gauge",
+            "Time the last finished Library Scan took",
+            progress.get_last_scan_time().as_secs_f64(),
+        );
+        add_value(
+            &mut text,
+            "slms_scan_failed_files",
+            "gauge",
+            "Files the current or last Library Scan was unable to parse",
+            progress.get_failed() as f64,
+        );
+        add_value(
+            &mut text,
+            "slms_ffprobe_failures_total",
+            "counter",
+            "Files ffprobe was unable to read",
+            media::get_ffprobe_failures() as f64,
+        );
+
+        text
+    }
+
+    /// Locks the Request Counts. Poisoning is ignored, as the
+    /// Counts can not become inconsistent.
+    fn lock(&self) -> MutexGuard<'_, Vec<RequestCount>> {
+        match self.requests.lock() {
+            Ok(value) => value,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+/// Appends a Metric without Labels to the Text
+///
+/// # Arguments
+///
+/// * `text` - Text to append to
+/// * `name` - Name of the Metric
+/// * `kind` - Prometheus Type of the Metric, e.g. counter
+/// * `help` - Description of the Metric
+/// * `value` - Value of the Metric
+fn add_value(text: &mut String, name: &str, kind: &str, help: &str, value: f64) {
+    add_metric(text, name, kind, help, &[(String::new(), value)]);
+}
+
+/// Appends a Metric with its Help and Type Line to the Text
+///
+/// # Arguments
+///
+/// * `text` - Text to append to
+/// * `name` - Name of the Metric
+/// * `kind` - Prometheus Type of the Metric, e.g. counter
+/// * `help` - Description of the Metric
+/// * `samples` - Labels and Values of the Samples
+fn add_metric(text: &mut String, name: &str, kind: &str, help: &str, samples: &[(String, f64)]) {
+    let _ = writeln!(text, "# HELP {} {}", name, help);
+    let _ = writeln!(text, "# TYPE {} {}", name, kind);
+
+    for &(ref labels, value) in samples {
+        if labels.is_empty() {
+            let _ = writeln!(text, "{} {}", name, value);
+        } else {
+            let _ = writeln!(text, "{}{{{}}} {}", name, labels, value);
+        }
+    }
+}
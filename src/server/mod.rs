@@ -3,3 +3,21 @@ pub use self::ssdp::SSDPServer;
 
 mod mediaserver;
 pub use self::mediaserver::MediaServer;
+
+mod workerpool;
+pub use self::workerpool::WorkerPool;
+
+mod streamlimits;
+pub use self::streamlimits::StreamLimits;
+
+mod adminapi;
+pub use self::adminapi::{AdminApi, ApiEndpoint};
+
+mod cutoff;
+pub use self::cutoff::CutOff;
+
+mod metrics;
+pub use self::metrics::Metrics;
+
+mod playbacktracker;
+pub use self::playbacktracker::PlaybackTracker;
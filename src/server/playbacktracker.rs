@@ -0,0 +1,127 @@
+use std::net::IpAddr;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// Time a Client may stop requesting an Item before its next
+/// Request counts as a new Playback
+const SESSION_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// An Item a Client is currently playing
+struct PlaybackSession {
+    client: IpAddr,
+    item_id: u64,
+    bytes: u64,
+    recorded: bool,
+    updated: Instant,
+}
+
+/// # PlaybackTracker
+///
+/// Finds out when a Client actually played an Item. Renderers fetch
+/// a File through many Range Requests and often read its End first,
+/// so the Bytes sent to a Client are summed up per Item until they
+/// pass the Threshold. The Sum is kept until the Client stops
+/// requesting the Item for a while. The Sessions also tell which
+/// Streams were started inside the Schedule of a Renderer.
+pub struct PlaybackTracker {
+    sessions: Mutex<Vec<PlaybackSession>>,
+}
+
+impl PlaybackTracker {
+    /// Creates a new PlaybackTracker without any Session
+    pub fn new() -> PlaybackTracker {
+        PlaybackTracker {
+            sessions: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Registers a Request of a Client for an Item. A Request belonging
+    /// to a running Session keeps it alive and is always let through.
+    /// Otherwise a new Session is only started if allowed. Returns true
+    /// if the Request may be served.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - Address of the Client
+    /// * `item_id` - Id of the requested Item
+    /// * `may_start` - A new Session may be started
+    pub fn begin_request(&self, client: IpAddr, item_id: u64, may_start: bool) -> bool {
+        let mut sessions = self.lock();
+        let now = Instant::now();
+
+        sessions.retain(|session| now.duration_since(session.updated) < SESSION_TIMEOUT);
+
+        if let Some(session) = sessions
+            .iter_mut()
+            .find(|session| session.client == client && session.item_id == item_id) {
+            session.updated = now;
+            return true;
+        }
+
+        if !may_start {
+            return false;
+        }
+
+        sessions.push(PlaybackSession {
+            client,
+            item_id,
+            bytes: 0,
+            recorded: false,
+            updated: now,
+        });
+        true
+    }
+
+    /// Adds the Bytes sent by a Request to the Session of the Client.
+    /// Returns true once the Session passed the Threshold, which means
+    /// the Item was played.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - Address of the Client
+    /// * `item_id` - Id of the streamed Item
+    /// * `bytes` - Bytes sent by the Request
+    /// * `threshold` - Bytes the Client has to receive for a Playback
+    pub fn add_progress(&self, client: IpAddr, item_id: u64, bytes: u64, threshold: u64) -> bool {
+        let mut sessions = self.lock();
+        let now = Instant::now();
+
+        sessions.retain(|session| now.duration_since(session.updated) < SESSION_TIMEOUT);
+
+        let position = match sessions
+            .iter()
+            .position(|session| session.client == client && session.item_id == item_id) {
+            Some(value) => value,
+            None => {
+                sessions.push(PlaybackSession {
+                    client,
+                    item_id,
+                    bytes: 0,
+                    recorded: false,
+                    updated: now,
+                });
+                sessions.len() - 1
+            }
+        };
+
+        let session = &mut sessions[position];
+        session.bytes += bytes;
+        session.updated = now;
+
+        if session.recorded || session.bytes < threshold {
+            return false;
+        }
+
+        session.recorded = true;
+        true
+    }
+
+    /// Locks the Sessions. Poisoning is ignored, as a Session can
+    /// not be left in an inconsistent State.
+    fn lock(&self) -> MutexGuard<'_, Vec<PlaybackSession>> {
+        match self.sessions.lock() {
+            Ok(value) => value,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
@@ -2,6 +2,7 @@ use std::net::{UdpSocket, SocketAddr, Ipv4Addr, IpAddr};
 use std::{str, time, thread};
 
 use configuration::ServerConfiguration;
+use server::Metrics;
 use tools::{Logger, LogLevel};
 
 /// # SSDP Server
@@ -15,6 +16,7 @@ pub struct SSDPServer<'a> {
     server_cfg: &'a ServerConfiguration,
     socket: UdpSocket,
     logger: Logger,
+    metrics: &'static Metrics,
 }
 
 impl<'a> SSDPServer<'a> {
@@ -24,17 +26,24 @@ impl<'a> SSDPServer<'a> {
     /// # Arguments
     ///
     /// * `server_cfg` - The Servers Configuration
-    pub fn new(server_cfg: &ServerConfiguration, logger: Logger) -> Result<SSDPServer, ()> {
+    /// * `logger` - Logger to use
+    /// * `metrics` - Metrics to count the sent Search Responses in
+    pub fn new(
+        server_cfg: &'a ServerConfiguration,
+        logger: Logger,
+        metrics: &'static Metrics,
+    ) -> Result<SSDPServer<'a>, ()> {
         let ssdp = SSDPServer {
             server_cfg,
             socket: match UdpSocket::bind(("0.0.0.0", 1900)) {
                 Ok(value) => value,
                 Err(_) => {
-                    logger.write_log("SSDP: Unable to bind to Interface!", LogLevel::ERROR);
+                    logger.write_log("Unable to bind to Interface!", LogLevel::ERROR);
                     return Err(());
                 }
             },
             logger,
+            metrics,
         };
 
         Ok(ssdp)
@@ -47,7 +56,7 @@ impl<'a> SSDPServer<'a> {
     pub fn discover(&self) -> Result<(), ()> {
         // Join Multicast Group and disable Loopback
         self.logger.write_log(
-            "SSDP: Joining Multicast Group",
+            "Joining Multicast Group",
             LogLevel::DEBUG,
         );
 
@@ -55,7 +64,7 @@ impl<'a> SSDPServer<'a> {
             Ok(_) => {}
             Err(_) => {
                 self.logger.write_log(
-                    "SSDP: Unable to set Loopback to false...",
+                    "Unable to set Loopback to false...",
                     LogLevel::ERROR,
                 );
                 return Err(());
@@ -68,7 +77,7 @@ impl<'a> SSDPServer<'a> {
                 Ok(value) => value,
                 Err(_) => {
                     self.logger.write_log(
-                        "SSDP: Unable to parse Multicast Group...",
+                        "Unable to parse Multicast Group...",
                         LogLevel::ERROR,
                     );
                     return Err(());
@@ -78,7 +87,7 @@ impl<'a> SSDPServer<'a> {
             Ok(_) => {}
             Err(_) => {
                 self.logger.write_log(
-                    "SSDP: Unable to join Multicast Group...",
+                    "Unable to join Multicast Group...",
                     LogLevel::ERROR,
                 );
                 return Err(());
@@ -90,7 +99,7 @@ impl<'a> SSDPServer<'a> {
             Ok(value) => value,
             Err(_) => {
                 self.logger.write_log(
-                    "SSDP: Unable to clone Socket to notify Thread...",
+                    "Unable to clone Socket to notify Thread...",
                     LogLevel::ERROR,
                 );
                 return Err(());
@@ -102,7 +111,7 @@ impl<'a> SSDPServer<'a> {
         let cfg_tag = self.server_cfg.server_tag.clone();
 
         self.logger.write_log(
-            "SSDP: Sending Notify Packages...",
+            "Sending Notify Packages...",
             LogLevel::DEBUG,
         );
 
@@ -125,7 +134,7 @@ impl<'a> SSDPServer<'a> {
             Ok(value) => value,
             Err(_) => {
                 self.logger.write_log(
-                    "SSDP: Unable to clone Socket to SEARCH Response Thread...",
+                    "Unable to clone Socket to SEARCH Response Thread...",
                     LogLevel::ERROR,
                 );
                 return Err(());
@@ -135,9 +144,11 @@ impl<'a> SSDPServer<'a> {
         let cfg_port = self.server_cfg.server_port;
         let cfg_uuid = self.server_cfg.server_uuid.clone();
         let cfg_tag = self.server_cfg.server_tag.clone();
+        let access_rules = self.server_cfg.access_rules.clone();
+        let metrics = self.metrics;
 
         self.logger.write_log(
-            "SSDP: Waiting for SEARCH Requests...",
+            "Waiting for SEARCH Requests...",
             LogLevel::DEBUG,
         );
 
@@ -153,7 +164,7 @@ impl<'a> SSDPServer<'a> {
                     Ok(value) => value,
                     Err(_) => {
                         logg.write_log(
-                            "SSDP: Unable to receive on UDP Socket from Multicast Group...",
+                            "Unable to receive on UDP Socket from Multicast Group...",
                             LogLevel::ERROR,
                         );
                         break;
@@ -165,22 +176,33 @@ impl<'a> SSDPServer<'a> {
                     let request = match str::from_utf8(&buffer[..amt]) {
                         Ok(value) => value,
                         Err(_) => {
-                            logg.write_log(
-                                "SSDP: Unable to convert Request to UTF-8...",
-                                LogLevel::ERROR,
+                            logg.write_fields(
+                                "Ignoring Request that is no valid UTF-8...",
+                                LogLevel::VERBOSE,
+                                &[("client", &src.to_string())],
                             );
-                            break;
+                            continue;
                         }
                     };
 
                     // Only react to M-SEARCH Requests
                     if request.find("M-SEARCH *").is_some() {
-                        SSDPServer::send_search_response(
+                        // Denied Clients must not even discover the Server
+                        if !access_rules.is_allowed(&src.ip()) {
+                            logg.write_fields(
+                                "Ignoring M-SEARCH from denied Address",
+                                LogLevel::VERBOSE,
+                                &[("client", &src.ip().to_string())],
+                            );
+                            continue;
+                        }
+
+                        let sent = SSDPServer::send_search_response(
                             match socket_c.try_clone() {
                                 Ok(value) => value,
                                 Err(_) => {
                                     logg.write_log(
-                                        "SSDP: Unable to clone Socket to Search Response Task...",
+                                        "Unable to clone Socket to Search Response Task...",
                                         LogLevel::ERROR,
                                     );
                                     break;
@@ -194,6 +216,10 @@ impl<'a> SSDPServer<'a> {
                             &cfg_tag,
                             logg.clone(),
                         );
+
+                        if sent {
+                            metrics.add_ssdp_response();
+                        }
                     }
                 }
             }
@@ -208,7 +234,7 @@ impl<'a> SSDPServer<'a> {
     pub fn byebye(&self) {
 
         self.logger.write_log(
-            "SSDP: Sending ByeBye Signals...",
+            "Sending ByeBye Signals...",
             LogLevel::DEBUG,
         );
 
@@ -217,7 +243,7 @@ impl<'a> SSDPServer<'a> {
                 Ok(value) => value,
                 Err(_) => {
                     self.logger.write_log(
-                        "SSDP: Unable to parse Multicast Address...",
+                        "Unable to parse Multicast Address...",
                         LogLevel::ERROR,
                     );
                     return;
@@ -241,7 +267,7 @@ impl<'a> SSDPServer<'a> {
             Ok(_) => {}
             Err(_) => {
                 self.logger.write_log(
-                    "SSDP: Unable to send RootDevice ByeBye to Multicast Address...",
+                    "Unable to send RootDevice ByeBye to Multicast Address...",
                     LogLevel::ERROR,
                 );
                 return;
@@ -263,7 +289,7 @@ impl<'a> SSDPServer<'a> {
             Ok(_) => {}
             Err(_) => {
                 self.logger.write_log(
-                    "SSDP: Unable to send UUID ByeBye to Multicast Address...",
+                    "Unable to send UUID ByeBye to Multicast Address...",
                     LogLevel::ERROR,
                 );
                 return;
@@ -288,7 +314,7 @@ impl<'a> SSDPServer<'a> {
             Ok(_) => {}
             Err(_) => {
                 self.logger.write_log(
-                    "SSDP: Unable to send MediaServer ByeBye to Multicast Address...",
+                    "Unable to send MediaServer ByeBye to Multicast Address...",
                     LogLevel::ERROR,
                 );
                 return;
@@ -313,7 +339,7 @@ impl<'a> SSDPServer<'a> {
             Ok(_) => {}
             Err(_) => {
                 self.logger.write_log(
-                    "SSDP: Unable to send ContentDirectory ByeBye to Multicast Address...",
+                    "Unable to send ContentDirectory ByeBye to Multicast Address...",
                     LogLevel::ERROR,
                 );
                 return;
@@ -338,7 +364,7 @@ impl<'a> SSDPServer<'a> {
             Ok(_) => {}
             Err(_) => {
                 self.logger.write_log(
-                    "SSDP: Unable to send ConnectionManager ByeBye to Multicast Address...",
+                    "Unable to send ConnectionManager ByeBye to Multicast Address...",
                     LogLevel::ERROR,
                 );
                 return;
@@ -370,7 +396,7 @@ impl<'a> SSDPServer<'a> {
                 Ok(value) => value,
                 Err(_) => {
                     logger.write_log(
-                        "SSDP: Unable to parse Multicast Address...",
+                        "Unable to parse Multicast Address...",
                         LogLevel::ERROR,
                     );
                     return;
@@ -381,7 +407,7 @@ impl<'a> SSDPServer<'a> {
 
         loop {
             logger.write_log(
-                "SSDP: Sending Alive to Multicast Address...",
+                "Sending Alive to Multicast Address...",
                 LogLevel::VERBOSE,
             );
 
@@ -400,7 +426,7 @@ impl<'a> SSDPServer<'a> {
                 Ok(_) => {}
                 Err(_) => {
                     logger.write_log(
-                        "SSDP: Unable to send RootDevice Alive to Multicast Address...",
+                        "Unable to send RootDevice Alive to Multicast Address...",
                         LogLevel::ERROR,
                     );
                     return;
@@ -422,7 +448,7 @@ impl<'a> SSDPServer<'a> {
                 Ok(_) => {}
                 Err(_) => {
                     logger.write_log(
-                        "SSDP: Unable to send UUID Alive to Multicast Address...",
+                        "Unable to send UUID Alive to Multicast Address...",
                         LogLevel::ERROR,
                     );
                     return;
@@ -444,7 +470,7 @@ impl<'a> SSDPServer<'a> {
                 Ok(_) => {}
                 Err(_) => {
                     logger.write_log(
-                        "SSDP: Unable to send MediaServer Alive to Multicast Address...",
+                        "Unable to send MediaServer Alive to Multicast Address...",
                         LogLevel::ERROR,
                     );
                     return;
@@ -469,7 +495,7 @@ impl<'a> SSDPServer<'a> {
                 Ok(_) => {}
                 Err(_) => {
                     logger.write_log(
-                        "SSDP: Unable to send ContentDirectory Alive to Multicast Address...",
+                        "Unable to send ContentDirectory Alive to Multicast Address...",
                         LogLevel::ERROR,
                     );
                     return;
@@ -494,7 +520,7 @@ impl<'a> SSDPServer<'a> {
                 Ok(_) => {}
                 Err(_) => {
                     logger.write_log(
-                        "SSDP: Unable to send ConnectionManager Alive to Multicast Address...",
+                        "Unable to send ConnectionManager Alive to Multicast Address...",
                         LogLevel::ERROR,
                     );
                     return;
@@ -502,15 +528,16 @@ impl<'a> SSDPServer<'a> {
             };
 
             logger.write_log(
-                "SSDP: Waiting 180s to resend Alive Packages...",
-                LogLevel::ERROR,
+                "Waiting 180s to resend Alive Packages...",
+                LogLevel::DEBUG,
             );
 
             thread::sleep(time::Duration::from_secs(180));
         }
     }
 
-    /// Send answers to Search Requests
+    /// Send answers to Search Requests. Returns true if a Response
+    /// was sent.
     ///
     /// # Arguments
     ///
@@ -521,6 +548,7 @@ impl<'a> SSDPServer<'a> {
     /// * `ip` - IP Address of the Server
     /// * `port` - Port the Server listens on
     /// * `tag` - The Servers HTTP Server Header Tag
+    /// * `logger` - Logger to use
     fn send_search_response(
         socket: UdpSocket,
         receiver: SocketAddr,
@@ -530,7 +558,7 @@ impl<'a> SSDPServer<'a> {
         port: &str,
         tag: &str,
         logger: Logger,
-    ) {
+    ) -> bool {
         let mut message = String::new();
 
         if request.find("ssdp:all").is_some() {
@@ -612,20 +640,21 @@ impl<'a> SSDPServer<'a> {
 
         if message.len() > 0 {
             logger.write_log(
-                &format!("SSDP: Sending Search Response to {}", receiver),
+                &format!("Sending Search Response to {}", receiver),
                 LogLevel::VERBOSE,
             );
             match socket.send_to(message.as_bytes(), receiver) {
-                Ok(_) => {}
+                Ok(_) => return true,
                 Err(_) => {
                     logger.write_log(
-                        &format!("SSDP: Unable to send Search Response to {}...", receiver),
+                        &format!("Unable to send Search Response to {}...", receiver),
                         LogLevel::ERROR,
                     );
-                    return;
                 }
             };
         }
+
+        false
     }
 
     /// Generate a Notify Package for sending over UDP
@@ -0,0 +1,122 @@
+use std::net::IpAddr;
+use std::sync::{Mutex, MutexGuard};
+use std::time;
+
+/// # ActiveStream
+///
+/// A Media Stream currently sent to a Client
+#[derive(Clone)]
+pub struct ActiveStream {
+    pub id: u64, // Number identifying the Stream while it is running
+    pub address: IpAddr, // IP Address of the Client
+    pub item_id: u64, // Id of the streamed Item
+    pub file_path: String, // Path of the streamed File
+    pub started: u64, // UNIX Timestamp the Stream was started at
+}
+
+/// The running Streams and the Id of the next one
+struct StreamList {
+    streams: Vec<ActiveStream>,
+    next_id: u64,
+}
+
+/// # StreamLimits
+///
+/// Keeps track of the Media Streams currently sent, so a
+/// single Renderer can not use up the Bandwidth and Workers
+/// of the whole Server and the running Streams can be listed.
+pub struct StreamLimits {
+    list: Mutex<StreamList>,
+}
+
+/// # StreamGuard
+///
+/// A Slot taken from the StreamLimits. The Slot is given back
+/// as soon as the Guard is dropped.
+pub struct StreamGuard<'a> {
+    limits: &'a StreamLimits,
+    id: u64,
+}
+
+impl StreamLimits {
+    /// Creates new Limits without any running Stream
+    pub fn new() -> StreamLimits {
+        StreamLimits {
+            list: Mutex::new(StreamList {
+                streams: Vec::new(),
+                next_id: 1,
+            }),
+        }
+    }
+
+    /// Takes a Slot for a new Stream of the Client. Returns None if
+    /// either the Server or the Client already reached its Limit.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - IP Address of the Client
+    /// * `item_id` - Id of the streamed Item
+    /// * `file_path` - Path of the streamed File
+    /// * `max_streams` - Largest Number of Streams in total
+    /// * `max_streams_per_client` - Largest Number of Streams per Client
+    pub fn try_acquire(
+        &self,
+        address: IpAddr,
+        item_id: u64,
+        file_path: &str,
+        max_streams: usize,
+        max_streams_per_client: usize,
+    ) -> Option<StreamGuard<'_>> {
+        let mut list = self.lock();
+        let count = list.streams.iter().filter(|stream| stream.address == address).count();
+
+        if list.streams.len() >= max_streams || count >= max_streams_per_client {
+            return None;
+        }
+
+        let id = list.next_id;
+        list.next_id += 1;
+
+        list.streams.push(ActiveStream {
+            id,
+            address,
+            item_id,
+            file_path: file_path.to_string(),
+            started: match time::SystemTime::now().duration_since(time::UNIX_EPOCH) {
+                Ok(value) => value.as_secs(),
+                Err(_) => 0,
+            },
+        });
+
+        Some(StreamGuard { limits: self, id })
+    }
+
+    /// Returns the currently running Streams
+    pub fn get_streams(&self) -> Vec<ActiveStream> {
+        self.lock().streams.clone()
+    }
+
+    /// Gives the Slot of a Stream back
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Id of the finished Stream
+    fn release(&self, id: u64) {
+        self.lock().streams.retain(|stream| stream.id != id);
+    }
+
+    /// Locks the List. A Stream that panicked can not leave it in
+    /// an inconsistent State, so Poisoning is ignored.
+    fn lock(&self) -> MutexGuard<'_, StreamList> {
+        match self.list.lock() {
+            Ok(value) => value,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+impl<'a> Drop for StreamGuard<'a> {
+    fn drop(&mut self) {
+        self.limits.release(self.id);
+    }
+}
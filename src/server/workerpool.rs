@@ -0,0 +1,97 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+
+/// A Job executed by one of the Workers
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// # WorkerPool
+///
+/// A fixed Number of Threads working off a Queue of Jobs. The
+/// Pool counts the queued and running Jobs, so the Caller can
+/// refuse new Work once it is saturated instead of piling up
+/// Threads.
+pub struct WorkerPool {
+    sender: Sender<Job>,
+    pending: Arc<AtomicUsize>,
+    capacity: usize,
+}
+
+impl WorkerPool {
+    /// Creates the Pool and starts its Workers
+    ///
+    /// # Arguments
+    ///
+    /// * `workers` - Number of Worker Threads
+    /// * `queue_size` - Number of Jobs that may wait for a free Worker
+    pub fn new(workers: usize, queue_size: usize) -> WorkerPool {
+        let workers = workers.max(1);
+        let (sender, receiver) = channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let pending = Arc::new(AtomicUsize::new(0));
+
+        for _ in 0..workers {
+            let receiver = receiver.clone();
+            let pending = pending.clone();
+
+            thread::spawn(move || {
+                WorkerPool::work(&receiver, &pending);
+            });
+        }
+
+        WorkerPool {
+            sender,
+            pending,
+            capacity: workers + queue_size,
+        }
+    }
+
+    /// Takes Jobs from the Queue until the Pool is dropped
+    ///
+    /// # Arguments
+    ///
+    /// * `receiver` - Receiving End of the Queue shared by all Workers
+    /// * `pending` - Number of queued and running Jobs
+    fn work(receiver: &Mutex<Receiver<Job>>, pending: &AtomicUsize) {
+        loop {
+            // A Worker that panicked while waiting can not break the Queue
+            let job = match receiver.lock() {
+                Ok(value) => value.recv(),
+                Err(poisoned) => poisoned.into_inner().recv(),
+            };
+
+            let job = match job {
+                Ok(value) => value,
+                Err(_) => return,
+            };
+
+            // A panicking Job must neither kill the Worker nor leak its Slot
+            let _ = panic::catch_unwind(AssertUnwindSafe(job));
+
+            pending.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    /// Checks if every Worker is busy and the Queue is full
+    pub fn is_saturated(&self) -> bool {
+        self.pending.load(Ordering::SeqCst) >= self.capacity
+    }
+
+    /// Queues a Job. Returns false if it could not be queued.
+    ///
+    /// # Arguments
+    ///
+    /// * `job` - The Job to execute
+    pub fn execute<F: FnOnce() + Send + 'static>(&self, job: F) -> bool {
+        self.pending.fetch_add(1, Ordering::SeqCst);
+
+        if self.sender.send(Box::new(job)).is_err() {
+            self.pending.fetch_sub(1, Ordering::SeqCst);
+            return false;
+        }
+
+        true
+    }
+}
@@ -0,0 +1,210 @@
+use chrono::Local;
+use std::fs::{File, OpenOptions};
+use std::io::prelude::*;
+use std::io::LineWriter;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use super::JSONValue;
+
+/// # AccessLogFormat
+///
+/// Format the Lines of the Access Log are written in
+#[derive(Copy, Clone, PartialEq)]
+pub enum AccessLogFormat {
+    Combined, // Apache Combined Log Format, followed by Renderer, Range and Duration
+    Json, // One JSON Object per Line
+}
+
+/// # AccessEntry
+///
+/// Everything written to the Access Log about a single Request.
+/// Values unknown, e.g. because the Request could not be read,
+/// are left empty.
+pub struct AccessEntry<'a> {
+    pub client: IpAddr, // Address of the Client
+    pub renderer: &'a str, // Display Name of the matched Renderer Profile
+    pub user_agent: &'a str, // "User-Agent" Header of the Request
+    pub referer: &'a str, // "Referer" Header of the Request
+    pub method: &'a str, // Method of the Request
+    pub path: &'a str, // Requested Path including the Query
+    pub version: &'a str, // HTTP Version of the Request
+    pub range: &'a str, // "Range" Header of the Request
+    pub status: u16, // Status of the Response. 0 if nothing was sent
+    pub bytes: u64, // Bytes sent including the Header
+    pub duration: Duration, // Time it took to answer the Request
+}
+
+/// # AccessLog
+///
+/// Log of every Request answered by the Server, kept apart from
+/// the diagnostic Log so it can be processed by common Tools.
+/// The File is opened with the first Entry, so it is created
+/// with the Permissions of the running Daemon.
+pub struct AccessLog {
+    target: Mutex<AccessTarget>,
+}
+
+/// The File the Access Log is written to
+struct AccessTarget {
+    path: String,
+    format: AccessLogFormat,
+    writer: Option<LineWriter<File>>,
+}
+
+impl AccessLog {
+    /// Creates a new, disabled Access Log
+    pub fn new() -> AccessLog {
+        AccessLog {
+            target: Mutex::new(AccessTarget {
+                path: String::new(),
+                format: AccessLogFormat::Combined,
+                writer: None,
+            }),
+        }
+    }
+
+    /// Sets the File and the Format of the Access Log
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the Access Log File. Empty to disable the Access Log
+    /// * `format` - Format of the Lines
+    pub fn set(&self, path: &str, format: AccessLogFormat) {
+        let mut target = match self.target.lock() {
+            Ok(value) => value,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        target.path = path.to_string();
+        target.format = format;
+        target.writer = None;
+    }
+
+    /// Writes an Entry to the Access Log. Failures are ignored, as
+    /// they must never keep the Server from answering Requests.
+    ///
+    /// # Arguments
+    ///
+    /// * `entry` - The Entry to write
+    pub fn write(&self, entry: &AccessEntry) {
+        let mut target = match self.target.lock() {
+            Ok(value) => value,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        if target.path.is_empty() {
+            return;
+        }
+
+        let line = match target.format {
+            AccessLogFormat::Combined => get_combined_line(entry),
+            AccessLogFormat::Json => get_json_line(entry),
+        };
+
+        if target.writer.is_none() {
+            target.writer = match OpenOptions::new().create(true).append(true).open(&target.path) {
+                Ok(value) => Some(LineWriter::new(value)),
+                Err(_) => return,
+            };
+        }
+
+        if let Some(ref mut writer) = target.writer {
+            writer.write_all(line.as_bytes()).unwrap_or(());
+        }
+    }
+}
+
+/// Formats an Entry in the Combined Log Format, followed by the
+/// Renderer, the Range and the Duration in Milliseconds:
+///
+/// `client - - [time] "method path version" status bytes "referer" "user-agent" "renderer" "range" duration`
+///
+/// # Arguments
+///
+/// * `entry` - The Entry to format
+fn get_combined_line(entry: &AccessEntry) -> String {
+    let request = if entry.method.is_empty() {
+        String::from("-")
+    } else {
+        format!("{} {} {}", entry.method, entry.path, entry.version)
+    };
+
+    format!(
+        "{} - - [{}] \"{}\" {} {} \"{}\" \"{}\" \"{}\" \"{}\" {}\n",
+        entry.client,
+        Local::now().format("%d/%b/%Y:%H:%M:%S %z"),
+        escape(&request),
+        match entry.status {
+            0 => String::from("-"),
+            status => status.to_string(),
+        },
+        entry.bytes,
+        escape(entry.referer),
+        escape(entry.user_agent),
+        escape(entry.renderer),
+        escape(entry.range),
+        entry.duration.as_millis()
+    )
+}
+
+/// Formats an Entry as single JSON Object
+///
+/// # Arguments
+///
+/// * `entry` - The Entry to format
+fn get_json_line(entry: &AccessEntry) -> String {
+    let optional = |value: &str| match value {
+        "" => JSONValue::Null,
+        value => JSONValue::string(value),
+    };
+
+    let json = JSONValue::object(vec![
+        ("time", JSONValue::string(&Local::now().format("%Y-%m-%dT%H:%M:%S%.3f%:z").to_string())),
+        ("client", JSONValue::string(&entry.client.to_string())),
+        ("renderer", optional(entry.renderer)),
+        ("userAgent", optional(entry.user_agent)),
+        ("referer", optional(entry.referer)),
+        ("method", optional(entry.method)),
+        ("path", optional(entry.path)),
+        ("version", optional(entry.version)),
+        (
+            "status",
+            match entry.status {
+                0 => JSONValue::Null,
+                status => JSONValue::unsigned(u64::from(status)),
+            },
+        ),
+        ("range", optional(entry.range)),
+        ("bytes", JSONValue::unsigned(entry.bytes)),
+        ("duration", JSONValue::unsigned(entry.duration.as_millis() as u64)),
+    ]);
+
+    format!("{}\n", json.to_json())
+}
+
+/// Escapes a Value written between Quotes of a Combined Log Line.
+/// Empty Values are written as "-".
+///
+/// # Arguments
+///
+/// * `value` - The Value to escape
+fn escape(value: &str) -> String {
+    if value.is_empty() {
+        return String::from("-");
+    }
+
+    let mut escaped = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if c.is_control() => escaped.push_str(&format!("\\x{:02x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
@@ -0,0 +1,147 @@
+/// # JSONValue
+///
+/// A single Value of a JSON Document. Objects keep the Order
+/// their Members were added in, so the generated Documents
+/// always look the same.
+///
+/// ```
+/// use tools::JSONValue;
+///
+/// let value = JSONValue::object(vec![
+///     ("id", JSONValue::unsigned(1)),
+///     ("title", JSONValue::string("Song")),
+/// ]);
+/// let json: String = value.to_json();
+/// ```
+#[derive(Clone)]
+pub enum JSONValue {
+    Null,
+    Bool(bool),
+    Integer(i64),
+    Number(f64),
+    String(String),
+    Array(Vec<JSONValue>),
+    Object(Vec<(String, JSONValue)>),
+}
+
+impl JSONValue {
+    /// Creates an Object from the given Members
+    ///
+    /// # Arguments
+    ///
+    /// * `members` - Names and Values of the Members
+    pub fn object(members: Vec<(&str, JSONValue)>) -> JSONValue {
+        JSONValue::Object(
+            members
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value))
+                .collect(),
+        )
+    }
+
+    /// Creates a String Value
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The Text
+    pub fn string(value: &str) -> JSONValue {
+        JSONValue::String(value.to_string())
+    }
+
+    /// Creates a Number Value from an unsigned Integer
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The Number
+    pub fn unsigned(value: u64) -> JSONValue {
+        JSONValue::Integer(value.min(i64::MAX as u64) as i64)
+    }
+
+    /// Creates an Array of Strings
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The Texts
+    pub fn strings(values: &[String]) -> JSONValue {
+        JSONValue::Array(values.iter().map(|value| JSONValue::string(value)).collect())
+    }
+
+    /// Serializes the Value into a compact JSON Document
+    pub fn to_json(&self) -> String {
+        let mut json = String::new();
+        self.write(&mut json);
+        json
+    }
+
+    /// Appends the serialized Value to the given String
+    ///
+    /// # Arguments
+    ///
+    /// * `json` - String to append to
+    fn write(&self, json: &mut String) {
+        match *self {
+            JSONValue::Null => json.push_str("null"),
+            JSONValue::Bool(value) => json.push_str(if value { "true" } else { "false" }),
+            JSONValue::Integer(value) => json.push_str(&value.to_string()),
+            JSONValue::Number(value) => {
+                // JSON knows neither NaN nor Infinity
+                if value.is_finite() {
+                    json.push_str(&value.to_string());
+                } else {
+                    json.push_str("null");
+                }
+            }
+            JSONValue::String(ref value) => write_string(value, json),
+            JSONValue::Array(ref values) => {
+                json.push('[');
+
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        json.push(',');
+                    }
+                    value.write(json);
+                }
+
+                json.push(']');
+            }
+            JSONValue::Object(ref members) => {
+                json.push('{');
+
+                for (index, (name, value)) in members.iter().enumerate() {
+                    if index > 0 {
+                        json.push(',');
+                    }
+                    write_string(name, json);
+                    json.push(':');
+                    value.write(json);
+                }
+
+                json.push('}');
+            }
+        }
+    }
+}
+
+/// Appends the given Text as quoted and escaped JSON String
+///
+/// # Arguments
+///
+/// * `value` - The Text
+/// * `json` - String to append to
+fn write_string(value: &str, json: &mut String) {
+    json.push('"');
+
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+
+    json.push('"');
+}
@@ -1,6 +1,23 @@
-use chrono::Local;
-use std::fs::OpenOptions;
+use chrono::{Datelike, Local};
+use libc;
+use std::ffi::CString;
+use std::fs::{self, File, OpenOptions};
 use std::io::prelude::*;
+use std::io::BufWriter;
+use std::os::unix::net::UnixDatagram;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, RwLock, RwLockWriteGuard};
+use std::thread;
+use std::time::Duration;
+
+/// Socket journald receives native Log Entries on
+const JOURNALD_SOCKET: &str = "/run/systemd/journal/socket";
+
+/// Name the Server identifies itself with in syslog and journald
+const IDENTIFIER: &[u8] = b"slms\0";
+
+/// Interval buffered Log Entries are written to the File in
+const FLUSH_INTERVAL: u64 = 1;
 
 /// Enumaration to use different Log Levels
 #[derive(Copy, Clone)]
@@ -12,74 +29,454 @@ pub enum LogLevel {
     VERBOSE = 4,
 }
 
+impl LogLevel {
+    /// Converts the Number used inside the Configuration into a LogLevel.
+    /// Numbers above 4 are treated as VERBOSE.
+    ///
+    /// # Arguments
+    ///
+    /// * `number` - The Log Level as Number
+    pub fn from_number(number: u8) -> LogLevel {
+        match number {
+            0 => LogLevel::OFF,
+            1 => LogLevel::INFORMATION,
+            2 => LogLevel::ERROR,
+            3 => LogLevel::DEBUG,
+            _ => LogLevel::VERBOSE,
+        }
+    }
+
+    /// Returns the Name written in front of every Log Entry
+    fn get_name(self) -> &'static str {
+        match self {
+            LogLevel::OFF => "OFF",
+            LogLevel::INFORMATION => "INFO",
+            LogLevel::ERROR => "ERROR",
+            LogLevel::DEBUG => "DEBUG",
+            LogLevel::VERBOSE => "VERBOSE",
+        }
+    }
+
+    /// Returns the syslog Priority of the Level
+    fn get_priority(self) -> libc::c_int {
+        match self {
+            LogLevel::ERROR => libc::LOG_ERR,
+            LogLevel::INFORMATION | LogLevel::OFF => libc::LOG_INFO,
+            LogLevel::DEBUG | LogLevel::VERBOSE => libc::LOG_DEBUG,
+        }
+    }
+}
+
+/// # LogModule
+///
+/// The Subsystem a Log Entry belongs to. Every Subsystem can
+/// have its own Log Level.
+#[derive(Copy, Clone, PartialEq)]
+pub enum LogModule {
+    Server, // Everything not belonging to one of the others
+    Ssdp, // Discovery and Announcements
+    Database, // Media Database and Scanner
+    Http, // Connections, Requests and Streams
+    Upnp, // ContentDirectory and ConnectionManager
+}
+
+impl LogModule {
+    /// Returns the Name written into every Log Entry
+    pub fn get_name(self) -> &'static str {
+        match self {
+            LogModule::Server => "SERVER",
+            LogModule::Ssdp => "SSDP",
+            LogModule::Database => "DB",
+            LogModule::Http => "HTTP",
+            LogModule::Upnp => "UPNP",
+        }
+    }
+}
+
+/// # LogOutput
+///
+/// Where Log Entries are written to
+#[derive(Copy, Clone, PartialEq)]
+pub enum LogOutput {
+    File, // The Log File when running as Daemon, the Console otherwise
+    Syslog, // The local syslog Daemon
+    Journald, // The systemd Journal, keeping the Fields of every Entry
+}
+
+/// Settings of the Logger shared by all its Clones
+struct LogSettings {
+    path: String,
+    level: LogLevel,
+    module_levels: Vec<(LogModule, LogLevel)>,
+    daemonize: bool,
+    output: LogOutput,
+    rotate_size: u64,
+    rotate_daily: bool,
+    retention: usize,
+}
+
+/// The opened Log File and what is needed to rotate it
+struct LogFile {
+    writer: Option<BufWriter<File>>,
+    size: u64,
+    day: i32,
+    journal: Option<UnixDatagram>,
+    syslog_opened: bool,
+}
+
+/// State shared by all Clones of a Logger
+struct LogWriter {
+    settings: RwLock<LogSettings>,
+    file: Mutex<LogFile>,
+    flusher_running: AtomicBool,
+}
+
 /// # Logger
 ///
-/// This Structure is designed do provide
-/// simple Logging Capabilities.
+/// This Structure is designed do provide simple Logging
+/// Capabilities. All Clones write through the same buffered
+/// Writer, so they can be handed to other Threads freely.
+/// A Clone can be bound to a Subsystem using for_module().
 ///
-/// # TO-DO
-/// - Make it thread safe
-/// - Remove Unwrap etc.
+/// The Log File is rotated by Size and / or Day if requested.
+/// Log Entries can also be sent to syslog or journald.
 #[derive(Clone)]
 pub struct Logger {
-    pub logfile_path: String,
-    pub log_level: LogLevel,
-    pub daemonize: bool,
+    module: LogModule,
+    writer: Arc<LogWriter>,
 }
 
 impl Logger {
     pub fn new() -> Logger {
         Logger {
-            logfile_path: String::from("/var/log/slms.log"),
-            log_level: LogLevel::INFORMATION,
-            daemonize: true,
+            module: LogModule::Server,
+            writer: Arc::new(LogWriter {
+                settings: RwLock::new(LogSettings {
+                    path: String::from("/var/log/slms.log"),
+                    level: LogLevel::INFORMATION,
+                    module_levels: Vec::new(),
+                    daemonize: true,
+                    output: LogOutput::File,
+                    rotate_size: 0,
+                    rotate_daily: false,
+                    retention: 5,
+                }),
+                file: Mutex::new(LogFile {
+                    writer: None,
+                    size: 0,
+                    day: 0,
+                    journal: None,
+                    syslog_opened: false,
+                }),
+                flusher_running: AtomicBool::new(false),
+            }),
         }
     }
 
-    /// Create a new Instance of the Logger with the given
-    /// LogLevel and Path to the File to create.
-    pub fn set(&mut self, log_path: &str, log_level: u8, daemonize: bool) {
+    /// Sets the LogLevel and Path to the File to create. Applies to
+    /// all Clones of the Logger.
+    pub fn set(&self, log_path: &str, log_level: u8, daemonize: bool) {
+        let mut settings = self.get_settings_mut();
+        settings.path = log_path.to_string();
+        settings.level = LogLevel::from_number(log_level);
+        settings.daemonize = daemonize;
 
-        let l_level = match log_level {
-            0 => LogLevel::OFF,
-            1 => LogLevel::INFORMATION,
-            2 => LogLevel::ERROR,
-            3 => LogLevel::DEBUG,
-            _ => LogLevel::VERBOSE,
-        };
+        // The next Entry opens the new File
+        self.get_file().writer = None;
+    }
+
+    /// Overrides the LogLevel for a single Subsystem
+    ///
+    /// # Arguments
+    ///
+    /// * `module` - The Subsystem
+    /// * `log_level` - Log Level to use for it
+    pub fn set_module_level(&self, module: LogModule, log_level: u8) {
+        let mut settings = self.get_settings_mut();
+        settings.module_levels.retain(|entry| entry.0 != module);
+        settings.module_levels.push((module, LogLevel::from_number(log_level)));
+    }
+
+    /// Sets where the Log Entries are written to
+    ///
+    /// # Arguments
+    ///
+    /// * `output` - The Output to use
+    pub fn set_output(&self, output: LogOutput) {
+        self.get_settings_mut().output = output;
+    }
+
+    /// Sets when the Log File is rotated and how many old Files are kept
+    ///
+    /// # Arguments
+    ///
+    /// * `max_size` - Size in Bytes the File is rotated at. 0 to not rotate by Size
+    /// * `daily` - Rotate the File every Day?
+    /// * `retention` - Number of rotated Files to keep
+    pub fn set_rotation(&self, max_size: u64, daily: bool, retention: usize) {
+        let mut settings = self.get_settings_mut();
+        settings.rotate_size = max_size;
+        settings.rotate_daily = daily;
+        settings.retention = retention;
+    }
 
-        self.logfile_path = log_path.to_string();
-        self.log_level = l_level;
-        self.daemonize = daemonize;
+    /// Returns a Clone writing its Entries for the given Subsystem
+    ///
+    /// # Arguments
+    ///
+    /// * `module` - The Subsystem
+    pub fn for_module(&self, module: LogModule) -> Logger {
+        Logger {
+            module,
+            writer: self.writer.clone(),
+        }
     }
 
     /// Writes a new line containing the message to the log file.
     /// An Output will happen only if the log level of the message
     /// is inside of the one set in the logger.
     pub fn write_log(&self, message: &str, log_level: LogLevel) {
-        if log_level as u8 <= self.log_level as u8 {
-            let now = Local::now();
-
-            if self.daemonize {
-                let mut file = match OpenOptions::new()
-                    .create(true)
-                    .write(true)
-                    .append(true)
-                    .open(&self.logfile_path) {
-                    Ok(value) => value,
-                    Err(_) => {
-                        println!("{}: {}", now.format("%d.%m.%Y %H:%M:%S.%f"), message);
-                        return;
-                    }
-                };
+        self.write_fields(message, log_level, &[]);
+    }
+
+    /// Writes a new Log Entry with additional Key / Value Fields,
+    /// e.g. the Address of the Client or the Id of an Item.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The Message
+    /// * `log_level` - Log Level of the Entry
+    /// * `fields` - Names and Values of the Fields
+    pub fn write_fields(&self, message: &str, log_level: LogLevel, fields: &[(&str, &str)]) {
+        let settings = match self.writer.settings.read() {
+            Ok(value) => value,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        let max_level = settings
+            .module_levels
+            .iter()
+            .find(|entry| entry.0 == self.module)
+            .map(|entry| entry.1)
+            .unwrap_or(settings.level);
+
+        if log_level as u8 == 0 || log_level as u8 > max_level as u8 {
+            return;
+        }
 
-                match writeln!(file, "{}: {}", now.format("%d.%m.%Y %H:%M:%S.%f"), message) {
-                    Err(_) => println!("{}: {}", now.format("%d.%m.%Y %H:%M:%S.%f"), message),
-                    Ok(_) => {}
+        let now = Local::now();
+        let mut line = format!(
+            "{}: {} [{}] {}",
+            now.format("%d.%m.%Y %H:%M:%S.%f"),
+            log_level.get_name(),
+            self.module.get_name(),
+            message
+        );
+
+        for &(name, value) in fields {
+            line.push_str(&format!(" {}={}", name, quote_value(value)));
+        }
+
+        match settings.output {
+            LogOutput::Syslog if self.write_syslog(&line, log_level) => (),
+            LogOutput::Journald if self.write_journal(message, log_level, fields) => (),
+            _ if settings.daemonize => {
+                if !self.write_file(&settings, &line, now.num_days_from_ce(), log_level) {
+                    println!("{}", line);
                 }
-            } else {
-                println!("{}: {}", now.format("%d.%m.%Y %H:%M:%S.%f"), message);
             }
+            _ => println!("{}", line),
+        }
+    }
+
+    /// Writes everything buffered to the Log File
+    pub fn flush(&self) {
+        if let Some(ref mut writer) = self.get_file().writer {
+            let _ = writer.flush();
         }
     }
+
+    /// Appends a Line to the Log File and rotates it if needed.
+    /// Returns false if the File can not be written.
+    fn write_file(&self, settings: &LogSettings, line: &str, day: i32, log_level: LogLevel) -> bool {
+        let mut file = self.get_file();
+        let length = line.len() as u64 + 1;
+
+        let rotate = file.writer.is_some() &&
+            ((settings.rotate_size > 0 && file.size + length > settings.rotate_size) ||
+                 (settings.rotate_daily && file.day != day));
+
+        if rotate {
+            if let Some(mut writer) = file.writer.take() {
+                let _ = writer.flush();
+            }
+            rotate_files(&settings.path, settings.retention);
+        }
+
+        if file.writer.is_none() {
+            let opened = match OpenOptions::new().create(true).append(true).open(&settings.path) {
+                Ok(value) => value,
+                Err(_) => return false,
+            };
+
+            file.size = opened.metadata().map(|value| value.len()).unwrap_or(0);
+            file.day = day;
+            file.writer = Some(BufWriter::new(opened));
+        }
+
+        let written = match file.writer {
+            Some(ref mut writer) => {
+                let mut result = writeln!(writer, "{}", line);
+
+                // Errors should not get lost if the Server crashes
+                if result.is_ok() {
+                    if let LogLevel::ERROR = log_level {
+                        result = writer.flush();
+                    }
+                }
+
+                result.is_ok()
+            }
+            None => false,
+        };
+
+        if !written {
+            file.writer = None;
+            return false;
+        }
+
+        file.size += length;
+        drop(file);
+        self.start_flusher();
+
+        true
+    }
+
+    /// Sends the Entry to the local syslog Daemon. Returns false if
+    /// the Message can not be converted.
+    fn write_syslog(&self, line: &str, log_level: LogLevel) -> bool {
+        let message = match CString::new(line) {
+            Ok(value) => value,
+            Err(_) => return false,
+        };
+
+        let mut file = self.get_file();
+
+        unsafe {
+            if !file.syslog_opened {
+                libc::openlog(IDENTIFIER.as_ptr() as *const libc::c_char, libc::LOG_PID, libc::LOG_DAEMON);
+                file.syslog_opened = true;
+            }
+
+            libc::syslog(
+                log_level.get_priority(),
+                b"%s\0".as_ptr() as *const libc::c_char,
+                message.as_ptr(),
+            );
+        }
+
+        true
+    }
+
+    /// Sends the Entry including its Fields to journald using its
+    /// native Protocol. Returns false if journald is not reachable.
+    fn write_journal(&self, message: &str, log_level: LogLevel, fields: &[(&str, &str)]) -> bool {
+        let mut entry = format!(
+            "PRIORITY={}\nSYSLOG_IDENTIFIER=slms\nSLMS_MODULE={}\nMESSAGE={}\n",
+            log_level.get_priority(),
+            self.module.get_name(),
+            message.replace('\n', " ")
+        );
+
+        for &(name, value) in fields {
+            entry.push_str(&format!(
+                "{}={}\n",
+                name.to_uppercase().replace(|c: char| !c.is_ascii_alphanumeric(), "_"),
+                value.replace('\n', " ")
+            ));
+        }
+
+        let mut file = self.get_file();
+
+        if file.journal.is_none() {
+            file.journal = UnixDatagram::unbound().ok();
+        }
+
+        let sent = match file.journal {
+            Some(ref socket) => socket.send_to(entry.as_bytes(), JOURNALD_SOCKET).is_ok(),
+            None => false,
+        };
+
+        if !sent {
+            file.journal = None;
+        }
+
+        sent
+    }
+
+    /// Starts the Thread writing buffered Entries to the File regularly.
+    /// It is started with the first Entry, so it survives daemonizing.
+    fn start_flusher(&self) {
+        if self.writer.flusher_running.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let logger = self.clone();
+        thread::spawn(move || loop {
+            thread::sleep(Duration::from_secs(FLUSH_INTERVAL));
+            logger.flush();
+        });
+    }
+
+    /// Locks the Log File. A Thread that panicked while writing
+    /// does not stop the others from logging.
+    fn get_file(&self) -> MutexGuard<'_, LogFile> {
+        match self.writer.file.lock() {
+            Ok(value) => value,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    /// Locks the Settings for Changes
+    fn get_settings_mut(&self) -> RwLockWriteGuard<'_, LogSettings> {
+        match self.writer.settings.write() {
+            Ok(value) => value,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+/// Renames the Log File to path.1, path.1 to path.2 and so on.
+/// Files beyond the Retention are deleted.
+///
+/// # Arguments
+///
+/// * `path` - Path of the Log File
+/// * `retention` - Number of rotated Files to keep
+fn rotate_files(path: &str, retention: usize) {
+    if retention == 0 {
+        let _ = fs::remove_file(path);
+        return;
+    }
+
+    let _ = fs::remove_file(format!("{}.{}", path, retention));
+
+    for index in (1..retention).rev() {
+        let _ = fs::rename(format!("{}.{}", path, index), format!("{}.{}", path, index + 1));
+    }
+
+    let _ = fs::rename(path, format!("{}.1", path));
+}
+
+/// Quotes the Value of a Field if it contains Spaces, Quotes or "="
+///
+/// # Arguments
+///
+/// * `value` - Value of the Field
+fn quote_value(value: &str) -> String {
+    if value.is_empty() || value.contains(|c: char| c.is_whitespace() || c == '"' || c == '=') {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        value.to_string()
+    }
 }
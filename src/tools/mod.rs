@@ -8,3 +8,13 @@ pub use self::xmlparser::XMLEntry;
 mod logger;
 pub use self::logger::Logger;
 pub use self::logger::LogLevel;
+pub use self::logger::LogModule;
+pub use self::logger::LogOutput;
+
+mod json;
+pub use self::json::JSONValue;
+
+mod accesslog;
+pub use self::accesslog::AccessLog;
+pub use self::accesslog::AccessEntry;
+pub use self::accesslog::AccessLogFormat;
@@ -223,7 +223,7 @@ impl XMLParser {
                     if end_position >= content.len() || tag_end_position >= content.len() {
                         break;
                     }
-                    result.value = content[end_position..tag_end_position].trim().to_string();
+                    result.value = XMLParser::unescape(content[end_position..tag_end_position].trim());
                 }
 
                 start_position = tag_end_position + 3 + result.tag.len();
@@ -313,7 +313,8 @@ impl XMLParser {
 
                 value = &value[..sub_position];
 
-                let nm_pair: NameValuePair = NameValuePair::new(name.trim(), value.trim());
+                let nm_pair: NameValuePair =
+                    NameValuePair::new(name.trim(), &XMLParser::unescape(value.trim()));
 
                 result.push(nm_pair);
             }
@@ -357,7 +358,7 @@ impl XMLParser {
         for attr in attributes {
             self.xml_content += &attr.name;
             self.xml_content += "=\"";
-            self.xml_content += &attr.value;
+            self.xml_content += &XMLParser::escape(&attr.value);
             self.xml_content += "\" ";
         }
 
@@ -404,6 +405,80 @@ impl XMLParser {
         return entry;
     }
 
+    /// Replaces the Characters with a special Meaning inside XML by
+    /// their Entities, so the Value can be used as Text or Attribute.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The Text to escape
+    pub fn escape(value: &str) -> String {
+        let mut escaped = String::with_capacity(value.len());
+
+        for c in value.chars() {
+            match c {
+                '&' => escaped.push_str("&amp;"),
+                '<' => escaped.push_str("&lt;"),
+                '>' => escaped.push_str("&gt;"),
+                '"' => escaped.push_str("&quot;"),
+                '\'' => escaped.push_str("&apos;"),
+                c => escaped.push(c),
+            }
+        }
+
+        escaped
+    }
+
+    /// Replaces the predefined Entities and numeric Character
+    /// References by their Characters. Anything else, e.g. a single
+    /// "&" written by an older Version, is kept as it is.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The Text to unescape
+    pub fn unescape(value: &str) -> String {
+        let mut unescaped = String::with_capacity(value.len());
+        let mut rest = value;
+
+        while let Some(position) = rest.find('&') {
+            unescaped.push_str(&rest[..position]);
+            rest = &rest[position..];
+
+            let entity = match rest.find(';') {
+                Some(end) if end <= 10 => &rest[1..end],
+                _ => "",
+            };
+
+            let character = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                _ => {
+                    let number = match entity.strip_prefix("#x") {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                        None => entity.strip_prefix('#').and_then(|dec| dec.parse::<u32>().ok()),
+                    };
+                    number.and_then(std::char::from_u32)
+                }
+            };
+
+            match character {
+                Some(c) => {
+                    unescaped.push(c);
+                    rest = &rest[entity.len() + 2..];
+                }
+                None => {
+                    unescaped.push('&');
+                    rest = &rest[1..];
+                }
+            }
+        }
+
+        unescaped.push_str(rest);
+        unescaped
+    }
+
     /// This function takes a list of Name-Value Pairs and returns the
     /// Value of the Pair where the name matches the given one.
     pub fn get_value_from_name(attr_list: &Vec<NameValuePair>, name: &str) -> String {
@@ -1,5 +1,5 @@
 use std::io::prelude::*;
-use std::net::TcpStream;
+use provider::{Connection, Request};
 
 use configuration::ServerConfiguration;
 
@@ -34,8 +34,8 @@ impl<'a> ConnectionManager<'a> {
     /// # Arguments
     ///
     /// * `response` - Message to be send
-    /// * `stream` - TCP Stream to send the data to
-    pub fn send_data(&self, response: &str, stream: &mut TcpStream) -> bool {
+    /// * `stream` - Connection to send the data to
+    pub fn send_data(&self, response: &str, stream: &mut Connection) -> bool {
 
         match stream.write_all(response.as_bytes()) {
             Ok(_) => {
@@ -48,107 +48,6 @@ impl<'a> ConnectionManager<'a> {
         }
     }
 
-    /// Uses the Tcp Stream to collect the whole Request including Header
-    /// and Content.
-    ///
-    /// # Arguments
-    ///
-    /// * `stream` - Stream to read Data from
-    pub fn handle_connection(&self, stream: &mut TcpStream) -> String {
-        let mut buffer = [0; 65515];
-        let mut content_size: usize = 0;
-        let mut content_size_expected: usize = 0;
-        let mut content: String = String::new();
-        let mut header_received: bool = false;
-        let mut header_content: String = String::new();
-
-        loop {
-            let readed = match stream.read(&mut buffer) {
-                Ok(bytes) => bytes,
-                Err(_) => {
-                    break;
-                }
-            };
-
-            if readed == 0 {
-                break;
-            }
-
-            let tmp_content: String = match String::from_utf8(buffer[..readed].to_vec()) {
-                Ok(value) => value,
-                Err(_) => break,
-            };
-
-            if header_received {
-                content.push_str(&tmp_content);
-                content_size += readed;
-
-                if content_size >= content_size_expected {
-                    break;
-                }
-            } else {
-                content.push_str(&tmp_content);
-
-                match content.find("\r\n\r\n") {
-                    Some(position) => {
-                        // Get Content Length
-                        if position + 4 > content.len() {
-                            break;
-                        }
-
-                        let mut header = content[..position].to_lowercase();
-                        header_content = content[..(position + 4)].to_string();
-
-                        match header.find("content-length:") {
-                            Some(content_pos) => {
-                                if content_pos + 16 > header.len() {
-                                    break;
-                                }
-
-                                header = header[(content_pos + 16)..].to_string();
-                                match header.find("\r\n") {
-                                    Some(inner_pos) => {
-                                        header = header[..inner_pos].to_string();
-                                    }
-                                    _ => {}
-                                }
-
-                                content_size_expected = match header.trim().parse::<usize>() {
-                                    Ok(value) => value,
-                                    Err(_) => break,
-                                };
-                            }
-                            None => {
-                                content = String::new();
-                                break;
-                            }
-                        }
-
-                        // Set Header is complete
-                        header_received = true;
-
-                        if position + 4 < content.len() {
-                            content = content[(position + 4)..].to_string();
-                            content_size = content.len();
-                        } else {
-                            content = String::new();
-                            content_size = 0;
-                        }
-
-                        // Recheck if everything is there
-                        if content_size >= content_size_expected {
-                            break;
-                        }
-                    }
-                    _ => {}
-                }
-            }
-        }
-
-        header_content.push_str(&content);
-        return header_content;
-    }
-
     /// Takes a Request and returns the corresponding XML Answer Content.
     /// Content only. No Headers!
     /// Returns an empty String Response could not be generated
@@ -156,24 +55,31 @@ impl<'a> ConnectionManager<'a> {
     /// # Arguments
     ///
     /// * `request` - Request from the Renderer to process
-    pub fn handle_request(&self, request: &str) -> String {
-        if &request[..31] == "GET /connection/description.xml" {
-            return self.get_device_description();
-        } else if &request[..40] == "SUBSCRIBE /connection/connection_manager" {
-            return self.do_subscribe();
-        } else if &request[..38] == "GET /connection/connection_manager.xml" {
-            return self.get_connection_manager_description();
-        } else if &request[..37] == "GET /connection/content_directory.xml" {
-            return self.get_content_directory_description();
-        } else if request.find("u:GetProtocolInfo").is_some() {
-            return self.get_protocoll_info();
-        } else if request.find("u:PrepareForConnection").is_some() {
-            // Still To-Do
-        } else if request.find("u:ConnectionComplete").is_some() {
-            // Still To-Do
-        }
+    pub fn handle_request(&self, request: &Request) -> String {
+        // HEAD Requests get the same Answer, the Content is dropped later on
+        let method = match request.method.as_str() {
+            "HEAD" => "GET",
+            value => value,
+        };
 
-        String::new()
+        match (method, request.path.as_str()) {
+            ("GET", "/connection/description.xml") => self.get_device_description(),
+            ("GET", "/connection/connection_manager.xml") => {
+                self.get_connection_manager_description()
+            }
+            ("GET", "/connection/content_directory.xml") => {
+                self.get_content_directory_description()
+            }
+            ("SUBSCRIBE", "/connection/connection_manager") => self.do_subscribe(),
+            ("POST", "/connection/connection_manager") => {
+                match request.get_soap_action().as_str() {
+                    "GetProtocolInfo" => self.get_protocoll_info(),
+                    // "PrepareForConnection" and "ConnectionComplete" are still To-Do
+                    _ => String::new(),
+                }
+            }
+            _ => String::new(),
+        }
     }
 
     /// Handles the "Subscribe" Request
@@ -241,7 +147,7 @@ impl<'a> ConnectionManager<'a> {
                                         <url>/files/images/icon.png</url>
                                 </icon>
                         </iconList>
-                        <presentationURL>http://{}:{}/console/console.html</presentationURL>
+                        <presentationURL>http://{}:{}/web/</presentationURL>
                         <serviceList>
                                 <service>
                                         <serviceType>urn:schemas-upnp-org:service:ContentDirectory:1</serviceType>
@@ -765,6 +671,31 @@ impl<'a> ConnectionManager<'a> {
 			 </argument>
 			 </argumentList>
 			 </action> 
+			 <action>
+			 <name>X_SetBookmark</name>
+			 <argumentList>
+			 <argument>
+			 <name>CategoryType</name>
+			 <direction>in</direction>
+			 <relatedStateVariable>A_ARG_TYPE_CategoryType</relatedStateVariable>
+			 </argument>
+			 <argument>
+			 <name>RID</name>
+			 <direction>in</direction>
+			 <relatedStateVariable>A_ARG_TYPE_RID</relatedStateVariable>
+			 </argument>
+			 <argument>
+			 <name>ObjectID</name>
+			 <direction>in</direction>
+			 <relatedStateVariable>A_ARG_TYPE_ObjectID</relatedStateVariable>
+			 </argument>
+			 <argument>
+			 <name>PosSecond</name>
+			 <direction>in</direction>
+			 <relatedStateVariable>A_ARG_TYPE_PosSec</relatedStateVariable>
+			 </argument>
+			 </argumentList>
+			 </action>
 			 </actionList>
 			 <serviceStateTable>
 			 <stateVariable sendEvents=\"yes\">
@@ -857,6 +788,18 @@ impl<'a> ConnectionManager<'a> {
 			 <name>ContainerUpdateIDs</name>
 			 <dataType>string</dataType>
 			 </stateVariable> 
+			 <stateVariable sendEvents=\"no\">
+			 <name>A_ARG_TYPE_CategoryType</name>
+			 <dataType>ui4</dataType>
+			 </stateVariable>
+			 <stateVariable sendEvents=\"no\">
+			 <name>A_ARG_TYPE_RID</name>
+			 <dataType>ui4</dataType>
+			 </stateVariable>
+			 <stateVariable sendEvents=\"no\">
+			 <name>A_ARG_TYPE_PosSec</name>
+			 <dataType>ui4</dataType>
+			 </stateVariable>
 			 </serviceStateTable>
 		</scpd>"
             .to_string()
@@ -1,7 +1,17 @@
-use configuration::ConfigurationHandler;
-use database::{DatabaseManager, Folder};
+use chrono::Local;
+use configuration::{ConfigurationHandler, RendererConfiguration};
+use database::{DatabaseManager, Folder, MediaDatabase, Viewer};
 use tools::{XMLParser, NameValuePair};
+use std::cmp::Ordering;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use media::Item;
+use provider::Request;
+
+/// Properties the Browse Results can be sorted by
+const SORT_CAPABILITIES: &str = "dc:title,dc:date,upnp:album,upnp:artist,upnp:genre,upnp:originalTrackNumber,upnp:originalDiscNumber,upnp:playbackCount,upnp:lastPlaybackTime";
 
 /// # ContentDirectory
 ///
@@ -12,9 +22,12 @@ use media::Item;
 /// Every Content requested will be gathered and provided here.
 pub struct ContentDirectory<'a, 'b> {
     cfg_handler: &'a ConfigurationHandler,
-    db_handler: &'b DatabaseManager,
+    db: &'b MediaDatabase,
+    db_handler: Arc<DatabaseManager>,
     xml_parser: XMLParser,
     system_update_id: u64,
+    client: IpAddr,
+    renderer: Option<&'a RendererConfiguration>,
 }
 
 impl<'a, 'b> ContentDirectory<'a, 'b> {
@@ -24,16 +37,23 @@ impl<'a, 'b> ContentDirectory<'a, 'b> {
     /// # Arguments
     ///
     /// * `cfg_handler` - Configuration Handler that provides any Configuration needed here
-    /// * `db_handler` - Database Handler that provides Media DB Access
+    /// * `db` - Media Database, read from its latest Snapshot and changed by Bookmarks
+    /// * `client` - Address of the Renderer, used to hide Shares it may not access
+    /// * `renderer` - Configuration of the Renderer, None if there is no Renderer at all
     pub fn new(
         cfg_handler: &'a ConfigurationHandler,
-        db_handler: &'b DatabaseManager,
+        db: &'b MediaDatabase,
+        client: IpAddr,
+        renderer: Option<&'a RendererConfiguration>,
     ) -> ContentDirectory<'a, 'b> {
         ContentDirectory {
             cfg_handler: cfg_handler,
-            db_handler: db_handler,
+            db,
+            db_handler: db.read(),
             xml_parser: XMLParser::new(),
             system_update_id: 1,
+            client,
+            renderer,
         }
     }
 
@@ -42,30 +62,36 @@ impl<'a, 'b> ContentDirectory<'a, 'b> {
     ///
     /// # Arguments
     ///
-    /// * `request` - Request received from a Renderer including Header and Content
-    pub fn handle_request(&mut self, request: &str) -> String {
-        if &request[..36] == "SUBSCRIBE /content/content_directory" {
+    /// * `request` - Request received from a Renderer
+    pub fn handle_request(&mut self, request: &Request) -> String {
+        if request.method == "SUBSCRIBE" {
             return self.do_subscribe();
-        } else if request.find("u:GetSearchCapabilities").is_some() {
-            return self.get_search_capabilities();
-        } else if request.find("u:GetSortCapabilities").is_some() {
-            return self.get_sort_capabilities();
-        } else if request.find("u:Browse").is_some() {
-            if request.find("BrowseMetadata").is_some() {
-                return self.browser_direct_child(request);
-            } else {
-                return self.browse(request);
+        } else if request.method != "POST" {
+            return String::new();
+        }
+
+        let body: String = request.get_body_text();
+
+        match request.get_soap_action().as_str() {
+            "GetSearchCapabilities" => self.get_search_capabilities(),
+            "GetSortCapabilities" => self.get_sort_capabilities(),
+            "Browse" => {
+                if self.find_value_from_name(&body, "BrowseFlag") == "BrowseMetadata" {
+                    self.browser_direct_child(&body)
+                } else {
+                    self.browse(&body)
+                }
             }
-        } else if request.find("u:Search").is_some() {
-            if request.find("SearchMetadata").is_some() {
-                return self.search_direct_children();
-            } else {
-                return self.search();
+            "Search" => {
+                if body.contains("SearchMetadata") {
+                    self.search_direct_children()
+                } else {
+                    self.search()
+                }
             }
-        } else if request.find("u:GetSystemUpdateID").is_some() {
-            return self.get_system_update_id();
-        } else {
-            return String::new();
+            "GetSystemUpdateID" => self.get_system_update_id(),
+            "X_SetBookmark" => self.set_bookmark(&body),
+            _ => String::new(),
         }
     }
 
@@ -77,14 +103,16 @@ impl<'a, 'b> ContentDirectory<'a, 'b> {
     /// * `request` - The incoming Request from the Renderer
     fn browse(&mut self, request: &str) -> String {
         // Check if there are renderers
-        if self.cfg_handler.renderer_configurations.len() == 0 {
-            return String::new();
-        }
+        let renderer = match self.renderer {
+            Some(value) => value,
+            None => return String::new(),
+        };
 
         let empty_vec: Vec<NameValuePair> = Vec::new();
         let mut content: String = String::from(
-            "&lt;DIDL-Lite xmlns=\"urn:schemas-upnp-org:metadata-1-0/DIDL-Lite/\" 
-            xmlns:dc=\"http://purl.org/dc/elements/1.1/\" xmlns:upnp=\"urn:schemas-upnp-org:metadata-1-0/upnp/\"&gt;",
+            "<DIDL-Lite xmlns=\"urn:schemas-upnp-org:metadata-1-0/DIDL-Lite/\" 
+            xmlns:dc=\"http://purl.org/dc/elements/1.1/\" xmlns:upnp=\"urn:schemas-upnp-org:metadata-1-0/upnp/\"
+            xmlns:sec=\"http://www.sec.co.kr/\">",
         );
 
         self.xml_parser.start_xml();
@@ -134,8 +162,19 @@ impl<'a, 'b> ContentDirectory<'a, 'b> {
         let mut act_count: u64 = 0;
         let mut item_index: usize = start_index;
 
-        let mut folders: Vec<Folder> = self.db_handler.get_folder_from_parent(id);
-        folders.sort_by(|a, b| a.title.cmp(&b.title));
+        let sort_criteria = parse_sort_criteria(&self.find_value_from_name(request, "SortCriteria"));
+
+        // Folders only have a Title to be sorted by
+        let folders_descending = sort_criteria
+            .iter()
+            .any(|criterion| criterion.0 == "dc:title" && !criterion.1);
+
+        let mut folders: Vec<Folder> = self.db_handler.get_folder_from_parent(id, &self.get_viewer());
+        folders.retain(|folder| self.is_folder_visible(folder));
+        folders.sort_by(|a, b| {
+            let order = a.title.to_lowercase().cmp(&b.title.to_lowercase());
+            if folders_descending { order.reverse() } else { order }
+        });
 
         for index in start_index..folders.len() {
             if act_count < requested_count || requested_count == 0 {
@@ -146,8 +185,13 @@ impl<'a, 'b> ContentDirectory<'a, 'b> {
             }
         }
 
-        let mut items: Vec<Item> = self.db_handler.get_items_from_parent(id);
-        items.sort_by(|a, b| a.meta_data.file_name.cmp(&b.meta_data.file_name));
+        let mut items: Vec<Item> = self.db_handler.get_items_from_parent(id, &self.get_viewer());
+        items.retain(|item| self.is_item_visible(item));
+
+        // Containers like "Recently Played" keep their Order unless another one is requested
+        if !sort_criteria.is_empty() || !self.db_handler.is_ordered_folder(id) {
+            items.sort_by(|a, b| compare_items(a, b, &sort_criteria));
+        }
 
         if act_count > 0 {
             item_index = 0;
@@ -156,12 +200,7 @@ impl<'a, 'b> ContentDirectory<'a, 'b> {
         for index in item_index..items.len() {
             if act_count < requested_count || requested_count == 0 {
                 content.push_str(&items[index].generate_upnp_xml(
-                    match self.cfg_handler
-                        .renderer_configurations
-                        .get(0) {
-                        Some(value) => value,
-                        None => return String::new(),
-                    },
+                    renderer,
                     &self.cfg_handler.server_configuration,
                 ));
                 act_count += 1;
@@ -170,8 +209,8 @@ impl<'a, 'b> ContentDirectory<'a, 'b> {
             }
         }
 
-        content.push_str("&lt;/DIDL-Lite&gt;");
-        self.xml_parser.insert_value(&content);
+        content.push_str("</DIDL-Lite>");
+        self.xml_parser.insert_value(&XMLParser::escape(&content));
 
         self.xml_parser.close_tag("Result");
 
@@ -211,8 +250,9 @@ impl<'a, 'b> ContentDirectory<'a, 'b> {
     fn browser_direct_child(&mut self, request: &str) -> String {
         let empty_vec: Vec<NameValuePair> = Vec::new();
         let mut content: String = String::from(
-            "&lt;DIDL-Lite xmlns=\"urn:schemas-upnp-org:metadata-1-0/DIDL-Lite/\" 
-            xmlns:dc=\"http://purl.org/dc/elements/1.1/\" xmlns:upnp=\"urn:schemas-upnp-org:metadata-1-0/upnp/\"&gt;",
+            "<DIDL-Lite xmlns=\"urn:schemas-upnp-org:metadata-1-0/DIDL-Lite/\" 
+            xmlns:dc=\"http://purl.org/dc/elements/1.1/\" xmlns:upnp=\"urn:schemas-upnp-org:metadata-1-0/upnp/\"
+            xmlns:sec=\"http://www.sec.co.kr/\">",
         );
 
         self.xml_parser.start_xml();
@@ -251,16 +291,18 @@ impl<'a, 'b> ContentDirectory<'a, 'b> {
 
         let mut result_nb = 0;
 
-        match self.db_handler.get_folder_direct(id) {
+        match self.db_handler.get_folder_direct(id, &self.get_viewer()) {
             Ok(folder) => {
-                content.push_str(&folder.generate_upnp_xml());
-                result_nb = 1;
+                if self.is_folder_visible(&folder) {
+                    content.push_str(&folder.generate_upnp_xml());
+                    result_nb = 1;
+                }
             }
             Err(_) => {
                 match self.db_handler.get_item_direct(id) {
-                    Ok(item) => {
+                    Ok(ref item) if self.is_item_visible(item) => {
                         content.push_str(&item.generate_upnp_xml(
-                            match self.cfg_handler.renderer_configurations.get(0) {
+                            match self.renderer {
                                 Some(value) => value,
                                 None => return String::new(),
                             },
@@ -268,13 +310,13 @@ impl<'a, 'b> ContentDirectory<'a, 'b> {
                         ));
                         result_nb = 1;
                     }
-                    Err(_) => {}
+                    _ => {}
                 }
             }
         }
 
-        content.push_str("&lt;/DIDL-Lite&gt;");
-        self.xml_parser.insert_value(&content);
+        content.push_str("</DIDL-Lite>");
+        self.xml_parser.insert_value(&XMLParser::escape(&content));
 
         self.xml_parser.close_tag("Result");
 
@@ -303,6 +345,95 @@ impl<'a, 'b> ContentDirectory<'a, 'b> {
         self.xml_parser.xml_content.clone()
     }
 
+    /// Handles the Samsung X_SetBookmark Action a Renderer sends when
+    /// it stops playing an Item, so it can be resumed later on.
+    /// Returns an empty String if something went wrong.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The incoming Request from a Renderer
+    fn set_bookmark(&mut self, request: &str) -> String {
+        let renderer = match self.renderer {
+            Some(value) => value,
+            None => return String::new(),
+        };
+
+        let id: u64 = match self.find_value_from_name(request, "ObjectID")
+            .parse::<u64>() {
+            Ok(value) => value,
+            Err(_) => return String::new(),
+        };
+        let position: u64 = match self.find_value_from_name(request, "PosSecond")
+            .parse::<u64>() {
+            Ok(value) => value,
+            Err(_) => return String::new(),
+        };
+
+        match self.db_handler.get_item_direct(id) {
+            Ok(ref item) if self.is_item_visible(item) => (),
+            _ => return String::new(),
+        }
+
+        let now = match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(value) => value.as_secs(),
+            Err(_) => return String::new(),
+        };
+        let key = self.cfg_handler.server_configuration.get_bookmark_key(renderer);
+
+        if !self.db.update(|db| db.set_bookmark(id, key, position, now)) {
+            return String::new();
+        }
+
+        self.db.publish();
+        self.db.save_later();
+
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>
+         <s:Envelope xmlns:s=\"http://schemas.xmlsoap.org/soap/envelope/\" s:encodingStyle=\"http://schemas.xmlsoap.org/soap/encoding/\">
+	         <s:Body>
+		         <u:X_SetBookmarkResponse xmlns:u=\"urn:schemas-upnp-org:service:ContentDirectory:1\">
+		         </u:X_SetBookmarkResponse>
+	         </s:Body>
+         </s:Envelope>".to_string()
+    }
+
+    /// Checks if the Folder is shown to the Renderer according to the
+    /// Access Rules of its Share and the Renderers Configuration,
+    /// including its Schedule.
+    ///
+    /// # Arguments
+    ///
+    /// * `folder` - The Folder to check
+    fn is_folder_visible(&self, folder: &Folder) -> bool {
+        self.cfg_handler.server_configuration.is_path_allowed(&folder.path, &self.client) &&
+            self.renderer.is_none_or(|renderer| {
+                renderer.is_folder_visible(&folder.path) && !renderer.is_hidden_by_schedule(&Local::now())
+            })
+    }
+
+    /// Returns the Viewer the Played Containers are generated for,
+    /// which sees the Items shown to the Renderer and its Bookmarks
+    fn get_viewer(&self) -> Viewer<'_> {
+        Viewer {
+            is_visible: Box::new(move |item| self.is_item_visible(item)),
+            bookmark_key: self.renderer
+                .map(|renderer| self.cfg_handler.server_configuration.get_bookmark_key(renderer)),
+        }
+    }
+
+    /// Checks if the Item is shown to the Renderer according to the
+    /// Access Rules of its Share and the Renderers Configuration,
+    /// including its Schedule.
+    ///
+    /// # Arguments
+    ///
+    /// * `item` - The Item to check
+    fn is_item_visible(&self, item: &Item) -> bool {
+        self.cfg_handler.server_configuration.is_path_allowed(&item.file_path, &self.client) &&
+            self.renderer.is_none_or(|renderer| {
+                renderer.is_item_visible(item) && !renderer.is_hidden_by_schedule(&Local::now())
+            })
+    }
+
     /// Perfomrs the Subscribe Action
     ///
     /// # TO-DO
@@ -342,16 +473,14 @@ impl<'a, 'b> ContentDirectory<'a, 'b> {
 
     /// Returns the Sort Capabilities of the Server
     fn get_sort_capabilities(&self) -> String {
-        "<?xml version=\"1.0\" encoding=\"utf-8\"?>
+        format!("<?xml version=\"1.0\" encoding=\"utf-8\"?>
          <s:Envelope xmlns:s=\"http://schemas.xmlsoap.org/soap/envelope/ s:encodingStyle=\"http://schemas.xmlsoap.org/soap/encoding/\"\">
 	         <s:Body>
 		         <u:GetSortCapabilitiesResponse xmlns:u=\"urn:schemas-upnp-org:service:ContentDirectory:1\">
-			         <SortCaps>
-				         *
-			         </SortCaps>
+			         <SortCaps>{}</SortCaps>
 		         </u:GetSortCapabilitiesResponse>
 	         </s:Body>
-         </s:Envelope>".to_string()
+         </s:Envelope>", SORT_CAPABILITIES)
     }
 
     /// Returns the current Update Id of the Content
@@ -382,27 +511,15 @@ impl<'a, 'b> ContentDirectory<'a, 'b> {
         let start_search = format!("<{}>", name);
         let end_search = format!("</{}>", name);
 
-        match request.find(&start_search) {
-            Some(start_position) => {
-                match request.find(&end_search) {
-                    Some(end_position) => {
-                        if start_position + start_search.len() >= request.len() &&
-                            end_position >= request.len()
-                        {
-                            return String::new();
-                        }
-
-                        return request[start_position + start_search.len()..end_position]
-                            .to_string();
-                    }
-                    None => {
-                        return String::new();
-                    }
-                }
-            }
-            None => {
-                return String::new();
-            }
+        let start_position = match request.find(&start_search) {
+            Some(value) => value + start_search.len(),
+            None => return String::new(),
+        };
+
+        // The closing Tag has to follow the opening one
+        match request[start_position..].find(&end_search) {
+            Some(length) => request[start_position..start_position + length].to_string(),
+            None => String::new(),
         }
     }
 
@@ -422,3 +539,50 @@ impl<'a, 'b> ContentDirectory<'a, 'b> {
         String::new()
     }
 }
+
+/// Parses a SortCriteria like "+upnp:album,-dc:date" into a List
+/// of Properties and their Direction (true for ascending).
+/// Unsupported Properties are dropped.
+///
+/// # Arguments
+///
+/// * `value` - SortCriteria of the Browse Request
+fn parse_sort_criteria(value: &str) -> Vec<(String, bool)> {
+    let mut criteria: Vec<(String, bool)> = Vec::new();
+
+    for part in value.split(',') {
+        let part = part.trim();
+
+        let (property, ascending) = match part.strip_prefix('-') {
+            Some(value) => (value, false),
+            None => (part.strip_prefix('+').unwrap_or(part), true),
+        };
+
+        if SORT_CAPABILITIES.split(',').any(|capability| capability == property) {
+            criteria.push((property.to_string(), ascending));
+        }
+    }
+
+    criteria
+}
+
+/// Compares two Items by the given Sort Criteria. Items that are
+/// equal by all Criteria (or if there are none) are compared in
+/// their natural Album / Disc / Track Order.
+///
+/// # Arguments
+///
+/// * `a` - First Item
+/// * `b` - Second Item
+/// * `criteria` - Parsed Sort Criteria
+fn compare_items(a: &Item, b: &Item, criteria: &[(String, bool)]) -> Ordering {
+    for &(ref property, ascending) in criteria {
+        let order = a.compare_by(b, property);
+
+        if order != Ordering::Equal {
+            return if ascending { order } else { order.reverse() };
+        }
+    }
+
+    a.compare(b)
+}
@@ -0,0 +1,2 @@
+mod webinterface;
+pub use self::webinterface::WebInterface;
@@ -0,0 +1,440 @@
+use std::net::IpAddr;
+
+use chrono::Local;
+use configuration::{ConfigurationHandler, RendererConfiguration};
+use database::{DatabaseManager, Folder, Viewer};
+use media::{imagescaler, Item, MediaType};
+
+/// Largest Number of Parent Folders shown in the Navigation
+const MAX_BREADCRUMBS: usize = 64;
+
+/// Style Sheet shared by all Pages
+const STYLE: &str = "body{margin:0;font-family:sans-serif;background:#202124;color:#e8eaed}\
+header{padding:12px 16px;background:#303134;font-size:14px}\
+header a{color:#8ab4f8;text-decoration:none}\
+main{padding:16px}\
+h1{font-size:22px;margin:0 0 16px}\
+ul.grid{list-style:none;margin:0;padding:0;display:grid;grid-template-columns:repeat(auto-fill,minmax(160px,1fr));gap:12px}\
+ul.grid a{display:block;padding:8px;border-radius:6px;background:#303134;color:inherit;text-decoration:none;overflow-wrap:anywhere}\
+ul.grid a:hover{background:#3c4043}\
+.cover{display:flex;align-items:center;justify-content:center;height:120px;margin-bottom:6px;font-size:40px;background:#202124;border-radius:4px;overflow:hidden}\
+.cover img{max-width:100%;max-height:100%}\
+.note{font-size:12px;color:#9aa0a6}\
+video,img.picture{max-width:100%;max-height:75vh;background:#000}\
+audio{width:100%}\
+table{margin-top:16px;border-collapse:collapse}\
+td{padding:4px 12px 4px 0;vertical-align:top}\
+td:first-child{color:#9aa0a6}";
+
+/// # WebInterface
+///
+/// Generates the HTML Pages of the built-in Web Interface,
+/// which allows Browsers to browse and play the Library
+/// without a DLNA Renderer. The Pages show the same Folders,
+/// including the virtual ones, as the ContentDirectory does
+/// and play the Items through the regular Stream Paths.
+pub struct WebInterface<'a, 'b> {
+    cfg_handler: &'a ConfigurationHandler,
+    db_handler: &'b DatabaseManager,
+    client: IpAddr,
+    renderer: Option<&'a RendererConfiguration>,
+}
+
+impl<'a, 'b> WebInterface<'a, 'b> {
+    /// Creates a new WebInterface
+    ///
+    /// # Arguments
+    ///
+    /// * `cfg_handler` - Configuration Handler that provides any Configuration needed here
+    /// * `db_handler` - Database Handler that provides Media DB Access
+    /// * `client` - Address of the Browser, used to hide Shares it may not access
+    /// * `renderer` - Configuration matching the Browser, used to hide Content it may not see
+    pub fn new(
+        cfg_handler: &'a ConfigurationHandler,
+        db_handler: &'b DatabaseManager,
+        client: IpAddr,
+        renderer: Option<&'a RendererConfiguration>,
+    ) -> WebInterface<'a, 'b> {
+        WebInterface {
+            cfg_handler,
+            db_handler,
+            client,
+            renderer,
+        }
+    }
+
+    /// Generates the Page listing the Content of a Folder. The Id 0
+    /// is the Root holding the Shares. Returns None if there is
+    /// no such Folder or the Browser may not access it.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Id of the Folder
+    pub fn get_folder_page(&self, id: u64) -> Option<String> {
+        let title = if id == 0 {
+            self.cfg_handler.server_configuration.server_name.clone()
+        } else {
+            let folder = self.db_handler.get_folder_direct(id, &self.get_viewer()).ok()?;
+
+            if !self.is_folder_visible(&folder) {
+                return None;
+            }
+
+            folder.title
+        };
+
+        let mut folders: Vec<Folder> = self.db_handler.get_folder_from_parent(id, &self.get_viewer());
+        folders.retain(|folder| self.is_folder_visible(folder));
+        folders.sort_by_key(|folder| folder.title.to_lowercase());
+
+        let mut items: Vec<Item> = self.db_handler.get_items_from_parent(id, &self.get_viewer());
+        items.retain(|item| self.is_item_visible(item));
+
+        if !self.db_handler.is_ordered_folder(id) {
+            items.sort_by(|a, b| a.compare(b));
+        }
+
+        let mut content = String::from("<ul class=\"grid\">");
+
+        for folder in &folders {
+            content.push_str(&format!(
+                "<li><a href=\"/web/folder/{}\"><div class=\"cover\">&#128193;</div>{}<div class=\"note\">{} Elements</div></a></li>",
+                folder.id,
+                escape_html(&folder.title),
+                folder.element_count
+            ));
+        }
+
+        for item in &items {
+            content.push_str(&format!(
+                "<li><a href=\"/web/item/{}\"><div class=\"cover\">{}</div>{}<div class=\"note\">{}</div></a></li>",
+                item.id,
+                get_cover(item),
+                escape_html(&get_title(item)),
+                escape_html(&get_summary(item))
+            ));
+        }
+
+        content.push_str("</ul>");
+
+        if folders.is_empty() && items.is_empty() {
+            content = String::from("<p class=\"note\">This Folder is empty.</p>");
+        }
+
+        Some(self.get_page(&title, &self.get_breadcrumbs(id, false), &content))
+    }
+
+    /// Generates the Page playing an Item and showing its Meta Data.
+    /// Returns None if there is no such Item or the Browser may not
+    /// access it.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Id of the Item
+    pub fn get_item_page(&self, id: u64) -> Option<String> {
+        let item = self.db_handler.get_item_direct(id).ok()?;
+
+        if !self.is_item_visible(&item) {
+            return None;
+        }
+
+        let title = get_title(&item);
+
+        let player = match item.media_type {
+            MediaType::VIDEO => format!(
+                "<video controls autoplay preload=\"metadata\" src=\"/stream/{}\"{}></video>",
+                item.id,
+                if item.thumbnail.is_available() {
+                    format!(" poster=\"/thumbnail/{}\"", item.id)
+                } else {
+                    String::new()
+                }
+            ),
+            MediaType::AUDIO => format!(
+                "{}<audio controls autoplay preload=\"metadata\" src=\"/stream/{}\"></audio>",
+                if item.thumbnail.is_available() {
+                    format!("<p><img class=\"picture\" src=\"/thumbnail/{}\" alt=\"\"></p>", item.id)
+                } else {
+                    String::new()
+                },
+                item.id
+            ),
+            MediaType::PICTURE => {
+                // Scaled Pictures are JPEGs every Browser can display
+                let source = match imagescaler::get_profiles(&item).last() {
+                    Some(profile) => format!("/image/{}/{}", item.id, profile.get_name()),
+                    None => format!("/stream/{}", item.id),
+                };
+
+                format!(
+                    "<img class=\"picture\" src=\"{}\" alt=\"{}\">",
+                    source,
+                    escape_html(&title)
+                )
+            }
+            MediaType::UNKNOWN => String::new(),
+        };
+
+        let mut details: Vec<(&str, String)> = vec![
+            ("Title", item.meta_data.title.clone()),
+            ("Artist", item.meta_data.artists.join(", ")),
+            ("Album", item.meta_data.album.clone()),
+            ("Genre", item.meta_data.genres.join(", ")),
+            ("Date", item.meta_data.date.clone()),
+            ("Description", item.meta_data.description.clone()),
+            ("Duration", get_duration(&item)),
+            ("Resolution", match item.get_display_size() {
+                Some((width, height)) => format!("{} x {}", width, height),
+                None => String::new(),
+            }),
+            ("Camera", format!("{} {}", item.meta_data.camera_make, item.meta_data.camera_model)),
+            ("Type", item.get_mime_type()),
+            ("Size", format_size(item.file_size)),
+            (
+                "File",
+                format!("{}.{}", item.meta_data.file_name, item.meta_data.file_extension),
+            ),
+        ];
+
+        details.retain(|detail| !detail.1.trim().is_empty());
+
+        let mut content = format!("<div>{}</div><table>", player);
+
+        for (name, value) in details {
+            content.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td></tr>",
+                name,
+                escape_html(&value)
+            ));
+        }
+
+        content.push_str(&format!(
+            "</table><p><a href=\"/stream/{}\" download>Download</a></p>",
+            item.id
+        ));
+
+        Some(self.get_page(
+            &title,
+            &self.get_breadcrumbs(item.parent_id, true),
+            &content,
+        ))
+    }
+
+    /// Generates the Navigation Links from the Root down to a Folder
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Id of the Folder
+    /// * `link_last` - Link the Folder itself as well
+    fn get_breadcrumbs(&self, id: u64, link_last: bool) -> String {
+        let mut folders: Vec<Folder> = Vec::new();
+        let mut current = id;
+
+        while current != 0 && folders.len() < MAX_BREADCRUMBS {
+            match self.db_handler.get_folder_direct(current, &self.get_viewer()) {
+                Ok(folder) => {
+                    current = folder.parent_id;
+                    folders.push(folder);
+                }
+                Err(_) => break,
+            }
+        }
+
+        let mut links = vec![String::from("<a href=\"/web/\">Home</a>")];
+
+        for (index, folder) in folders.iter().rev().enumerate() {
+            if index + 1 == folders.len() && !link_last {
+                links.push(escape_html(&folder.title));
+            } else {
+                links.push(format!(
+                    "<a href=\"/web/folder/{}\">{}</a>",
+                    folder.id,
+                    escape_html(&folder.title)
+                ));
+            }
+        }
+
+        links.join(" / ")
+    }
+
+    /// Checks if the Folder is shown to the Browser according to the
+    /// Access Rules of its Share and the matching Renderer Configuration,
+    /// including its Schedule.
+    ///
+    /// # Arguments
+    ///
+    /// * `folder` - The Folder to check
+    fn is_folder_visible(&self, folder: &Folder) -> bool {
+        self.cfg_handler.server_configuration.is_path_allowed(&folder.path, &self.client) &&
+            self.renderer.is_none_or(|renderer| {
+                renderer.is_folder_visible(&folder.path) && !renderer.is_hidden_by_schedule(&Local::now())
+            })
+    }
+
+    /// Returns the Viewer the Played Containers are generated for,
+    /// which sees the Items shown to the Browser and its Bookmarks
+    fn get_viewer(&self) -> Viewer<'_> {
+        Viewer {
+            is_visible: Box::new(move |item| self.is_item_visible(item)),
+            bookmark_key: self.renderer
+                .map(|renderer| self.cfg_handler.server_configuration.get_bookmark_key(renderer)),
+        }
+    }
+
+    /// Checks if the Item is shown to the Browser according to the
+    /// Access Rules of its Share and the matching Renderer Configuration,
+    /// including its Schedule.
+    ///
+    /// # Arguments
+    ///
+    /// * `item` - The Item to check
+    fn is_item_visible(&self, item: &Item) -> bool {
+        self.cfg_handler.server_configuration.is_path_allowed(&item.file_path, &self.client) &&
+            self.renderer.is_none_or(|renderer| {
+                renderer.is_item_visible(item) && !renderer.is_hidden_by_schedule(&Local::now())
+            })
+    }
+
+    /// Wraps the Content into a complete HTML Document
+    ///
+    /// # Arguments
+    ///
+    /// * `title` - Title of the Page
+    /// * `navigation` - Navigation Links shown on Top
+    /// * `content` - Content of the Page
+    fn get_page(&self, title: &str, navigation: &str, content: &str) -> String {
+        format!(
+            "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\">\
+             <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\
+             <title>{} - {}</title><link rel=\"icon\" href=\"/files/images/icon.png\">\
+             <style>{}</style></head><body><header>{}</header><main><h1>{}</h1>{}</main></body></html>",
+            escape_html(title),
+            escape_html(&self.cfg_handler.server_configuration.server_name),
+            STYLE,
+            navigation,
+            escape_html(title),
+            content
+        )
+    }
+}
+
+/// Returns the Title to display for an Item: the Title from its
+/// Meta Data or its File Name if there is none.
+///
+/// # Arguments
+///
+/// * `item` - The Item
+fn get_title(item: &Item) -> String {
+    if item.meta_data.title.trim().is_empty() {
+        item.meta_data.file_name.clone()
+    } else {
+        item.meta_data.title.clone()
+    }
+}
+
+/// Returns a short Description of an Item shown below its Title
+///
+/// # Arguments
+///
+/// * `item` - The Item
+fn get_summary(item: &Item) -> String {
+    match item.media_type {
+        MediaType::AUDIO => {
+            let duration = get_duration(item);
+
+            if item.meta_data.artists.is_empty() {
+                duration
+            } else {
+                format!("{} {}", item.meta_data.artists.join(", "), duration)
+            }
+        }
+        MediaType::VIDEO => get_duration(item),
+        MediaType::PICTURE => item.meta_data.date.clone(),
+        MediaType::UNKNOWN => String::new(),
+    }
+}
+
+/// Returns the Preview of an Item: its Thumbnail, a scaled Picture
+/// or a Symbol for its Media Type.
+///
+/// # Arguments
+///
+/// * `item` - The Item
+fn get_cover(item: &Item) -> String {
+    if item.media_type == MediaType::PICTURE && item.get_display_size().is_some() {
+        return format!("<img loading=\"lazy\" src=\"/image/{}/JPEG_TN\" alt=\"\">", item.id);
+    }
+
+    if item.thumbnail.is_available() {
+        return format!("<img loading=\"lazy\" src=\"/thumbnail/{}\" alt=\"\">", item.id);
+    }
+
+    String::from(match item.media_type {
+        MediaType::AUDIO => "&#127925;",
+        MediaType::VIDEO => "&#127916;",
+        MediaType::PICTURE => "&#128247;",
+        MediaType::UNKNOWN => "&#128196;",
+    })
+}
+
+/// Formats the Play Time of an Item as "M:SS" or "H:MM:SS"
+///
+/// # Arguments
+///
+/// * `item` - The Item
+fn get_duration(item: &Item) -> String {
+    let seconds = match item.get_duration_seconds() {
+        Some(value) => value.round() as u64,
+        None => return String::new(),
+    };
+
+    if seconds >= 3600 {
+        format!("{}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+    } else {
+        format!("{}:{:02}", seconds / 60, seconds % 60)
+    }
+}
+
+/// Formats a Number of Bytes for Humans
+///
+/// # Arguments
+///
+/// * `size` - Number of Bytes
+fn format_size(size: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = size as f64;
+    let mut unit = 0;
+
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} {}", size, UNITS[0])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// Escapes the Characters with a special Meaning in HTML
+///
+/// # Arguments
+///
+/// * `value` - Text to escape
+fn escape_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}